@@ -0,0 +1,200 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use colored::Colorize;
+use rayon::prelude::*;
+
+use playlist_formatter::options::ParseOptions;
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::types::PlaylistType;
+use playlist_formatter::utils;
+
+use crate::config::{SaveSettings, UserConfig};
+use crate::progress;
+
+/// Result of processing a single input file.
+#[derive(Debug)]
+pub enum BatchStatus {
+    Converted(PathBuf),
+    Skipped(String),
+    Failed(String),
+}
+
+impl fmt::Display for BatchStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BatchStatus::Converted(_) => write!(f, "converted"),
+            BatchStatus::Skipped(_) => write!(f, "skipped"),
+            BatchStatus::Failed(_) => write!(f, "failed"),
+        }
+    }
+}
+
+/// Format all given playlist files in parallel and print a summary.
+///
+/// Directories are expanded to the playlist files they contain.
+/// Returns an error if any of the files failed.
+/// Shows a progress bar while converting unless in quiet mode or not running in a terminal.
+pub fn convert_files(
+    inputs: &[String],
+    settings: &SaveSettings,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+    quiet: bool,
+) -> Result<()> {
+    let files = collect_playlist_files(inputs)?;
+    if files.is_empty() {
+        anyhow::bail!("No playlist files found");
+    }
+    tracing::info!("Processing {} files", files.len());
+
+    let progress_bar = progress::bar(files.len(), quiet);
+    let results: Vec<(PathBuf, BatchStatus)> = files
+        .into_par_iter()
+        .map(|file| {
+            let status = convert_file(&file, settings, user_config, parse_options);
+            progress_bar.set_message(file.file_name().unwrap_or_default().to_string_lossy().to_string());
+            progress_bar.inc(1);
+            (file, status)
+        })
+        .collect();
+    progress_bar.finish_and_clear();
+
+    print_summary(&results);
+
+    let failed = results
+        .iter()
+        .filter(|(_, status)| matches!(status, BatchStatus::Failed(_)))
+        .count();
+    if failed > 0 {
+        anyhow::bail!("{failed} file(s) failed");
+    }
+    Ok(())
+}
+
+/// Expand inputs to a sorted list of playlist files.
+fn collect_playlist_files(inputs: &[String]) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        let path = Path::new(input.trim());
+        if path.is_dir() {
+            for entry in std::fs::read_dir(path)? {
+                let entry_path = entry?.path();
+                if entry_path.is_file() && utils::playlist_format(&entry_path).is_ok() {
+                    files.push(entry_path);
+                }
+            }
+        } else if path.is_file() {
+            files.push(path.to_path_buf());
+        } else {
+            anyhow::bail!("File does not exist or is not accessible: '{}'", path.display());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+fn convert_file(
+    file: &Path,
+    settings: &SaveSettings,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+) -> BatchStatus {
+    let _span = tracing::debug_span!("convert", file = %file.display()).entered();
+    let playlist = match Playlist::new_with_options(file, parse_options) {
+        Ok(playlist) => playlist,
+        Err(error) => return BatchStatus::Failed(error.to_string()),
+    };
+    if playlist.playlist_type == PlaylistType::Formatted {
+        return BatchStatus::Skipped("already formatted".to_string());
+    }
+    if let Some(output_path) = user_config.up_to_date_output(&playlist, settings) {
+        tracing::debug!("Up to date: {}", output_path.display());
+        return BatchStatus::Skipped("up to date".to_string());
+    }
+    let output_path = user_config.resolve_output_path(&playlist, None, settings);
+    if output_path.is_file() && !settings.force {
+        return BatchStatus::Skipped(format!("output exists: {}", output_path.display()));
+    }
+    match settings.save(&playlist, &output_path) {
+        Ok(()) => BatchStatus::Converted(output_path),
+        Err(error) => BatchStatus::Failed(error.to_string()),
+    }
+}
+
+fn print_summary(results: &[(PathBuf, BatchStatus)]) {
+    let name_width = results
+        .iter()
+        .map(|(file, _)| file.display().to_string().chars().count())
+        .max()
+        .unwrap_or(0);
+
+    println!();
+    for (file, status) in results {
+        let (status_text, details) = match status {
+            BatchStatus::Converted(path) => (status.to_string().green(), path.display().to_string()),
+            BatchStatus::Skipped(reason) => (status.to_string().yellow(), reason.clone()),
+            BatchStatus::Failed(reason) => (status.to_string().red(), reason.clone()),
+        };
+        println!("{:<name_width$}   {:<9}   {}", file.display(), status_text, details);
+    }
+
+    let count = |f: fn(&BatchStatus) -> bool| results.iter().filter(|(_, status)| f(status)).count();
+    println!(
+        "\nConverted: {}, Skipped: {}, Failed: {}",
+        count(|s| matches!(s, BatchStatus::Converted(_))).to_string().green(),
+        count(|s| matches!(s, BatchStatus::Skipped(_))).to_string().yellow(),
+        count(|s| matches!(s, BatchStatus::Failed(_))).to_string().red(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn convert_directory_with_skipped_and_failed_files() {
+        let dir = std::env::temp_dir().join(format!("playfmt-batch-{}", std::process::id()));
+        let input_dir = dir.join("input");
+        std::fs::create_dir_all(&input_dir).unwrap();
+        let files_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("files");
+        std::fs::copy(files_dir.join("serato.csv"), input_dir.join("serato.csv")).unwrap();
+        std::fs::copy(files_dir.join("formatted.csv"), input_dir.join("formatted.csv")).unwrap();
+        std::fs::write(input_dir.join("broken.csv"), "not,a,playlist\n").unwrap();
+        std::fs::write(input_dir.join("notes.md"), "not a playlist file").unwrap();
+
+        let user_config = UserConfig {
+            save_dir: Some(dir.join("output")),
+            ..Default::default()
+        };
+        let settings = SaveSettings::default();
+        let parse_options = ParseOptions::default();
+        let inputs = [input_dir.to_string_lossy().to_string()];
+
+        let files = collect_playlist_files(&inputs).unwrap();
+        assert_eq!(files.len(), 3);
+        assert!(!files.iter().any(|file| file.ends_with("notes.md")));
+
+        assert!(matches!(
+            convert_file(
+                &input_dir.join("formatted.csv"),
+                &settings,
+                &user_config,
+                &parse_options
+            ),
+            BatchStatus::Skipped(_)
+        ));
+        assert!(matches!(
+            convert_file(&input_dir.join("broken.csv"), &settings, &user_config, &parse_options),
+            BatchStatus::Failed(_)
+        ));
+
+        let error = convert_files(&inputs, &settings, &user_config, &parse_options, true).unwrap_err();
+        assert_eq!(error.to_string(), "1 file(s) failed");
+        let converted = std::fs::read_dir(dir.join("output")).unwrap().count();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(converted, 1);
+    }
+}
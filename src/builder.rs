@@ -0,0 +1,333 @@
+use std::path::PathBuf;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use chrono_tz::Tz;
+
+use super::normalize;
+use super::playlist::Playlist;
+use super::track::Track;
+use super::types::{FileFormat, PlaylistType};
+
+/// Build a playlist programmatically instead of reading it from a file.
+///
+/// ```
+/// use playlist_formatter::PlaylistBuilder;
+/// use playlist_formatter::track::Track;
+///
+/// let playlist = PlaylistBuilder::new("Friday")
+///     .track(Track::new("Artist".to_string(), "Title".to_string()))
+///     .build();
+/// assert_eq!(playlist.tracks.len(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PlaylistBuilder {
+    name: String,
+    date: Option<NaiveDateTime>,
+    file: Option<PathBuf>,
+    timezone: Option<Tz>,
+    tracks: Vec<Track>,
+}
+
+impl PlaylistBuilder {
+    /// Start building a playlist with the given name.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the playlist date. Defaults to the start time of the first track.
+    #[must_use]
+    pub const fn date(mut self, date: NaiveDateTime) -> Self {
+        self.date = Some(date);
+        self
+    }
+
+    /// Set the file path used as the base for the default output path.
+    #[must_use]
+    pub fn file(mut self, file: impl Into<PathBuf>) -> Self {
+        self.file = Some(file.into());
+        self
+    }
+
+    /// Set the time zone of the track times.
+    #[must_use]
+    pub const fn timezone(mut self, timezone: Tz) -> Self {
+        self.timezone = Some(timezone);
+        self
+    }
+
+    /// Add a track to the end of the playlist.
+    #[must_use]
+    pub fn track(mut self, track: Track) -> Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Add tracks to the end of the playlist.
+    #[must_use]
+    pub fn tracks(mut self, tracks: impl IntoIterator<Item = Track>) -> Self {
+        self.tracks.extend(tracks);
+        self
+    }
+
+    /// Add a track to the end of the playlist in place.
+    pub fn push(&mut self, track: Track) -> &mut Self {
+        self.tracks.push(track);
+        self
+    }
+
+    /// Create the playlist, computing the total duration and the column widths.
+    pub fn build(self) -> Playlist {
+        let date = self.date.or_else(|| self.tracks.iter().find_map(|t| t.start_time));
+        let file = self.file.unwrap_or_else(|| PathBuf::from(format!("{}.csv", self.name)));
+        let mut playlist = Playlist {
+            date,
+            file,
+            file_format: FileFormat::Csv,
+            name: self.name,
+            playlist_type: PlaylistType::Formatted,
+            tracks: self.tracks,
+            max_artist_length: 0,
+            max_title_length: 0,
+            max_playtime_length: 0,
+            timezone: self.timezone,
+            total_duration: None,
+        };
+        playlist.update_totals();
+        playlist
+    }
+}
+
+/// Build a track with any of the optional fields set.
+///
+/// Optional fields accept either a value or an `Option`, so parsed values can be passed as is.
+///
+/// ```
+/// use playlist_formatter::track::Track;
+///
+/// let track = Track::builder().artist("Artist").title("Title").bpm(124.0).key("8A").build();
+/// assert_eq!(track.to_string(), "Artist - Title");
+/// assert_eq!(track.bpm, Some(124.0));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TrackBuilder {
+    track: Track,
+}
+
+impl Default for TrackBuilder {
+    fn default() -> Self {
+        Self {
+            track: Track::new(String::new(), String::new()),
+        }
+    }
+}
+
+impl TrackBuilder {
+    /// Start building a track with an empty artist and title.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn artist(mut self, artist: impl Into<String>) -> Self {
+        self.track.artist = normalize::nfc(artist.into());
+        self
+    }
+
+    #[must_use]
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.track.title = normalize::nfc(title.into());
+        self
+    }
+
+    #[must_use]
+    pub fn start_time(mut self, start_time: impl Into<Option<NaiveDateTime>>) -> Self {
+        self.track.start_time = start_time.into();
+        self
+    }
+
+    #[must_use]
+    pub fn end_time(mut self, end_time: impl Into<Option<NaiveDateTime>>) -> Self {
+        self.track.end_time = end_time.into();
+        self
+    }
+
+    #[must_use]
+    pub fn play_time(mut self, play_time: impl Into<Option<TimeDelta>>) -> Self {
+        self.track.play_time = play_time.into();
+        self
+    }
+
+    #[must_use]
+    pub fn bpm(mut self, bpm: impl Into<Option<f64>>) -> Self {
+        self.track.bpm = bpm.into();
+        self
+    }
+
+    #[must_use]
+    pub fn key(mut self, key: impl Into<String>) -> Self {
+        self.track.key = Some(key.into());
+        self
+    }
+
+    #[must_use]
+    pub fn genre(mut self, genre: impl Into<String>) -> Self {
+        self.track.genre = Some(genre.into());
+        self
+    }
+
+    #[must_use]
+    pub fn album(mut self, album: impl Into<String>) -> Self {
+        self.track.album = Some(album.into());
+        self
+    }
+
+    #[must_use]
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.track.label = Some(label.into());
+        self
+    }
+
+    #[must_use]
+    pub fn year(mut self, year: impl Into<Option<u16>>) -> Self {
+        self.track.year = year.into();
+        self
+    }
+
+    /// Set the star rating from 1 to 5.
+    #[must_use]
+    pub fn rating(mut self, rating: impl Into<Option<u8>>) -> Self {
+        self.track.rating = rating.into();
+        self
+    }
+
+    /// Set the full length of the audio file.
+    #[must_use]
+    pub fn length(mut self, length: impl Into<Option<TimeDelta>>) -> Self {
+        self.track.length = length.into();
+        self
+    }
+
+    #[must_use]
+    pub fn date_added(mut self, date_added: impl Into<Option<NaiveDate>>) -> Self {
+        self.track.date_added = date_added.into();
+        self
+    }
+
+    #[must_use]
+    pub fn deck(mut self, deck: impl Into<Option<u8>>) -> Self {
+        self.track.deck = deck.into();
+        self
+    }
+
+    #[must_use]
+    pub fn notes(mut self, notes: impl Into<String>) -> Self {
+        self.track.notes = Some(notes.into());
+        self
+    }
+
+    /// Set the DJ who played the track in a back-to-back set.
+    #[must_use]
+    pub fn dj(mut self, dj: impl Into<String>) -> Self {
+        self.track.dj = Some(dj.into());
+        self
+    }
+
+    /// Set the track number in the source playlist.
+    #[must_use]
+    pub fn number(mut self, number: impl Into<Option<usize>>) -> Self {
+        self.track.number = number.into();
+        self
+    }
+
+    /// Set the number of separate plays merged into the track. Defaults to one.
+    #[must_use]
+    pub const fn plays(mut self, plays: usize) -> Self {
+        self.track.plays = plays;
+        self
+    }
+
+    /// Add a value for an input column that is not otherwise used.
+    #[must_use]
+    pub fn extra(mut self, column: impl Into<String>, value: impl Into<String>) -> Self {
+        self.track.extras.insert(column.into(), value.into());
+        self
+    }
+
+    /// Add a track played on top of this track.
+    #[must_use]
+    pub fn mashup(mut self, mashup: Track) -> Self {
+        self.track.mashups.push(mashup);
+        self
+    }
+
+    pub fn build(self) -> Track {
+        self.track
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_playlist() {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 8)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        let mut builder = PlaylistBuilder::new("Friday").track(Track::new_with_time(
+            "Artist".to_string(),
+            "Title".to_string(),
+            Some(start),
+            None,
+            TimeDelta::try_minutes(5),
+        ));
+        builder.push(Track::new("Longer Artist".to_string(), "Song".to_string()) + TimeDelta::minutes(4));
+        let playlist = builder.build();
+        assert_eq!(playlist.name, "Friday");
+        assert_eq!(playlist.date, Some(start));
+        assert_eq!(playlist.file, PathBuf::from("Friday.csv"));
+        assert_eq!(playlist.total_duration, TimeDelta::try_minutes(9));
+        assert_eq!(playlist.max_artist_length, "Longer Artist".len());
+        assert_eq!(playlist.max_title_length, "Title".len());
+    }
+
+    #[test]
+    fn explicit_date() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0)
+            .unwrap();
+        let playlist = PlaylistBuilder::new("Empty").date(date).file("/tmp/empty.csv").build();
+        assert_eq!(playlist.date, Some(date));
+        assert_eq!(playlist.total_duration, None);
+        assert!(playlist.tracks.is_empty());
+    }
+
+    #[test]
+    fn build_track() {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 8)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        let track = Track::builder()
+            .artist("Artist")
+            .title("Title")
+            .start_time(start)
+            .end_time(None)
+            .play_time(TimeDelta::try_minutes(5))
+            .genre("House")
+            .extra("Comment", "opener")
+            .build();
+        assert_eq!(track.to_string(), "Artist - Title");
+        assert_eq!(track.start_time, Some(start));
+        assert_eq!(track.end_time, None);
+        assert_eq!(track.play_time, TimeDelta::try_minutes(5));
+        assert_eq!(track.genre.as_deref(), Some("House"));
+        assert_eq!(track.extras.get("Comment").map(String::as_str), Some("opener"));
+        assert_eq!(track.plays, 1);
+    }
+}
@@ -1,28 +1,68 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use chrono::{NaiveTime, TimeDelta};
+use chrono_tz::Tz;
+use clap::{Parser, Subcommand};
+use clap_complete::Shell;
+use serde::Deserialize;
 use strum_macros::Display;
+use tracing::level_filters::LevelFilter;
+
+use playlist_formatter::filter::TrackFilter;
+use playlist_formatter::options::{FormatOptions, ParseOptions};
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::stats::DEFAULT_BPM_JUMP;
+use playlist_formatter::types::{
+    DateFormat, DedupePolicy, DjAssignment, DurationFormat, FooterField, InputFormat, KeyNotation, MergeStrategy,
+    OutputFormat, SearchLink, SortKey, SplitPoint, TimeFormat, TimePosition, TrackRename, TransliterateMode,
+};
+use playlist_formatter::utils;
 
-use playlist_formatter::types::OutputFormat;
+use crate::config::{SaveSettings, UserConfig};
+use crate::list::ListSort;
 
 /// DJ playlist formatting utility
-#[derive(Parser)]
+#[derive(Parser, Default)]
 #[command(
     author,
     version,
     about,
     long_about = "DJ playlist formatting utility. Reads raw playlist files and creates a nicely formatted version.",
-    arg_required_else_help = true
+    arg_required_else_help = true,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 pub struct Args {
-    /// Playlist file to process
-    pub file: String,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Playlist file to process, or an HTTP(S) URL with the `net` feature
+    #[arg(required = true)]
+    pub file: Option<String>,
 
     /// Optional output path to save playlist to
     output: Option<String>,
 
     /// Log level
-    #[arg(value_enum, short, long, value_name = "LEVEL")]
+    #[arg(value_enum, short, long, value_name = "LEVEL", global = true)]
     pub log: Option<Level>,
 
+    /// Increase log verbosity: -v for debug and -vv for trace
+    #[arg(short, long, action = clap::ArgAction::Count, global = true, conflicts_with = "quiet")]
+    pub verbose: u8,
+
+    /// Config file to use instead of the default config location
+    #[arg(long, value_name = "CONFIG_FILE", global = true)]
+    pub config: Option<PathBuf>,
+
+    /// Error output format
+    #[arg(value_enum, long, value_name = "FORMAT", global = true)]
+    pub error_format: Option<ErrorFormat>,
+
+    /// Log output format
+    #[arg(value_enum, long, value_name = "FORMAT", global = true)]
+    pub log_format: Option<LogFormat>,
+
     /// Output format
     #[arg(value_enum, short = 't', long = "type", value_name = "OUTPUT_FORMAT")]
     pub output_format: Option<OutputFormat>,
@@ -32,9 +72,25 @@ pub struct Args {
     default: bool,
 
     /// Overwrite an existing output file
-    #[arg(short, long)]
+    #[arg(short, long, conflicts_with = "no_clobber")]
     force: bool,
 
+    /// Don't overwrite an existing output file even if the config file enables it
+    #[arg(long, overrides_with = "force")]
+    no_force: bool,
+
+    /// Save with a numbered file name instead of overwriting an existing file
+    #[arg(long)]
+    no_clobber: bool,
+
+    /// Back up an existing output file before overwriting it, keeping N backups
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1", requires = "force")]
+    backup: Option<usize>,
+
+    /// Append to an existing CSV or XLSX archive file instead of creating a new file
+    #[arg(long, conflicts_with_all = ["force", "no_clobber"])]
+    append: bool,
+
     /// Use basic print formatting style
     #[arg(short, long, conflicts_with = "numbered")]
     basic: bool,
@@ -43,9 +99,224 @@ pub struct Args {
     #[arg(short, long, conflicts_with = "basic")]
     numbered: bool,
 
-    /// Don't print playlist
+    /// Don't print playlist and only log warnings and errors
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Save to year and month subdirectories based on the playlist date
     #[arg(short, long)]
-    quiet: bool,
+    organize: bool,
+
+    /// Review and edit tracks interactively before saving
+    #[arg(short, long)]
+    interactive: bool,
+
+    /// Read the input as this format instead of detecting it from the file
+    #[arg(value_enum, long, value_name = "FORMAT", global = true)]
+    input_format: Option<InputFormat>,
+
+    /// How duplicate tracks are handled
+    #[arg(value_enum, long, value_name = "POLICY", global = true)]
+    dedupe: Option<DedupePolicy>,
+
+    /// Merge a track played again within this time after it ended, for example 60s
+    #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration, global = true)]
+    merge_reloads: Option<TimeDelta>,
+
+    /// Drop tracks that were played for a shorter time, for example 30s or 1m
+    #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration, global = true)]
+    min_playtime: Option<TimeDelta>,
+
+    /// Only keep tracks matching the filter, for example 'artist~="guetta"' or 'title!=jingle'
+    #[arg(
+        long,
+        value_name = "EXPR",
+        long_help = "Only keep tracks matching the filter. Can be given multiple times.\n\
+                     Format is <FIELD><OPERATOR><VALUE> where field is artist, title, or track (either one),\n\
+                     and operator is = (contains), ~= (regex), != (does not contain), or !~= (no regex match)"
+    )]
+    filter: Vec<TrackFilter>,
+
+    /// Sort tracks by the given field instead of playing order
+    #[arg(value_enum, long, value_name = "FIELD")]
+    sort: Option<SortKey>,
+
+    /// Reverse track order, or the sort order when used with --sort
+    #[arg(long)]
+    reverse: bool,
+
+    /// Standardize featuring credits, remix brackets, and whitespace in artists and titles
+    #[arg(long)]
+    normalize: bool,
+
+    /// Remove emoji and control characters from artists and titles
+    #[arg(long, global = true)]
+    strip_emoji: bool,
+
+    /// Convert all-caps and all-lowercase artists and titles to title case
+    #[arg(long)]
+    fix_case: bool,
+
+    /// Fail on invalid timestamps, short rows, and unknown columns instead of skipping them
+    #[arg(long, global = true)]
+    strict: bool,
+
+    /// Collect tracks given with "w/" in the title or played on top of another track as mashups
+    #[arg(long, global = true)]
+    mashups: bool,
+
+    /// Add a romanized form for artist names in a non-Latin script, or replace the name with it
+    #[arg(
+        value_enum,
+        long,
+        value_name = "MODE",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "append"
+    )]
+    transliterate: Option<TransliterateMode>,
+
+    /// Remove title suffixes such as "(Original Mix)", optionally with a comma-separated list of suffixes
+    #[arg(
+        long,
+        value_name = "SUFFIXES",
+        num_args = 0..,
+        require_equals = true,
+        value_delimiter = ',',
+        long_help = "Remove title suffixes such as \"(Original Mix)\", \"(Clean)\" and \"(Dirty)\".\n\
+                     Give a comma-separated list to use other suffixes, for example --strip-suffixes=\"Original Mix,Extended Mix\".\n\
+                     Without a list, uses the strip_suffixes config option or the default suffixes."
+    )]
+    strip_suffixes: Option<Vec<String>>,
+
+    /// Change artist and title of a track, for example '3:Artist - Title'
+    #[arg(long, value_name = "N:ARTIST - TITLE")]
+    rename_track: Vec<TrackRename>,
+
+    /// Remove track with the given number
+    #[arg(long, value_name = "N")]
+    remove_track: Vec<usize>,
+
+    /// Add a DJ column for back-to-back sets from a time range or deck, for example 'A=22:00-23:00' or 'B=2'
+    #[arg(long, value_name = "NAME=RANGE|DECK")]
+    dj: Vec<DjAssignment>,
+
+    /// Number of the first track, also adds a track number column to saved files
+    #[arg(long, value_name = "N")]
+    start_number: Option<usize>,
+
+    /// Keep the track numbers from the "#" column of the source playlist instead of renumbering
+    #[arg(long)]
+    original_numbers: bool,
+
+    /// Leave out input columns that are not otherwise used from saved files
+    #[arg(long)]
+    no_extras: bool,
+
+    /// Show musical keys in standard (F#m) or Camelot (11A) notation
+    #[arg(value_enum, long, value_name = "NOTATION")]
+    key_notation: Option<KeyNotation>,
+
+    /// Mark tracks with a key incompatible with the previous track in pretty output
+    #[arg(long)]
+    harmonic: bool,
+
+    /// Mark tempo changes over this many BPM from the previous track in pretty output
+    #[arg(
+        long,
+        value_name = "BPM",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "8"
+    )]
+    bpm_jump: Option<f64>,
+
+    /// Show how long each track overlapped the previous one, highlighting overlaps longer than this
+    #[arg(
+        long,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "5m",
+        value_parser = utils::parse_duration
+    )]
+    overlap: Option<TimeDelta>,
+
+    /// Show the Serato deck column in pretty output
+    #[arg(long)]
+    deck: bool,
+
+    /// Add a sheet with set statistics to XLSX output
+    #[arg(long)]
+    stats_sheet: bool,
+
+    /// Add an elapsed time column counted from the start of the set
+    #[arg(long)]
+    elapsed: bool,
+
+    /// Show a start time column in pretty output
+    #[arg(long)]
+    show_times: bool,
+
+    /// Link track names to a web search: beatport, youtube, discogs, or a URL template containing {query}
+    #[arg(long, value_name = "SEARCH", num_args = 0..=1, require_equals = true)]
+    links: Option<Option<SearchLink>>,
+
+    /// Maximum width of pretty output, defaults to the terminal width
+    #[arg(long, value_name = "COLUMNS")]
+    max_width: Option<usize>,
+
+    /// Comma-separated totals to show after the tracks in pretty output and saved files
+    #[arg(value_enum, long, value_name = "FIELDS", value_delimiter = ',')]
+    footer: Vec<FooterField>,
+
+    /// Leave out the totals row from pretty output and saved files
+    #[arg(long, conflicts_with = "footer")]
+    no_footer: bool,
+
+    /// Round displayed and saved track playtimes, for example 15s, 30s or 1m
+    #[arg(long, value_name = "STEP", value_parser = utils::parse_duration)]
+    round_playtime: Option<TimeDelta>,
+
+    /// How playtimes and total durations are formatted
+    #[arg(value_enum, long, value_name = "FORMAT")]
+    duration_format: Option<DurationFormat>,
+
+    /// Format for start and end times in saved files: "default", "iso", or a strftime pattern like "%H:%M:%S"
+    #[arg(long, value_name = "FORMAT")]
+    time_format: Option<TimeFormat>,
+
+    /// Format for the playlist date: "default", "iso", or a strftime pattern like "%d.%m.%Y"
+    #[arg(long, value_name = "FORMAT")]
+    date_format: Option<DateFormat>,
+
+    /// Shift elapsed times by [-]MM:SS for recordings that started before or after the first track
+    #[arg(long, value_name = "OFFSET", allow_hyphen_values = true, value_parser = utils::parse_offset)]
+    cue_offset: Option<TimeDelta>,
+
+    /// Show times in the given time zone, for example "Europe/London"
+    #[arg(long, value_name = "TZ")]
+    timezone: Option<Tz>,
+
+    /// Compute track start times from playtimes, starting from the given time, for example "22:00"
+    #[arg(long, value_name = "TIME", value_parser = utils::parse_clock_time)]
+    set_start: Option<NaiveTime>,
+
+    /// Drop the first N tracks
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    skip_first: usize,
+
+    /// Drop the last N tracks
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    skip_last: usize,
+
+    /// Only keep tracks starting at or after this clock time (22:30) or elapsed time (+1:30)
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    from: Option<TimePosition>,
+
+    /// Only keep tracks starting before this clock time (23:45) or elapsed time (+2:00)
+    #[arg(long, value_name = "TIME", allow_hyphen_values = true)]
+    to: Option<TimePosition>,
 
     /// Save formatted playlist to file
     #[arg(
@@ -58,6 +329,218 @@ pub struct Args {
     save: Option<Option<String>>,
 }
 
+/// Subcommands
+#[derive(Subcommand)]
+pub enum Command {
+    /// Check playlist for issues without writing anything
+    #[command(
+        long_about = "Check playlist for issues without writing anything. Exits with a non-zero code if problems are found."
+    )]
+    Validate {
+        /// Playlist file to validate
+        file: String,
+
+        /// Report gaps between tracks longer than this, for example 30s or 2m
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration, default_value = "60s")]
+        max_gap: TimeDelta,
+    },
+    /// Combine multiple playlists into one
+    Merge {
+        /// Playlist files to combine
+        #[arg(required = true, num_args = 2..)]
+        files: Vec<String>,
+
+        /// Output path to save combined playlist to
+        #[arg(short, long, value_name = "OUTPUT_FILE")]
+        output: Option<String>,
+
+        /// Name for the combined playlist
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// How to combine the tracks
+        #[arg(value_enum, short, long, default_value_t)]
+        strategy: MergeStrategy,
+
+        /// Output format
+        #[arg(value_enum, short = 't', long = "type", value_name = "OUTPUT_FORMAT")]
+        output_format: Option<OutputFormat>,
+
+        /// Overwrite an existing output file
+        #[arg(short, long)]
+        force: bool,
+
+        /// Don't overwrite an existing output file even if the config file enables it
+        #[arg(long, overrides_with = "force")]
+        no_force: bool,
+    },
+    /// Show the tracks added, removed, moved, and changed between two versions of a playlist
+    #[command(
+        long_about = "Show the tracks added, removed, moved, and changed between two versions of a playlist. Exits with a non-zero code if the playlists differ."
+    )]
+    Diff {
+        /// Old playlist file
+        old: String,
+
+        /// New playlist file
+        new: String,
+
+        /// Print differences as JSON
+        #[arg(short, long)]
+        json: bool,
+    },
+    /// Convert playlist to another format without any normalization
+    Convert {
+        /// Playlist file to convert
+        input: String,
+
+        /// Output file path
+        output: String,
+
+        /// Input format. Detected from the file if not given
+        #[arg(value_enum, long, value_name = "INPUT_FORMAT")]
+        from: Option<InputFormat>,
+
+        /// Output format. Detected from the output file extension if not given
+        #[arg(value_enum, long, value_name = "OUTPUT_FORMAT")]
+        to: Option<OutputFormat>,
+
+        /// Overwrite an existing output file
+        #[arg(short, long)]
+        force: bool,
+
+        /// Don't overwrite an existing output file even if the config file enables it
+        #[arg(long, overrides_with = "force")]
+        no_force: bool,
+    },
+    /// Check the environment for common setup problems
+    Doctor,
+    /// Format multiple playlist files in parallel
+    Batch {
+        /// Playlist files or directories to process
+        #[arg(required = true)]
+        inputs: Vec<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+    /// Edit playlist tracks in a text editor before saving
+    Edit {
+        /// Playlist file to edit
+        file: String,
+
+        /// Optional output path to save playlist to
+        output: Option<String>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+    /// Watch directory for new playlist exports and format them automatically
+    Watch {
+        /// Directory to watch
+        dir: String,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+    /// Generate shell completion script
+    Completions {
+        /// Shell to generate completions for
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// List formatted playlists in the save directory
+    List {
+        /// Directory to scan instead of the save directory
+        dir: Option<PathBuf>,
+
+        /// Column to sort by
+        #[arg(value_enum, short, long, default_value_t)]
+        sort: ListSort,
+
+        /// Reverse the sort order
+        #[arg(short, long)]
+        reverse: bool,
+    },
+    /// Generate roff man page
+    #[command(hide = true)]
+    Mangen {
+        /// Directory to write the man page to. Prints to stdout if not given
+        #[arg(short, long, value_name = "DIR")]
+        out_dir: Option<PathBuf>,
+    },
+    /// Print per-set analytics for a playlist
+    Stats {
+        /// Playlist file to analyze
+        file: String,
+
+        /// Print statistics as JSON
+        #[arg(short, long)]
+        json: bool,
+
+        /// BPM change between consecutive tracks that is flagged as a tempo jump
+        #[arg(long, value_name = "BPM", default_value_t = DEFAULT_BPM_JUMP)]
+        bpm_jump: f64,
+
+        /// Report gaps between tracks longer than this, for example 30s or 2m
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration, default_value = "60s")]
+        max_gap: TimeDelta,
+
+        /// Report tracks overlapping the previous track longer than this, for example 2m
+        #[arg(long, value_name = "DURATION", value_parser = utils::parse_duration, default_value = "5m")]
+        max_overlap: TimeDelta,
+    },
+    /// Print the JSON Schema document for the JSON output format
+    Schema,
+    /// Split playlist into multiple parts
+    #[command(
+        long_about = "Split playlist into multiple parts. A split point is either a track number starting a new part, or an elapsed time such as 2:00:00, 1:30 or 90m."
+    )]
+    Split {
+        /// Playlist file to split
+        file: String,
+
+        /// Split points: track numbers or elapsed times
+        #[arg(required = true, num_args = 1.., value_name = "POINT")]
+        at: Vec<SplitPoint>,
+
+        #[command(flatten)]
+        save: SaveArgs,
+    },
+}
+
+/// Shared options for subcommands that save playlists
+#[derive(clap::Args, Debug, Clone, Default)]
+pub struct SaveArgs {
+    /// Output format
+    #[arg(value_enum, short = 't', long = "type", value_name = "OUTPUT_FORMAT")]
+    pub output_format: Option<OutputFormat>,
+
+    /// Use default save directory
+    #[arg(short, long)]
+    pub default: bool,
+
+    /// Overwrite existing output files
+    #[arg(short, long, conflicts_with = "no_clobber")]
+    pub force: bool,
+
+    /// Don't overwrite existing output files even if the config file enables it
+    #[arg(long, overrides_with = "force")]
+    pub no_force: bool,
+
+    /// Save with a numbered file name instead of overwriting an existing file
+    #[arg(long)]
+    pub no_clobber: bool,
+
+    /// Save to year and month subdirectories based on the playlist date
+    #[arg(short, long)]
+    pub organize: bool,
+
+    /// Back up an existing output file before overwriting it, keeping N backups
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "1", requires = "force")]
+    pub backup: Option<usize>,
+}
+
 /// Logging level
 #[derive(clap::ValueEnum, Clone, Debug, Display)]
 pub enum Level {
@@ -68,8 +551,29 @@ pub enum Level {
     Error,
 }
 
+/// Output format for error reporting
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Human-readable colored text
+    #[default]
+    Text,
+    /// Structured JSON object on stderr
+    Json,
+}
+
+/// Output format for log records
+#[derive(clap::ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Plain text lines
+    #[default]
+    Text,
+    /// One JSON object per line
+    Json,
+}
+
 /// Output formatting style for playlist printing
-#[derive(Default, Debug, Clone, PartialEq, Display)]
+#[derive(Default, Debug, Clone, PartialEq, Display, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum FormattingStyle {
     /// Basic formatting for sharing playlist text online
     Basic,
@@ -82,57 +586,270 @@ pub enum FormattingStyle {
 
 #[derive(Default, Debug, Clone)]
 pub struct CliConfig {
+    pub append: bool,
+    pub backup: Option<usize>,
     pub default: bool,
     pub force: bool,
+    pub from: Option<TimePosition>,
+    pub to: Option<TimePosition>,
+    pub interactive: bool,
+    pub skip_first: usize,
+    pub skip_last: usize,
+    pub start_number: Option<usize>,
+    pub original_numbers: bool,
+    pub include_extras: bool,
+    pub show_deck: bool,
+    pub stats_sheet: bool,
+    pub key_notation: Option<KeyNotation>,
+    pub show_elapsed: bool,
+    pub show_key_clashes: bool,
+    pub show_start_times: bool,
+    pub search_links: Option<SearchLink>,
+    pub bpm_jump: Option<f64>,
+    pub long_overlap: Option<TimeDelta>,
+    pub max_width: Option<usize>,
+    pub footer: Option<Vec<FooterField>>,
+    pub cue_offset: Option<TimeDelta>,
+    pub round_playtime: Option<TimeDelta>,
+    pub duration_format: DurationFormat,
+    pub time_format: TimeFormat,
+    pub date_format: DateFormat,
+    pub timezone: Option<Tz>,
+    pub set_start: Option<NaiveTime>,
+    pub filters: Vec<TrackFilter>,
+    pub normalize: bool,
+    pub fix_case: bool,
+    pub transliterate: Option<TransliterateMode>,
+    pub strip_suffixes: Option<Vec<String>>,
+    pub rename_tracks: Vec<TrackRename>,
+    pub remove_tracks: Vec<usize>,
+    pub djs: Vec<DjAssignment>,
+    pub sort: Option<SortKey>,
+    pub reverse: bool,
+    pub no_clobber: bool,
+    pub organize: bool,
     pub quiet: bool,
     pub save: bool,
     pub style: FormattingStyle,
     pub output_path: Option<String>,
     pub output_format: OutputFormat,
+    pub parse_options: ParseOptions,
+    pub user_config: UserConfig,
+}
+
+impl Args {
+    /// Get log level to use.
+    ///
+    /// An explicit `--log` level takes precedence over the verbosity flags.
+    pub fn log_level(&self) -> Level {
+        if let Some(level) = &self.log {
+            return level.clone();
+        }
+        match self.verbose {
+            0 if self.quiet => Level::Warn,
+            0 => Level::Info,
+            1 => Level::Debug,
+            _ => Level::Trace,
+        }
+    }
+
+    /// Get the options for parsing input playlists, shared by the main command and the subcommands.
+    pub fn parse_options(&self) -> ParseOptions {
+        ParseOptions {
+            input_format: self.input_format,
+            dedupe: self.dedupe.unwrap_or_default(),
+            min_playtime: self.min_playtime,
+            merge_reloads: self.merge_reloads,
+            strip_emoji: self.strip_emoji,
+            mashups: self.mashups,
+            strict: self.strict,
+            ..ParseOptions::default()
+        }
+    }
+
+    /// Get the single input file being processed, if any.
+    pub fn input_file(&self) -> Option<PathBuf> {
+        match &self.command {
+            None => self.file.as_deref(),
+            Some(
+                Command::Validate { file, .. }
+                | Command::Edit { file, .. }
+                | Command::Stats { file, .. }
+                | Command::Split { file, .. },
+            ) => Some(file.as_str()),
+            Some(Command::Convert { input, .. }) => Some(input.as_str()),
+            Some(Command::Watch { dir, .. }) => Some(dir.as_str()),
+            Some(_) => None,
+        }
+        .map(|file| PathBuf::from(file.trim()))
+    }
 }
 
 impl CliConfig {
-    /// Create config from command line args.
-    pub fn from_args(args: Args) -> Self {
+    /// Create config from command line args and user config file.
+    ///
+    /// Command line args take precedence over the user config.
+    pub fn from_args(args: Args, user_config: UserConfig) -> Self {
+        let parse_options = args.parse_options();
         let style = if args.basic {
             FormattingStyle::Basic
         } else if args.numbered {
             FormattingStyle::Numbered
         } else {
-            FormattingStyle::default()
+            user_config.style.clone().unwrap_or_default()
         };
-        log::debug!("Formatting style: {style}");
+        tracing::debug!("Formatting style: {style}");
 
-        let (save, output_path) = if args.save.is_some() {
-            log::debug!("Save option specified");
-            (true, args.save.unwrap())
+        let (save, output_path) = if let Some(save) = args.save {
+            tracing::debug!("Save option specified");
+            (true, save)
         } else if args.output.is_some() {
-            log::debug!("Output path specified");
+            tracing::debug!("Output path specified");
             (true, args.output)
         } else {
             (false, None)
         };
 
         CliConfig {
-            force: args.force,
+            append: args.append,
+            backup: args.backup,
+            force: user_config.force_or_default(args.force, args.no_force),
             default: args.default,
+            from: args.from,
+            to: args.to,
+            interactive: args.interactive,
+            skip_first: args.skip_first,
+            skip_last: args.skip_last,
+            start_number: args.start_number,
+            original_numbers: args.original_numbers,
+            include_extras: !args.no_extras,
+            show_deck: args.deck,
+            stats_sheet: args.stats_sheet,
+            key_notation: args.key_notation,
+            show_elapsed: args.elapsed,
+            show_key_clashes: args.harmonic,
+            show_start_times: args.show_times,
+            search_links: args
+                .links
+                .map(|search| search.or_else(|| user_config.search_link.clone()).unwrap_or_default()),
+            bpm_jump: args.bpm_jump,
+            long_overlap: args.overlap,
+            max_width: args.max_width,
+            footer: if args.no_footer {
+                Some(Vec::new())
+            } else {
+                Some(args.footer).filter(|fields| !fields.is_empty())
+            },
+            cue_offset: args.cue_offset,
+            round_playtime: args.round_playtime,
+            duration_format: args.duration_format.unwrap_or_default(),
+            time_format: user_config.time_format_or_default(args.time_format),
+            date_format: user_config.date_format_or_default(args.date_format),
+            timezone: args.timezone,
+            set_start: args.set_start,
+            filters: args.filter,
+            normalize: args.normalize,
+            fix_case: args.fix_case,
+            transliterate: args.transliterate,
+            strip_suffixes: args
+                .strip_suffixes
+                .map(|suffixes| user_config.strip_suffixes_or_default(suffixes)),
+            rename_tracks: args.rename_track,
+            djs: args.dj,
+            remove_tracks: args.remove_track,
+            sort: args.sort,
+            reverse: args.reverse,
+            no_clobber: args.no_clobber,
+            organize: args.organize,
             quiet: args.quiet,
             save,
             style,
             output_path,
-            output_format: args.output_format.unwrap_or_default(),
+            // Archives default to CSV, XLSX archives need an explicit output type or file extension
+            output_format: if args.append && args.output_format.is_none() {
+                OutputFormat::Csv
+            } else {
+                user_config.output_format_or_default(args.output_format)
+            },
+            parse_options,
+            user_config,
+        }
+    }
+
+    /// Get the options for formatting the output files.
+    ///
+    /// Duplicates are already handled when parsing, so the options don't dedupe again.
+    pub fn format_options(&self) -> FormatOptions {
+        FormatOptions {
+            output_format: self.output_format.clone(),
+            start_number: self.start_number,
+            original_numbers: self.original_numbers,
+            include_extras: self.include_extras,
+            show_deck: self.show_deck,
+            show_elapsed: self.show_elapsed,
+            cue_offset: self.cue_offset.unwrap_or_default(),
+            playtime_rounding: self.round_playtime,
+            duration_format: self.duration_format,
+            time_format: self.time_format.clone(),
+            date_format: self.date_format.clone(),
+            dedupe: DedupePolicy::Off,
+            footer: self.footer.clone(),
+            show_key_clashes: self.show_key_clashes,
+            show_start_times: self.show_start_times,
+            search_links: self.search_links.clone(),
+            bpm_jump: self.bpm_jump,
+            long_overlap: self.long_overlap,
+            max_width: self.max_width,
+            stats_sheet: self.stats_sheet,
+        }
+    }
+
+    /// Get settings for saving the playlist.
+    pub fn save_settings(&self) -> SaveSettings {
+        let settings = self.user_config.save_settings(&SaveArgs {
+            output_format: Some(self.output_format.clone()),
+            default: self.default,
+            force: self.force,
+            no_force: !self.force,
+            no_clobber: self.no_clobber,
+            organize: self.organize,
+            backup: self.backup,
+        });
+        let settings = SaveSettings {
+            format: self.format_options(),
+            ..settings
+        };
+        if self.append {
+            SaveSettings {
+                append: true,
+                force: false,
+                no_clobber: false,
+                ..settings
+            }
+        } else {
+            settings
         }
     }
+
+    /// Get full output file path to save playlist to.
+    pub fn resolved_output_path(&self, playlist: &Playlist, output_format: &OutputFormat) -> PathBuf {
+        let settings = SaveSettings {
+            output_format: output_format.clone(),
+            ..self.save_settings()
+        };
+        self.user_config
+            .resolve_output_path(playlist, self.output_path.clone(), &settings)
+    }
 }
 
 impl Level {
-    pub fn to_log_filter(&self) -> log::LevelFilter {
+    pub fn to_log_filter(&self) -> LevelFilter {
         match self {
-            Level::Trace => log::LevelFilter::Trace,
-            Level::Debug => log::LevelFilter::Debug,
-            Level::Info => log::LevelFilter::Info,
-            Level::Warn => log::LevelFilter::Warn,
-            Level::Error => log::LevelFilter::Error,
+            Level::Trace => LevelFilter::TRACE,
+            Level::Debug => LevelFilter::DEBUG,
+            Level::Info => LevelFilter::INFO,
+            Level::Warn => LevelFilter::WARN,
+            Level::Error => LevelFilter::ERROR,
         }
     }
 }
@@ -145,18 +862,11 @@ mod tests {
     fn cli_config_basic() {
         // Simulating "--basic"
         let args = Args {
-            file: "playlist.txt".into(),
-            output: None,
-            default: false,
-            force: false,
-            log: None,
+            file: Some("playlist.txt".into()),
             basic: true,
-            numbered: false,
-            quiet: false,
-            save: None,
-            output_format: None,
+            ..Default::default()
         };
-        let config = CliConfig::from_args(args);
+        let config = CliConfig::from_args(args, UserConfig::default());
         assert_eq!(config.style, FormattingStyle::Basic);
         assert_eq!(config.output_path, None);
     }
@@ -165,19 +875,13 @@ mod tests {
     fn cli_config_with_output_and_force() {
         // Simulating "--output some/path --force"
         let args = Args {
-            file: "playlist.txt".into(),
+            file: Some("playlist.txt".into()),
             output: Some("some/path/playlist-2024".into()),
-            default: false,
             force: true,
-            log: None,
-            basic: false,
-            numbered: false,
-            quiet: false,
-            save: None,
-            output_format: None,
+            ..Default::default()
         };
 
-        let config = CliConfig::from_args(args);
+        let config = CliConfig::from_args(args, UserConfig::default());
 
         assert!(config.force);
         assert!(config.save);
@@ -188,19 +892,12 @@ mod tests {
     fn cli_config_with_save() {
         // Simulating "--save"
         let args = Args {
-            file: "playlist.txt".into(),
-            output: None,
-            default: false,
-            force: false,
-            log: None,
-            basic: false,
-            numbered: false,
-            quiet: false,
+            file: Some("playlist.txt".into()),
             save: Some(None),
-            output_format: None,
+            ..Default::default()
         };
 
-        let config = CliConfig::from_args(args);
+        let config = CliConfig::from_args(args, UserConfig::default());
 
         assert!(config.save);
         assert_eq!(config.output_path, None);
@@ -210,21 +907,711 @@ mod tests {
     fn cli_config_with_save_with_path() {
         // Simulating "--save playlist1.csv"
         let args = Args {
-            file: "playlist.txt".into(),
-            output: None,
-            default: false,
-            force: false,
-            log: None,
-            basic: false,
-            numbered: false,
-            quiet: false,
+            file: Some("playlist.txt".into()),
             save: Some(Some("playlist1.csv".to_string())),
-            output_format: None,
+            ..Default::default()
         };
 
-        let config = CliConfig::from_args(args);
+        let config = CliConfig::from_args(args, UserConfig::default());
+
+        assert!(config.save);
+        assert_eq!(config.output_path, Some("playlist1.csv".to_string()));
+    }
+
+    #[test]
+    fn cli_config_from_parsed_args() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--basic"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.style, FormattingStyle::Basic);
+        assert_eq!(config.output_path, None);
 
+        let args = Args::parse_from(["playfmt", "playlist.txt", "some/path/playlist-2024", "--force"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert!(config.force);
+        assert!(config.save);
+        assert_eq!(config.output_path, Some("some/path/playlist-2024".into()));
+
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "playlist1.csv"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
         assert!(config.save);
         assert_eq!(config.output_path, Some("playlist1.csv".to_string()));
     }
+
+    #[test]
+    fn cli_config_with_user_config() {
+        let user_config = UserConfig {
+            output_format: Some(OutputFormat::Csv),
+            style: Some(FormattingStyle::Numbered),
+            force: Some(true),
+            ..Default::default()
+        };
+        let config = CliConfig::from_args(Args::parse_from(["playfmt", "playlist.txt"]), user_config.clone());
+        assert_eq!(config.output_format, OutputFormat::Csv);
+        assert_eq!(config.style, FormattingStyle::Numbered);
+        assert!(config.force);
+
+        // Command line args take precedence
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--basic", "--type", "txt"]);
+        let config = CliConfig::from_args(args, user_config);
+        assert_eq!(config.output_format, OutputFormat::Txt);
+        assert_eq!(config.style, FormattingStyle::Basic);
+    }
+
+    #[test]
+    fn cli_config_with_no_clobber() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "--no-clobber"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert!(config.save_settings().no_clobber);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--force", "--no-clobber"]).is_err());
+    }
+
+    #[test]
+    fn log_level_from_verbosity_flags() {
+        let level = |args: &[&str]| Args::parse_from(args).log_level().to_log_filter();
+        assert_eq!(level(&["playfmt", "playlist.txt"]), LevelFilter::INFO);
+        assert_eq!(level(&["playfmt", "playlist.txt", "-v"]), LevelFilter::DEBUG);
+        assert_eq!(level(&["playfmt", "playlist.txt", "-vv"]), LevelFilter::TRACE);
+        assert_eq!(level(&["playfmt", "playlist.txt", "-q"]), LevelFilter::WARN);
+        assert_eq!(level(&["playfmt", "stats", "playlist.txt", "-v"]), LevelFilter::DEBUG);
+        assert_eq!(
+            level(&["playfmt", "playlist.txt", "-vv", "--log", "error"]),
+            LevelFilter::ERROR
+        );
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "-v", "-q"]).is_err());
+    }
+
+    #[test]
+    fn error_format_and_input_file() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--error-format", "json"]);
+        assert_eq!(args.error_format, Some(ErrorFormat::Json));
+        assert_eq!(args.input_file(), Some(PathBuf::from("playlist.txt")));
+
+        let args = Args::parse_from(["playfmt", "stats", "set.csv", "--error-format", "text"]);
+        assert_eq!(args.error_format, Some(ErrorFormat::Text));
+        assert_eq!(args.input_file(), Some(PathBuf::from("set.csv")));
+
+        let args = Args::parse_from(["playfmt", "merge", "a.txt", "b.txt"]);
+        assert_eq!(args.error_format, None);
+        assert_eq!(args.input_file(), None);
+
+        let args = Args::parse_from(["playfmt", "watch", ".", "--log-format", "json"]);
+        assert_eq!(args.log_format, Some(LogFormat::Json));
+    }
+
+    #[test]
+    fn cli_config_with_time_range() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--from", "22:30", "--to", "+2:00"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(
+            config.from,
+            Some(TimePosition::Clock(chrono::NaiveTime::from_hms_opt(22, 30, 0).unwrap()))
+        );
+        assert_eq!(config.to, Some(TimePosition::Elapsed(chrono::TimeDelta::hours(2))));
+    }
+
+    #[test]
+    fn cli_config_with_merge_reloads() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--merge-reloads", "60s"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.parse_options.merge_reloads, TimeDelta::try_seconds(60));
+    }
+
+    #[test]
+    fn cli_config_with_dedupe_policy() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.parse_options.dedupe, DedupePolicy::Consecutive);
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--dedupe", "fuzzy"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.parse_options.dedupe, DedupePolicy::Fuzzy);
+    }
+
+    #[test]
+    fn cli_config_with_min_playtime() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--min-playtime", "30s"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.parse_options.min_playtime, Some(TimeDelta::seconds(30)));
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--min-playtime", "30"]).is_err());
+    }
+
+    #[test]
+    fn parse_options_for_subcommands() {
+        let args = Args::parse_from([
+            "playfmt",
+            "stats",
+            "set.csv",
+            "--dedupe",
+            "fuzzy",
+            "--min-playtime",
+            "30s",
+            "--input-format",
+            "serato-csv",
+        ]);
+        let options = args.parse_options();
+        assert_eq!(options.dedupe, DedupePolicy::Fuzzy);
+        assert_eq!(options.min_playtime, Some(TimeDelta::seconds(30)));
+        assert_eq!(options.input_format, Some(InputFormat::SeratoCsv));
+
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--input-format", "rekordbox-txt", "--strict"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.parse_options.input_format, Some(InputFormat::RekordboxTxt));
+        assert!(config.parse_options.strict);
+    }
+
+    #[test]
+    fn cli_config_with_filters() {
+        let args = Args::parse_from([
+            "playfmt",
+            "playlist.txt",
+            "--filter",
+            r#"artist~="guetta""#,
+            "--filter",
+            "title!=jingle",
+        ]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.filters.len(), 2);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--filter", "album=x"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_djs() {
+        let args = Args::parse_from([
+            "playfmt",
+            "playlist.txt",
+            "--dj",
+            "A=22:00-23:00",
+            "--dj",
+            "B=23:00-0:00",
+        ]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.djs.len(), 2);
+        assert_eq!(config.djs[1].name, "B");
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--dj", "A"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_track_edits() {
+        let args = Args::parse_from([
+            "playfmt",
+            "playlist.txt",
+            "--rename-track",
+            "2:Artist - Title",
+            "--remove-track",
+            "5",
+            "--remove-track",
+            "7",
+        ]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.rename_tracks.len(), 1);
+        assert_eq!(config.rename_tracks[0].number, 2);
+        assert_eq!(config.remove_tracks, vec![5, 7]);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--rename-track", "Artist - Title"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_original_numbers() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--original-numbers"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).original_numbers);
+    }
+
+    #[test]
+    fn cli_config_without_extras() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).include_extras);
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--no-extras"]);
+        assert!(!CliConfig::from_args(args, UserConfig::default()).include_extras);
+    }
+
+    #[test]
+    fn cli_config_with_start_number() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--start-number", "16"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.start_number, Some(16));
+        assert!(!config.show_deck);
+    }
+
+    #[test]
+    fn cli_config_with_timezone() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--timezone", "Europe/London"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.timezone, Some(Tz::Europe__London));
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--timezone", "Mars/Olympus"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_set_start() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--set-start", "22:00"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.set_start, NaiveTime::from_hms_opt(22, 0, 0));
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--set-start", "25:00"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_normalize() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--normalize"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).normalize);
+    }
+
+    #[test]
+    fn cli_config_with_strip_emoji() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--strip-emoji"]);
+        assert!(
+            CliConfig::from_args(args, UserConfig::default())
+                .parse_options
+                .strip_emoji
+        );
+    }
+
+    #[test]
+    fn cli_config_with_mashups() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--mashups"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).parse_options.mashups);
+    }
+
+    #[test]
+    fn cli_config_with_strict() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--strict"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).parse_options.strict);
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert!(!CliConfig::from_args(args, UserConfig::default()).parse_options.strict);
+    }
+
+    #[test]
+    fn cli_config_with_fix_case() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--fix-case"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).fix_case);
+    }
+
+    #[test]
+    fn cli_config_with_transliterate() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--transliterate"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).transliterate,
+            Some(TransliterateMode::Append)
+        );
+        let args = Args::parse_from(["playfmt", "--transliterate=replace", "playlist.txt"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).transliterate,
+            Some(TransliterateMode::Replace)
+        );
+    }
+
+    #[test]
+    fn cli_config_with_strip_suffixes() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).strip_suffixes, None);
+
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--strip-suffixes"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).strip_suffixes,
+            Some(vec![
+                "Original Mix".to_string(),
+                "Clean".to_string(),
+                "Dirty".to_string()
+            ])
+        );
+
+        let args = Args::parse_from(["playfmt", "--strip-suffixes=Extended Mix,VIP", "playlist.txt"]);
+        assert_eq!(args.file.as_deref(), Some("playlist.txt"));
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(
+            config.strip_suffixes,
+            Some(vec!["Extended Mix".to_string(), "VIP".to_string()])
+        );
+
+        let user_config = UserConfig {
+            strip_suffixes: Some(vec!["Radio Edit".to_string()]),
+            ..UserConfig::default()
+        };
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--strip-suffixes"]);
+        assert_eq!(
+            CliConfig::from_args(args, user_config).strip_suffixes,
+            Some(vec!["Radio Edit".to_string()])
+        );
+    }
+
+    #[test]
+    fn cli_config_with_key_notation() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--key-notation", "camelot"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).key_notation,
+            Some(KeyNotation::Camelot)
+        );
+    }
+
+    #[test]
+    fn cli_config_with_harmonic() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--harmonic"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).show_key_clashes);
+    }
+
+    #[test]
+    fn cli_config_with_bpm_jump() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--bpm-jump"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).bpm_jump, Some(8.0));
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--bpm-jump=5"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).bpm_jump, Some(5.0));
+    }
+
+    #[test]
+    fn cli_config_with_show_times() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--show-times"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).show_start_times);
+    }
+
+    #[test]
+    fn cli_config_with_links() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--links"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).search_links,
+            Some(SearchLink::Beatport)
+        );
+        let user_config = UserConfig::from_toml("search_link = \"discogs\"").unwrap();
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--links"]);
+        assert_eq!(
+            CliConfig::from_args(args, user_config.clone()).search_links,
+            Some(SearchLink::Discogs)
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--links=youtube"]);
+        assert_eq!(
+            CliConfig::from_args(args, user_config.clone()).search_links,
+            Some(SearchLink::Youtube)
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(CliConfig::from_args(args, user_config).search_links, None);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--links=example.com"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_footer() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--footer", "tracks,average"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).footer,
+            Some(vec![FooterField::Tracks, FooterField::Average])
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--no-footer"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).footer,
+            Some(Vec::new())
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).footer, None);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--footer", "tracks", "--no-footer"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_max_width() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--max-width", "80"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).max_width, Some(80));
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).max_width, None);
+    }
+
+    #[test]
+    fn cli_config_with_overlap() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--overlap"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).long_overlap,
+            TimeDelta::try_minutes(5)
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--overlap=90s"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).long_overlap,
+            TimeDelta::try_seconds(90)
+        );
+    }
+
+    #[test]
+    fn cli_config_with_deck() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--deck"]);
+        assert!(CliConfig::from_args(args, UserConfig::default()).show_deck);
+    }
+
+    #[test]
+    fn cli_config_with_stats_sheet() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert!(
+            !CliConfig::from_args(args, UserConfig::default())
+                .format_options()
+                .stats_sheet
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--stats-sheet"]);
+        assert!(
+            CliConfig::from_args(args, UserConfig::default())
+                .format_options()
+                .stats_sheet
+        );
+    }
+
+    #[test]
+    fn cli_config_with_elapsed() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--elapsed"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert!(config.show_elapsed);
+        assert!(!config.show_deck);
+    }
+
+    #[test]
+    fn cli_config_with_round_playtime() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--round-playtime", "30s"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.round_playtime, TimeDelta::try_seconds(30));
+    }
+
+    #[test]
+    fn cli_config_with_duration_format() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).duration_format,
+            DurationFormat::Auto
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--duration-format", "hh:mm:ss"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).duration_format,
+            DurationFormat::Padded
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--duration-format", "seconds"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).duration_format,
+            DurationFormat::Seconds
+        );
+    }
+
+    #[test]
+    fn cli_config_with_time_format() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--time-format", "iso"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).time_format,
+            TimeFormat::Iso
+        );
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--time-format", "unix"]).is_err());
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--time-format", "%H:%M"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).time_format,
+            TimeFormat::Custom("%H:%M".to_string())
+        );
+    }
+
+    #[test]
+    fn cli_config_with_date_format() {
+        let args = Args::parse_from(["playfmt", "playlist.txt"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).date_format,
+            DateFormat::default()
+        );
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--date-format", "%d.%m.%Y"]);
+        assert_eq!(
+            CliConfig::from_args(args, UserConfig::default()).date_format,
+            "%d.%m.%Y".parse::<DateFormat>().unwrap()
+        );
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--date-format", "%Q"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_configured_formats() {
+        let user_config = UserConfig {
+            time_format: Some(TimeFormat::Iso),
+            date_format: "%d.%m.%Y".parse().ok(),
+            ..Default::default()
+        };
+        let config = CliConfig::from_args(Args::parse_from(["playfmt", "playlist.txt"]), user_config.clone());
+        assert_eq!(config.time_format, TimeFormat::Iso);
+        assert_eq!(config.date_format, "%d.%m.%Y".parse::<DateFormat>().unwrap());
+
+        // Command line args take precedence
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--time-format", "default"]);
+        assert_eq!(CliConfig::from_args(args, user_config).time_format, TimeFormat::Default);
+    }
+
+    #[test]
+    fn cli_config_with_no_force() {
+        let user_config = UserConfig {
+            force: Some(true),
+            ..Default::default()
+        };
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "--no-force"]);
+        let config = CliConfig::from_args(args, user_config.clone());
+        assert!(!config.force);
+        assert!(!config.save_settings().force);
+
+        // The last flag wins
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--no-force", "--force"]);
+        assert!(CliConfig::from_args(args, user_config).force);
+    }
+
+    #[test]
+    fn cli_config_with_cue_offset() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--elapsed", "--cue-offset", "-0:30"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.cue_offset, TimeDelta::try_seconds(-30));
+    }
+
+    #[test]
+    fn cli_config_format_options() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--elapsed", "--no-extras", "--type", "txt"]);
+        let options = CliConfig::from_args(args, UserConfig::default()).format_options();
+        assert_eq!(options.output_format, OutputFormat::Txt);
+        assert!(options.show_elapsed);
+        assert!(!options.include_extras);
+        assert_eq!(options.dedupe, DedupePolicy::Off);
+    }
+
+    #[test]
+    fn cli_config_with_sort() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--sort", "start-time", "--reverse"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.sort, Some(SortKey::StartTime));
+        assert!(config.reverse);
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--reverse"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.sort, None);
+        assert!(config.reverse);
+    }
+
+    #[test]
+    fn cli_config_with_skipped_tracks() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--skip-first", "2", "--skip-last", "1"]);
+        let config = CliConfig::from_args(args, UserConfig::default());
+        assert_eq!(config.skip_first, 2);
+        assert_eq!(config.skip_last, 1);
+    }
+
+    #[test]
+    fn list_subcommand() {
+        let args = Args::parse_from(["playfmt", "list", "--sort", "tracks", "-r"]);
+        match args.command {
+            Some(Command::List { dir, sort, reverse }) => {
+                assert_eq!(dir, None);
+                assert_eq!(sort, ListSort::Tracks);
+                assert!(reverse);
+            }
+            _ => panic!("Expected list subcommand"),
+        }
+    }
+
+    #[test]
+    fn split_subcommand() {
+        let args = Args::parse_from(["playfmt", "split", "set.csv", "11", "2:00:00", "-t", "csv"]);
+        match args.command {
+            Some(Command::Split { file, at, save }) => {
+                assert_eq!(file, "set.csv");
+                assert_eq!(
+                    at,
+                    vec![SplitPoint::Track(11), SplitPoint::Elapsed(chrono::TimeDelta::hours(2))]
+                );
+                assert_eq!(save.output_format, Some(OutputFormat::Csv));
+            }
+            _ => panic!("Expected split subcommand"),
+        }
+        assert!(Args::try_parse_from(["playfmt", "split", "set.csv"]).is_err());
+        assert!(Args::try_parse_from(["playfmt", "split", "set.csv", "later"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_append() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "archive.csv", "--append"]);
+        let user_config = UserConfig {
+            no_clobber: Some(true),
+            ..UserConfig::default()
+        };
+        let settings = CliConfig::from_args(args, user_config).save_settings();
+        assert!(settings.append);
+        assert!(!settings.no_clobber);
+        assert_eq!(settings.output_format, OutputFormat::Csv);
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--append", "--force"]).is_err());
+    }
+
+    #[test]
+    fn cli_config_with_backup() {
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "--force", "--backup"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).backup, Some(1));
+        let args = Args::parse_from(["playfmt", "playlist.txt", "--save", "--force", "--backup=3"]);
+        assert_eq!(CliConfig::from_args(args, UserConfig::default()).backup, Some(3));
+        assert!(Args::try_parse_from(["playfmt", "playlist.txt", "--backup"]).is_err());
+    }
+
+    #[test]
+    fn validate_subcommand() {
+        let args = Args::parse_from(["playfmt", "validate", "playlist.txt", "--log", "debug"]);
+        assert!(args.file.is_none());
+        assert!(matches!(args.command, Some(Command::Validate { file, .. }) if file == "playlist.txt"));
+    }
+
+    #[test]
+    fn validate_subcommand_with_max_gap() {
+        let args = Args::parse_from(["playfmt", "validate", "playlist.txt", "--max-gap", "30s"]);
+        assert!(matches!(args.command, Some(Command::Validate { max_gap, .. }) if max_gap == TimeDelta::seconds(30)));
+        let args = Args::parse_from(["playfmt", "validate", "playlist.txt"]);
+        assert!(matches!(args.command, Some(Command::Validate { max_gap, .. }) if max_gap == TimeDelta::seconds(60)));
+    }
+
+    #[test]
+    fn merge_subcommand() {
+        let args = Args::parse_from(["playfmt", "merge", "a.csv", "b.txt", "-o", "combined.xlsx"]);
+        match args.command {
+            Some(Command::Merge {
+                files,
+                output,
+                strategy,
+                ..
+            }) => {
+                assert_eq!(files, vec!["a.csv", "b.txt"]);
+                assert_eq!(output, Some("combined.xlsx".to_string()));
+                assert_eq!(strategy, MergeStrategy::Concatenate);
+            }
+            _ => panic!("Expected merge subcommand"),
+        }
+        assert!(Args::try_parse_from(["playfmt", "merge", "a.csv"]).is_err());
+        let args = Args::parse_from(["playfmt", "merge", "a.csv", "b.csv", "--strategy", "timestamp"]);
+        match args.command {
+            Some(Command::Merge { strategy, .. }) => assert_eq!(strategy, MergeStrategy::Timestamp),
+            _ => panic!("Expected merge subcommand"),
+        }
+    }
+
+    #[test]
+    fn convert_subcommand() {
+        let args = Args::parse_from([
+            "playfmt",
+            "convert",
+            "--from",
+            "serato-csv",
+            "--to",
+            "m3u",
+            "in.csv",
+            "out.m3u",
+        ]);
+        match args.command {
+            Some(Command::Convert { from, to, .. }) => {
+                assert_eq!(from, Some(InputFormat::SeratoCsv));
+                assert_eq!(to, Some(OutputFormat::M3u));
+            }
+            _ => panic!("Expected convert subcommand"),
+        }
+    }
+
+    #[test]
+    fn completions_subcommand() {
+        let args = Args::parse_from(["playfmt", "completions", "zsh"]);
+        assert!(matches!(args.command, Some(Command::Completions { shell: Shell::Zsh })));
+    }
+
+    #[test]
+    fn diff_subcommand() {
+        let args = Args::parse_from(["playfmt", "diff", "old.csv", "new.csv", "--json"]);
+        match args.command {
+            Some(Command::Diff { old, new, json }) => {
+                assert_eq!(old, "old.csv");
+                assert_eq!(new, "new.csv");
+                assert!(json);
+            }
+            _ => panic!("Expected diff subcommand"),
+        }
+    }
+
+    #[test]
+    fn schema_subcommand() {
+        let args = Args::parse_from(["playfmt", "schema"]);
+        assert!(matches!(args.command, Some(Command::Schema)));
+    }
 }
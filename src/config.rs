@@ -0,0 +1,284 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use playlist_formatter::normalize;
+use playlist_formatter::options::{FormatOptions, SaveOptions};
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::types::{DateFormat, OutputFormat, SearchLink, TimeFormat};
+use playlist_formatter::utils;
+
+use crate::cli::{FormattingStyle, SaveArgs};
+
+/// Persistent defaults read from the user config file.
+///
+/// Command line arguments take precedence over these values.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    /// Default output format
+    pub output_format: Option<OutputFormat>,
+    /// Default print formatting style
+    pub style: Option<FormattingStyle>,
+    /// Directory to save playlists to instead of the default save directory
+    pub save_dir: Option<PathBuf>,
+    /// Overwrite existing output files
+    pub force: Option<bool>,
+    /// Save playlists to year and month subdirectories
+    pub organize: Option<bool>,
+    /// Save with a numbered file name instead of overwriting an existing file
+    pub no_clobber: Option<bool>,
+    /// Number of backups to keep when overwriting an existing file
+    pub backup: Option<usize>,
+    /// Title suffixes to remove with the strip suffixes option
+    pub strip_suffixes: Option<Vec<String>>,
+    /// Web search used by the links option when no search is given
+    pub search_link: Option<SearchLink>,
+    /// Default format for start and end times in saved files
+    pub time_format: Option<TimeFormat>,
+    /// Default format for the playlist date
+    pub date_format: Option<DateFormat>,
+}
+
+/// Resolved settings for saving playlist files.
+#[derive(Debug, Clone, Default)]
+pub struct SaveSettings {
+    pub output_format: OutputFormat,
+    pub use_default_dir: bool,
+    pub force: bool,
+    pub no_clobber: bool,
+    pub organize: bool,
+    /// Number of backups to keep when overwriting an existing file
+    pub backup: Option<usize>,
+    /// Append to an existing archive file instead of creating a new file
+    pub append: bool,
+    /// How the playlist is formatted in the saved file
+    pub format: FormatOptions,
+}
+
+impl SaveSettings {
+    /// Save playlist to the given path, backing up an existing file first if enabled.
+    ///
+    /// The output format is taken from the file extension, falling back to the configured output format.
+    pub fn save(&self, playlist: &Playlist, path: &Path) -> Result<()> {
+        if self.append {
+            return Ok(playlist.append_to_path(path, &self.format)?);
+        }
+        if self.force && path.is_file() {
+            if let Some(keep) = self.backup.filter(|keep| *keep > 0) {
+                let backup_path = utils::backup_file(path, keep)?;
+                tracing::info!("Backup: {}", backup_path.display());
+            }
+        }
+        let options = SaveOptions {
+            format: FormatOptions {
+                output_format: OutputFormat::from_path(path).unwrap_or_else(|| self.output_format.clone()),
+                ..self.format.clone()
+            },
+            overwrite: self.force,
+        };
+        Ok(playlist.save_with_options(path, &options)?)
+    }
+}
+
+impl UserConfig {
+    /// Load config from the given path, or from the default location if no path is given.
+    ///
+    /// A missing config file in the default location is not an error.
+    pub fn load(path: Option<&Path>) -> Result<Self> {
+        let path = match path {
+            Some(path) => path.to_path_buf(),
+            None => match Self::default_path() {
+                Some(path) if path.is_file() => path,
+                _ => return Ok(Self::default()),
+            },
+        };
+        tracing::debug!("Reading config: {}", path.display());
+        let contents = std::fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read config file: '{}'", path.display()))?;
+        Self::from_toml(&contents).with_context(|| format!("Invalid config file: '{}'", path.display()))
+    }
+
+    /// Parse config from a TOML string.
+    pub fn from_toml(contents: &str) -> Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// Default config file location: `~/.config/playlist-formatter/config.toml`
+    pub fn default_path() -> Option<PathBuf> {
+        home::home_dir().map(|home| home.join(".config").join("playlist-formatter").join("config.toml"))
+    }
+
+    /// Get output format from the command line arg, falling back to the configured format.
+    pub fn output_format_or_default(&self, output_format: Option<OutputFormat>) -> OutputFormat {
+        output_format.or_else(|| self.output_format.clone()).unwrap_or_default()
+    }
+
+    /// Overwrite existing files if the command line flag is set,
+    /// falling back to the config option when neither `--force` nor `--no-force` is given.
+    pub fn force_or_default(&self, force: bool, no_force: bool) -> bool {
+        if force || no_force {
+            force
+        } else {
+            self.force.unwrap_or(false)
+        }
+    }
+
+    /// Get time format from the command line arg, falling back to the configured format.
+    pub fn time_format_or_default(&self, time_format: Option<TimeFormat>) -> TimeFormat {
+        time_format.or_else(|| self.time_format.clone()).unwrap_or_default()
+    }
+
+    /// Get date format from the command line arg, falling back to the configured format.
+    pub fn date_format_or_default(&self, date_format: Option<DateFormat>) -> DateFormat {
+        date_format.or_else(|| self.date_format.clone()).unwrap_or_default()
+    }
+
+    /// Get title suffixes to strip from the command line list, falling back to the configured or default suffixes.
+    pub fn strip_suffixes_or_default(&self, suffixes: Vec<String>) -> Vec<String> {
+        if !suffixes.is_empty() {
+            return suffixes;
+        }
+        self.strip_suffixes.clone().unwrap_or_else(|| {
+            normalize::DEFAULT_STRIP_SUFFIXES
+                .iter()
+                .map(ToString::to_string)
+                .collect()
+        })
+    }
+
+    /// Combine command line save options with the config defaults.
+    pub fn save_settings(&self, args: &SaveArgs) -> SaveSettings {
+        SaveSettings {
+            output_format: self.output_format_or_default(args.output_format.clone()),
+            use_default_dir: args.default,
+            force: self.force_or_default(args.force, args.no_force),
+            no_clobber: !args.force && (args.no_clobber || self.no_clobber.unwrap_or(false)),
+            organize: args.organize || self.organize.unwrap_or(false),
+            backup: args.backup.or(self.backup),
+            append: false,
+            format: FormatOptions::default(),
+        }
+    }
+
+    /// Resolve the full output file path for a playlist.
+    ///
+    /// Uses the configured save directory instead of the built-in default save directory when set,
+    /// puts the file in a year and month subdirectory when organizing is enabled,
+    /// and picks a numbered file name if the file already exists and no-clobber is enabled.
+    pub fn resolve_output_path(
+        &self,
+        playlist: &Playlist,
+        output_path: Option<String>,
+        settings: &SaveSettings,
+    ) -> PathBuf {
+        let output_path = self.output_path(&playlist.name, output_path, settings.use_default_dir);
+        let use_default_dir = settings.use_default_dir && self.save_dir.is_none();
+        let path = if settings.organize {
+            playlist.get_organized_output_file_path(output_path, use_default_dir, &settings.output_format)
+        } else {
+            playlist.get_output_file_path(output_path, use_default_dir, &settings.output_format)
+        };
+        if settings.no_clobber {
+            utils::next_available_path(&path)
+        } else {
+            path
+        }
+    }
+
+    /// Get the existing output file path if it already has the same contents the playlist would be saved as.
+    pub fn up_to_date_output(&self, playlist: &Playlist, settings: &SaveSettings) -> Option<PathBuf> {
+        let settings = SaveSettings {
+            no_clobber: false,
+            ..settings.clone()
+        };
+        let path = self.resolve_output_path(playlist, None, &settings);
+        playlist.is_up_to_date(&path, &settings.format).then_some(path)
+    }
+
+    /// Resolve output path using the configured save directory.
+    ///
+    /// The save directory is used when no output path is given,
+    /// or when the default directory option is used with an output file name.
+    fn output_path(&self, playlist_name: &str, output_path: Option<String>, use_default_dir: bool) -> Option<String> {
+        let Some(save_dir) = &self.save_dir else {
+            return output_path;
+        };
+        let output_path = output_path.filter(|path| !path.trim().is_empty());
+        match output_path {
+            None => Some(save_dir.join(playlist_name).to_string_lossy().to_string()),
+            Some(path) if use_default_dir => Path::new(path.trim())
+                .file_name()
+                .map(|name| save_dir.join(name).to_string_lossy().to_string())
+                .or(Some(path)),
+            Some(path) => Some(path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_config() {
+        let config = UserConfig::from_toml(
+            r#"
+            output_format = "csv"
+            style = "numbered"
+            save_dir = "/playlists"
+            force = true
+            organize = true
+            time_format = "iso"
+            date_format = "%d.%m.%Y"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.output_format, Some(OutputFormat::Csv));
+        assert_eq!(config.style, Some(FormattingStyle::Numbered));
+        assert_eq!(config.save_dir, Some(PathBuf::from("/playlists")));
+        assert_eq!(config.force, Some(true));
+        assert_eq!(config.organize, Some(true));
+        assert_eq!(config.time_format, Some(TimeFormat::Iso));
+        assert_eq!(config.date_format, "%d.%m.%Y".parse().ok());
+    }
+
+    #[test]
+    fn parse_invalid_config() {
+        assert!(UserConfig::from_toml("output_format = \"mp3\"").is_err());
+        assert!(UserConfig::from_toml("unknown = true").is_err());
+        assert!(UserConfig::from_toml("time_format = \"%Q\"").is_err());
+    }
+
+    #[test]
+    fn output_path_with_save_dir() {
+        let config = UserConfig {
+            save_dir: Some(PathBuf::from("/playlists")),
+            ..Default::default()
+        };
+        assert_eq!(
+            config.output_path("Set 1.1.2024", None, false),
+            Some(
+                PathBuf::from("/playlists")
+                    .join("Set 1.1.2024")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            config.output_path("Set", Some("some/dir/out.csv".to_string()), true),
+            Some(
+                PathBuf::from("/playlists")
+                    .join("out.csv")
+                    .to_string_lossy()
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            config.output_path("Set", Some("some/dir/out.csv".to_string()), false),
+            Some("some/dir/out.csv".to_string())
+        );
+        assert_eq!(UserConfig::default().output_path("Set", None, false), None);
+    }
+}
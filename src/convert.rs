@@ -0,0 +1,80 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use chrono::TimeDelta;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use super::error::Result;
+use super::options::{ConvertOptions, ProgressEvent};
+use super::playlist::Playlist;
+use super::types::PlaylistType;
+
+/// Input playlist file and the output file to write it to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConvertJob {
+    pub input: PathBuf,
+    /// The output format is selected from the file extension
+    pub output: PathBuf,
+}
+
+impl ConvertJob {
+    pub fn new(input: impl Into<PathBuf>, output: impl Into<PathBuf>) -> Self {
+        Self {
+            input: input.into(),
+            output: output.into(),
+        }
+    }
+}
+
+/// Summary of a converted playlist file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub input: PathBuf,
+    pub output: PathBuf,
+    pub name: String,
+    pub playlist_type: PlaylistType,
+    pub tracks: usize,
+    pub total_duration: Option<TimeDelta>,
+}
+
+/// Parse the input playlist and write it to the output file.
+pub fn convert(job: &ConvertJob, options: &ConvertOptions) -> Result<Report> {
+    let playlist = Playlist::new_with_options(&job.input, &options.parse)?;
+    playlist.save_to_path(&job.output, options.overwrite)?;
+    Ok(Report {
+        input: job.input.clone(),
+        output: job.output.clone(),
+        tracks: playlist.len(),
+        total_duration: playlist.total_duration,
+        name: playlist.name,
+        playlist_type: playlist.playlist_type,
+    })
+}
+
+/// Convert multiple playlist files.
+///
+/// The files are converted in parallel when the `parallel` feature is enabled.
+/// A failed file does not stop the others,
+/// and the results are in the same order as the jobs.
+/// Each finished file is reported to the progress callback in the parse options.
+pub fn convert_many(jobs: &[ConvertJob], options: &ConvertOptions) -> Vec<Result<Report>> {
+    let total = jobs.len();
+    let completed = AtomicUsize::new(0);
+    #[cfg(feature = "parallel")]
+    let jobs = jobs.par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let jobs = jobs.iter();
+    jobs.map(|job| {
+        let result = convert(job, options);
+        if let Some(progress) = &options.parse.progress {
+            progress.report(&ProgressEvent::FileCompleted {
+                file: job.input.clone(),
+                completed: completed.fetch_add(1, Ordering::Relaxed) + 1,
+                total,
+            });
+        }
+        result
+    })
+    .collect()
+}
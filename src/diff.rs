@@ -0,0 +1,271 @@
+use std::collections::{HashMap, VecDeque};
+
+use chrono::NaiveDateTime;
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use serde::Serialize;
+
+use super::track::{Track, METADATA_FIELDS};
+use super::utils;
+
+/// Track that is only in one of the playlists.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DiffTrack {
+    /// Number of the track in the playlist it is in, counting from one
+    pub track_number: usize,
+    pub track: String,
+}
+
+/// Track that is in both playlists but in a different order.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MovedTrack {
+    /// Number of the track in the old playlist, counting from one
+    pub from: usize,
+    /// Number of the track in the new playlist, counting from one
+    pub to: usize,
+    pub track: String,
+}
+
+/// Field with a different value in the new playlist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: Option<String>,
+    pub new: Option<String>,
+}
+
+/// Track that is in both playlists with different field values.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ChangedTrack {
+    /// Number of the track in the new playlist, counting from one
+    pub track_number: usize,
+    pub track: String,
+    pub changes: Vec<FieldChange>,
+}
+
+/// Differences between an old and a new version of a playlist.
+///
+/// Tracks are matched by artist and title, ignoring case and punctuation.
+/// A track played several times is matched in order of appearance.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlaylistDiff {
+    pub added: Vec<DiffTrack>,
+    pub removed: Vec<DiffTrack>,
+    /// Tracks out of order relative to the other matched tracks
+    pub moved: Vec<MovedTrack>,
+    pub changed: Vec<ChangedTrack>,
+}
+
+impl PlaylistDiff {
+    /// Compare the old tracks to the new tracks.
+    pub fn new(old: &[Track], new: &[Track]) -> Self {
+        let mut new_indices: HashMap<String, VecDeque<usize>> = HashMap::new();
+        for (index, track) in new.iter().enumerate() {
+            new_indices.entry(track.fuzzy_key()).or_default().push_back(index);
+        }
+        let mut matches: Vec<(usize, usize)> = Vec::new();
+        let mut removed: Vec<DiffTrack> = Vec::new();
+        for (index, track) in old.iter().enumerate() {
+            match new_indices.get_mut(&track.fuzzy_key()).and_then(VecDeque::pop_front) {
+                Some(new_index) => matches.push((index, new_index)),
+                None => removed.push(DiffTrack {
+                    track_number: index + 1,
+                    track: track.to_string(),
+                }),
+            }
+        }
+        let mut added: Vec<DiffTrack> = new_indices
+            .into_values()
+            .flatten()
+            .map(|index| DiffTrack {
+                track_number: index + 1,
+                track: new[index].to_string(),
+            })
+            .collect();
+        added.sort_by_key(|track| track.track_number);
+
+        let in_order = longest_increasing(&matches.iter().map(|(_, new_index)| *new_index).collect::<Vec<_>>());
+        let moved = matches
+            .iter()
+            .enumerate()
+            .filter(|(position, _)| !in_order[*position])
+            .map(|(_, &(old_index, new_index))| MovedTrack {
+                from: old_index + 1,
+                to: new_index + 1,
+                track: new[new_index].to_string(),
+            })
+            .collect();
+
+        let mut changed: Vec<ChangedTrack> = matches
+            .iter()
+            .filter_map(|&(old_index, new_index)| {
+                let changes = field_changes(&old[old_index], &new[new_index]);
+                (!changes.is_empty()).then(|| ChangedTrack {
+                    track_number: new_index + 1,
+                    track: new[new_index].to_string(),
+                    changes,
+                })
+            })
+            .collect();
+        changed.sort_by_key(|track| track.track_number);
+
+        Self {
+            added,
+            removed,
+            moved,
+            changed,
+        }
+    }
+
+    /// Check if the playlists have the same tracks in the same order with the same values.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.moved.is_empty() && self.changed.is_empty()
+    }
+
+    /// Print the differences with added tracks in green and removed tracks in red.
+    #[cfg(feature = "cli")]
+    pub fn print(&self) {
+        for track in &self.removed {
+            println!("{}", format!("- {:>3}: {}", track.track_number, track.track).red());
+        }
+        for track in &self.added {
+            println!("{}", format!("+ {:>3}: {}", track.track_number, track.track).green());
+        }
+        for track in &self.moved {
+            println!(
+                "{}",
+                format!("~ {:>3}: {} (moved from {})", track.to, track.track, track.from).yellow()
+            );
+        }
+        for track in &self.changed {
+            println!("{}", format!("* {:>3}: {}", track.track_number, track.track).cyan());
+            for change in &track.changes {
+                println!(
+                    "        {}: '{}' -> '{}'",
+                    change.field,
+                    change.old.as_deref().unwrap_or_default(),
+                    change.new.as_deref().unwrap_or_default()
+                );
+            }
+        }
+    }
+}
+
+/// Get the fields that have a different value in the new track.
+fn field_changes(old: &Track, new: &Track) -> Vec<FieldChange> {
+    let values = |track: &Track| -> Vec<(&str, Option<String>)> {
+        let timestamp = |time: NaiveDateTime| time.format("%Y-%m-%d %H:%M:%S").to_string();
+        let mut values = vec![
+            ("Artist", Some(track.artist.clone())),
+            ("Title", Some(track.title.clone())),
+            ("Playtime", track.play_time.map(utils::formatted_duration)),
+            ("Start time", track.start_time.map(timestamp)),
+            ("End time", track.end_time.map(timestamp)),
+        ];
+        values.extend(METADATA_FIELDS.into_iter().zip(track.metadata()));
+        values.push(("DJ", track.dj.clone()));
+        values.push(("Mashup", Some(track.mashups_text()).filter(|text| !text.is_empty())));
+        values.push(("Notes", track.notes.clone()));
+        values
+    };
+    values(old)
+        .into_iter()
+        .zip(values(new))
+        .filter(|((_, old), (_, new))| old != new)
+        .map(|((field, old), (_, new))| FieldChange {
+            field: field.to_string(),
+            old,
+            new,
+        })
+        .collect()
+}
+
+/// Mark the values that are part of a longest strictly increasing subsequence.
+fn longest_increasing(values: &[usize]) -> Vec<bool> {
+    // Position of the last value for each subsequence length, and the previous value for each position
+    let mut tails: Vec<usize> = Vec::new();
+    let mut previous: Vec<Option<usize>> = vec![None; values.len()];
+    for (position, value) in values.iter().enumerate() {
+        let length = tails.partition_point(|&tail| values[tail] < *value);
+        if length > 0 {
+            previous[position] = Some(tails[length - 1]);
+        }
+        if length == tails.len() {
+            tails.push(position);
+        } else {
+            tails[length] = position;
+        }
+    }
+    let mut in_sequence = vec![false; values.len()];
+    let mut position = tails.last().copied();
+    while let Some(current) = position {
+        in_sequence[current] = true;
+        position = previous[current];
+    }
+    in_sequence
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tracks(names: &[&str]) -> Vec<Track> {
+        names.iter().map(|name| Track::from_name(name)).collect()
+    }
+
+    #[test]
+    fn added_removed_and_moved() {
+        let old = tracks(&["A - One", "B - Two", "C - Three", "D - Four", "E - Five"]);
+        let new = tracks(&["A - One", "E - Five", "B - Two", "D - Four", "F - Six"]);
+        let diff = PlaylistDiff::new(&old, &new);
+        assert_eq!(
+            diff.removed,
+            vec![DiffTrack {
+                track_number: 3,
+                track: "C - Three".to_string()
+            }]
+        );
+        assert_eq!(
+            diff.added,
+            vec![DiffTrack {
+                track_number: 5,
+                track: "F - Six".to_string()
+            }]
+        );
+        assert_eq!(
+            diff.moved,
+            vec![MovedTrack {
+                from: 5,
+                to: 2,
+                track: "E - Five".to_string()
+            }]
+        );
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn changed_fields() {
+        let old = tracks(&["A - one", "B - Two"]);
+        let mut new = tracks(&["A - One", "B - Two"]);
+        new[1].bpm = Some(124.0);
+        let diff = PlaylistDiff::new(&old, &new);
+        assert!(diff.added.is_empty() && diff.removed.is_empty() && diff.moved.is_empty());
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![FieldChange {
+                field: "Title".to_string(),
+                old: Some("one".to_string()),
+                new: Some("One".to_string()),
+            }]
+        );
+        assert_eq!(diff.changed[1].changes[0].field, "BPM");
+        assert!(PlaylistDiff::new(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn increasing_subsequence() {
+        assert_eq!(longest_increasing(&[0, 3, 1, 2]), vec![true, false, true, true]);
+        assert_eq!(longest_increasing(&[]), Vec::<bool>::new());
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use anyhow::Result;
+use colored::Colorize;
+
+use playlist_formatter::utils;
+
+use crate::config::UserConfig;
+
+/// Outcome of one environment check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// Result of one environment check, with a suggested fix for warnings and failures.
+#[derive(Debug, Clone)]
+struct Check {
+    status: Status,
+    message: String,
+    fix: &'static str,
+}
+
+impl Check {
+    fn ok(message: String) -> Self {
+        Self {
+            status: Status::Ok,
+            message,
+            fix: "",
+        }
+    }
+
+    fn warn(message: impl Into<String>, fix: &'static str) -> Self {
+        Self {
+            status: Status::Warn,
+            message: message.into(),
+            fix,
+        }
+    }
+
+    fn fail(message: String, fix: &'static str) -> Self {
+        Self {
+            status: Status::Fail,
+            message,
+            fix,
+        }
+    }
+
+    fn print(&self) {
+        match self.status {
+            Status::Ok => println!("{} {}", "OK".green().bold(), self.message),
+            Status::Warn => println!("{} {}\n     {}", "WARN".yellow().bold(), self.message, self.fix.cyan()),
+            Status::Fail => println!("{} {}\n     {}", "FAIL".red().bold(), self.message, self.fix.cyan()),
+        }
+    }
+}
+
+/// Check the environment and print fixes for any problems found.
+///
+/// Returns an error if any check failed, so this can be used to gate automation.
+/// Playlists are downloaded without authentication, so there are no API credentials to check.
+pub fn run_doctor(config_path: Option<&Path>) -> Result<()> {
+    let checks = run_checks(config_path);
+    for check in &checks {
+        check.print();
+    }
+    let problems = checks.iter().filter(|check| check.status == Status::Fail).count();
+    if problems > 0 {
+        anyhow::bail!("Found {problems} problem(s)");
+    }
+    println!("\n{}", "No problems found".green());
+    Ok(())
+}
+
+/// Run all environment checks.
+fn run_checks(config_path: Option<&Path>) -> Vec<Check> {
+    let mut checks: Vec<Check> = Vec::new();
+    let config_file = config_path.map(Path::to_path_buf).or_else(UserConfig::default_path);
+    match &config_file {
+        Some(path) if path.is_file() => match UserConfig::load(Some(path)) {
+            Ok(config) => {
+                checks.push(Check::ok(format!("Config file: {}", path.display())));
+                if let Some(save_dir) = &config.save_dir {
+                    if save_dir.is_dir() && utils::is_writable_dir(save_dir) {
+                        checks.push(Check::ok(format!("Configured save directory: {}", save_dir.display())));
+                    } else {
+                        checks.push(Check::fail(
+                            format!(
+                                "Configured save directory is missing or not writable: {}",
+                                save_dir.display()
+                            ),
+                            "Create the directory or change `save_dir` in the config file",
+                        ));
+                    }
+                }
+            }
+            Err(error) => checks.push(Check::fail(
+                format!("{error:#}"),
+                "Fix or remove the invalid config values",
+            )),
+        },
+        Some(path) if config_path.is_some() => checks.push(Check::fail(
+            format!("Config file not found: {}", path.display()),
+            "Check the config file path",
+        )),
+        Some(path) => checks.push(Check::ok(format!("No config file, using defaults: {}", path.display()))),
+        None => checks.push(Check::warn(
+            "Could not resolve home directory for config file",
+            "Use the config option",
+        )),
+    }
+    checks.push(match utils::dropbox_save_dir() {
+        Some(dir) if utils::is_writable_dir(&dir) => Check::ok(format!("Default save directory: {}", dir.display())),
+        Some(dir) => Check::fail(
+            format!("Default save directory is not writable: {}", dir.display()),
+            "Check the directory permissions",
+        ),
+        None => Check::warn(
+            "Dropbox playlist directory not found, saving next to the input file by default",
+            "Create the directory or use an explicit output path",
+        ),
+    });
+    checks.push(match std::env::current_dir() {
+        Ok(dir) if utils::is_writable_dir(&dir) => {
+            Check::ok(format!("Current directory is writable: {}", dir.display()))
+        }
+        Ok(dir) => Check::fail(
+            format!("Current directory is not writable: {}", dir.display()),
+            "Run from a writable directory or use the default save directory option",
+        ),
+        Err(error) => Check::fail(
+            format!("Failed to get current directory: {error}"),
+            "Check the working directory",
+        ),
+    });
+    checks.push(match utils::serato_dir() {
+        Some(dir) => Check::ok(format!("Serato directory: {}", dir.display())),
+        None => Check::warn(
+            "Serato directory not found",
+            "Export playlists from Serato DJ Pro history",
+        ),
+    });
+    checks.push(match utils::rekordbox_dir() {
+        Some(dir) => Check::ok(format!("Rekordbox directory: {}", dir.display())),
+        None => Check::warn(
+            "Rekordbox directory not found",
+            "Export playlists from Rekordbox as TXT",
+        ),
+    });
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_config_file_fails() {
+        let path = std::env::temp_dir().join(format!("playfmt-doctor-missing-{}.toml", std::process::id()));
+        let checks = run_checks(Some(&path));
+        assert_eq!(checks[0].status, Status::Fail);
+        assert!(checks[0].message.starts_with("Config file not found"));
+        assert!(run_doctor(Some(&path)).is_err());
+    }
+
+    #[test]
+    fn config_with_missing_save_dir_fails() {
+        let dir = std::env::temp_dir();
+        let path = dir.join(format!("playfmt-doctor-{}.toml", std::process::id()));
+        let save_dir = dir.join(format!("playfmt-doctor-missing-dir-{}", std::process::id()));
+        std::fs::write(&path, format!("save_dir = {:?}\n", save_dir.to_string_lossy())).unwrap();
+        let checks = run_checks(Some(&path));
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(checks[0].status, Status::Ok);
+        assert_eq!(checks[1].status, Status::Fail);
+        assert!(checks[1].message.contains("save directory"));
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::{Path, PathBuf};
+use std::process;
+
+use anyhow::{Context, Result};
+
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::track::Track;
+
+const EDIT_HELP: &str = "\
+# Edit the tracks below, then save and close the editor.
+# Each line is: <original track number> | <artist> | <title>
+# Delete a line to remove the track, or reorder lines to move tracks.
+# New tracks can be added by leaving the track number empty: | Artist | Title
+# Write a '|' inside an artist or title as '\\|' and a backslash as '\\\\'.
+# Lines starting with '#' are ignored.
+";
+
+/// Open playlist tracks in the user's editor and update the playlist from the edited text.
+pub fn edit_playlist(playlist: &mut Playlist) -> Result<()> {
+    let temp_file = std::env::temp_dir().join(format!("playfmt-edit-{}.txt", process::id()));
+    std::fs::write(&temp_file, render_editable_text(&playlist.tracks))?;
+
+    let result = open_editor(&temp_file).and_then(|()| {
+        let text = std::fs::read_to_string(&temp_file)?;
+        parse_edited_text(&text, &playlist.tracks)
+    });
+    if let Err(error) = std::fs::remove_file(&temp_file) {
+        tracing::warn!("Failed to remove temporary file {}: {error}", temp_file.display());
+    }
+
+    playlist.tracks = result?;
+    playlist.update_totals();
+    Ok(())
+}
+
+/// Render tracks in a simple line-based text format.
+fn render_editable_text(tracks: &[Track]) -> String {
+    let mut text = String::from(EDIT_HELP);
+    for (index, track) in tracks.iter().enumerate() {
+        text.push_str(&format!(
+            "{} | {} | {}\n",
+            index + 1,
+            escape_field(&track.artist),
+            escape_field(&track.title)
+        ));
+    }
+    text
+}
+
+/// Parse edited text back to tracks.
+///
+/// Tracks that refer to an original track number keep their time information.
+fn parse_edited_text(text: &str, original_tracks: &[Track]) -> Result<Vec<Track>> {
+    let mut tracks: Vec<Track> = Vec::new();
+    for (line_number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let mut parts = split_fields(line);
+        if parts.len() < 3 {
+            anyhow::bail!("Invalid line {}: '{line}'", line_number + 1);
+        }
+        // Keep an unescaped separator in the title as text
+        let title = parts.split_off(2).join("|");
+        let [number, artist] = [parts[0].trim(), parts[1].trim()];
+        let title = title.trim();
+        let mut track = if number.is_empty() {
+            Track::new(String::new(), String::new())
+        } else {
+            let index: usize = number
+                .parse()
+                .with_context(|| format!("Invalid track number on line {}: '{number}'", line_number + 1))?;
+            index
+                .checked_sub(1)
+                .and_then(|i| original_tracks.get(i))
+                .cloned()
+                .with_context(|| format!("Unknown track number on line {}: {index}", line_number + 1))?
+        };
+        track.artist = artist.to_string();
+        track.title = title.to_string();
+        tracks.push(track);
+    }
+    Ok(tracks)
+}
+
+/// Escape the field separator and backslashes in a track field.
+fn escape_field(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('|', "\\|")
+}
+
+/// Split a line into fields at unescaped `|` characters.
+///
+/// Escaped separators and backslashes are unescaped, other backslashes are kept as is.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        let field = fields.last_mut().expect("There is always at least one field");
+        match c {
+            '\\' if matches!(chars.peek(), Some('|' | '\\')) => field.extend(chars.next()),
+            '|' => fields.push(String::new()),
+            _ => field.push(c),
+        }
+    }
+    fields
+}
+
+/// Open file in the editor from `VISUAL` or `EDITOR` and wait for it to close.
+fn open_editor(file: &Path) -> Result<()> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(target_os = "windows") {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().context("Empty editor command")?;
+    let status = process::Command::new(program)
+        .args(parts)
+        .arg(PathBuf::from(file))
+        .status()
+        .with_context(|| format!("Failed to start editor: {editor}"))?;
+    if !status.success() {
+        anyhow::bail!("Editor exited with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeDelta;
+
+    #[test]
+    fn edit_round_trip() {
+        let tracks = vec![
+            Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::try_minutes(3).unwrap(),
+            Track::new("Other".to_string(), "Song".to_string()),
+        ];
+        let text = render_editable_text(&tracks);
+        let parsed = parse_edited_text(&text, &tracks).unwrap();
+        assert_eq!(parsed, tracks);
+        assert_eq!(parsed[0].play_time, TimeDelta::try_minutes(3));
+    }
+
+    #[test]
+    fn edit_changes() {
+        let tracks = vec![
+            Track::new("Artist".to_string(), "Titel".to_string()) + TimeDelta::try_minutes(3).unwrap(),
+            Track::new("Other".to_string(), "Song".to_string()),
+        ];
+        let text = "# comment\n1 | Artist | Title\n| New | Track\n";
+        let parsed = parse_edited_text(text, &tracks).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].title, "Title");
+        assert_eq!(parsed[0].play_time, TimeDelta::try_minutes(3));
+        assert_eq!(parsed[1].artist, "New");
+        assert!(parsed[1].play_time.is_none());
+    }
+
+    #[test]
+    fn edit_round_trip_with_separator_in_names() {
+        let tracks = vec![
+            Track::new("Artist | Band".to_string(), "Title|Remix".to_string()),
+            Track::new("AC\\DC".to_string(), "Back\\|slash".to_string()),
+        ];
+        let text = render_editable_text(&tracks);
+        assert!(text.contains("1 | Artist \\| Band | Title\\|Remix\n"));
+        let parsed = parse_edited_text(&text, &tracks).unwrap();
+        assert_eq!(parsed, tracks);
+
+        // Unescaped separators after the artist stay in the title
+        let parsed = parse_edited_text("| Artist | Title | Remix", &[]).unwrap();
+        assert_eq!(parsed[0].title, "Title | Remix");
+    }
+
+    #[test]
+    fn edit_invalid_lines() {
+        let tracks = vec![Track::new("Artist".to_string(), "Title".to_string())];
+        assert!(parse_edited_text("Artist - Title", &tracks).is_err());
+        assert!(parse_edited_text("5 | Artist | Title", &tracks).is_err());
+    }
+}
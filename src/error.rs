@@ -0,0 +1,98 @@
+use thiserror::Error;
+
+/// Errors from reading, processing, and writing playlists.
+#[derive(Debug, Error)]
+pub enum PlaylistError {
+    /// Input file or output type is not supported
+    #[error("{0}")]
+    UnsupportedFormat(String),
+    /// Required column is missing from the input file
+    #[error("{}", missing_field_message(.kind, .field, .suggestion.as_deref(), .found))]
+    MissingField {
+        /// Kind of input file, for example "Serato CSV"
+        kind: String,
+        field: String,
+        /// Header that was most likely meant as the missing field
+        suggestion: Option<String>,
+        /// Headers found in the input file
+        found: Vec<String>,
+    },
+    /// Input file contents can't be used, for example unreadable rows in strict mode
+    #[error("{0}")]
+    InvalidData(String),
+    /// Date, time, or duration can't be parsed
+    #[error("{0}")]
+    ParseDate(String),
+    /// Requested change does not fit the playlist, for example removing a track that does not exist
+    #[error("{0}")]
+    InvalidArgument(String),
+    /// Reading or writing a file failed
+    #[error("{message}")]
+    Io {
+        message: String,
+        #[source]
+        source: std::io::Error,
+    },
+    #[error(transparent)]
+    Csv(#[from] csv::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[cfg(feature = "xlsx")]
+    #[error(transparent)]
+    Xlsx(#[from] rust_xlsxwriter::XlsxError),
+    /// Downloading a remote input failed
+    #[cfg(feature = "net")]
+    #[error("Request failed: {0}")]
+    Http(#[from] reqwest::Error),
+}
+
+/// Result type used by the playlist library.
+pub type Result<T, E = PlaylistError> = std::result::Result<T, E>;
+
+impl PlaylistError {
+    /// Create an I/O error with a description of what failed.
+    pub fn io(message: impl Into<String>, source: std::io::Error) -> Self {
+        PlaylistError::Io {
+            message: message.into(),
+            source,
+        }
+    }
+}
+
+fn missing_field_message(kind: &str, field: &str, suggestion: Option<&str>, found: &[String]) -> String {
+    let found = found.iter().map(|h| format!("'{h}'")).collect::<Vec<_>>().join(", ");
+    match suggestion {
+        Some(suggestion) => {
+            format!("{kind} missing required field: '{field}'. Did you mean '{suggestion}'?\nFound headers: {found}")
+        }
+        None => format!("{kind} missing required field: '{field}'\nFound headers: {found}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_field_display() {
+        let error = PlaylistError::MissingField {
+            kind: "Serato CSV".to_string(),
+            field: "name".to_string(),
+            suggestion: Some("Track Title".to_string()),
+            found: vec!["artist".to_string(), "Track Title".to_string()],
+        };
+        assert_eq!(
+            error.to_string(),
+            "Serato CSV missing required field: 'name'. Did you mean 'Track Title'?\nFound headers: 'artist', 'Track Title'"
+        );
+    }
+
+    #[test]
+    fn match_error_kind() {
+        let error: PlaylistError = serde_json::from_str::<u8>("x").unwrap_err().into();
+        assert!(matches!(error, PlaylistError::Json(_)));
+        let error = PlaylistError::io("Failed to read", std::io::Error::other("disk"));
+        assert_eq!(error.to_string(), "Failed to read");
+        assert!(std::error::Error::source(&error).is_some());
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::Cursor;
+
+use percent_encoding::percent_decode_str;
+use reqwest::{Client, Url};
+use tokio::task::JoinSet;
+
+use super::error::{PlaylistError, Result};
+use super::options::ParseOptions;
+use super::playlist::Playlist;
+
+/// Client for downloading playlists and other remote inputs.
+///
+/// Requests are asynchronous so multiple downloads can run concurrently.
+/// Synchronous callers can use [`fetch_playlist_blocking`] instead.
+#[derive(Debug, Clone, Default)]
+pub struct Fetcher {
+    client: Client,
+}
+
+impl Fetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Download the contents of a URL.
+    pub async fn fetch_bytes(&self, url: &str) -> Result<Vec<u8>> {
+        let url = parse_url(url)?;
+        let response = self.client.get(url).send().await?.error_for_status()?;
+        Ok(response.bytes().await?.to_vec())
+    }
+
+    /// Download and parse a playlist.
+    ///
+    /// The playlist type is detected from the file name at the end of the URL path,
+    /// unless the input format is given in the options.
+    pub async fn fetch_playlist(&self, url: &str, options: &ParseOptions) -> Result<Playlist> {
+        let file_name = file_name_from_url(&parse_url(url)?)?;
+        let contents = self.fetch_bytes(url).await?;
+        Playlist::from_reader(Cursor::new(contents), &file_name, options)
+    }
+
+    /// Download and parse playlists concurrently.
+    ///
+    /// A failed download does not stop the others,
+    /// and the results are in the same order as the URLs.
+    pub async fn fetch_playlists(&self, urls: &[String], options: &ParseOptions) -> Vec<Result<Playlist>> {
+        let mut tasks = JoinSet::new();
+        for (index, url) in urls.iter().enumerate() {
+            let fetcher = self.clone();
+            let url = url.clone();
+            let options = options.clone();
+            tasks.spawn(async move { (index, fetcher.fetch_playlist(&url, &options).await) });
+        }
+        let mut results: Vec<Option<Result<Playlist>>> = urls.iter().map(|_| None).collect();
+        while let Some(task) = tasks.join_next().await {
+            match task {
+                Ok((index, result)) => results[index] = Some(result),
+                Err(error) => tracing::error!("Download task failed: {error}"),
+            }
+        }
+        results
+            .into_iter()
+            .map(|result| result.unwrap_or_else(|| Err(PlaylistError::InvalidData("Download task failed".to_string()))))
+            .collect()
+    }
+}
+
+/// Download and parse a playlist, waiting for the download to finish.
+pub fn fetch_playlist_blocking(url: &str, options: &ParseOptions) -> Result<Playlist> {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| PlaylistError::io("Failed to start async runtime", e))?;
+    runtime.block_on(Fetcher::new().fetch_playlist(url, options))
+}
+
+/// Check if the input is an HTTP or HTTPS URL instead of a file path.
+pub fn is_url(input: &str) -> bool {
+    let input = input.trim();
+    ["http://", "https://"].iter().any(|scheme| {
+        input
+            .get(..scheme.len())
+            .is_some_and(|start| start.eq_ignore_ascii_case(scheme))
+    })
+}
+
+fn parse_url(url: &str) -> Result<Url> {
+    Url::parse(url.trim()).map_err(|error| PlaylistError::InvalidArgument(format!("Invalid URL '{url}': {error}")))
+}
+
+/// Get the decoded file name from the last segment of the URL path.
+fn file_name_from_url(url: &Url) -> Result<String> {
+    url.path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .map(|segment| percent_decode_str(segment).decode_utf8_lossy().to_string())
+        .filter(|name| !name.is_empty())
+        .ok_or_else(|| PlaylistError::InvalidArgument(format!("URL does not end with a file name: '{url}'")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    #[test]
+    fn url_inputs() {
+        assert!(is_url("https://example.com/set.csv"));
+        assert!(is_url(" HTTP://example.com/set.csv"));
+        assert!(!is_url("/music/set.csv"));
+        assert!(!is_url("http"));
+
+        let url = parse_url("https://example.com/sets/serato%208.3.2024.csv?download=1").unwrap();
+        assert_eq!(file_name_from_url(&url).unwrap(), "serato 8.3.2024.csv");
+        let url = parse_url("https://example.com/").unwrap();
+        assert!(matches!(
+            file_name_from_url(&url),
+            Err(PlaylistError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn fetch_from_local_server() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0; 1024];
+            let _ = stream.read(&mut request).unwrap();
+            let body = "Artist,Title\nA,One\nB,Two\n";
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            )
+            .unwrap();
+        });
+        let playlist =
+            fetch_playlist_blocking(&format!("http://{address}/friday.csv"), &ParseOptions::default()).unwrap();
+        server.join().unwrap();
+        assert_eq!(playlist.name, "friday");
+        assert_eq!(playlist.len(), 2);
+    }
+}
@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+use regex::{Regex, RegexBuilder};
+
+use super::error::{PlaylistError, Result};
+use super::track::Track;
+
+/// Track field a filter is matched against
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FilterField {
+    Artist,
+    Title,
+    /// Either artist or title
+    Track,
+}
+
+/// How the filter value is matched
+#[derive(Debug, Clone)]
+pub enum FilterPattern {
+    /// Case-insensitive substring
+    Contains(String),
+    /// Case-insensitive regular expression
+    Regex(Regex),
+}
+
+/// Include or exclude filter for tracks.
+///
+/// Parsed from expressions of the form `<field><operator><value>`,
+/// where field is `artist`, `title`, or `track`, and the operator is one of:
+/// - `=` contains substring
+/// - `~=` matches regex
+/// - `!=` does not contain substring
+/// - `!~=` does not match regex
+///
+/// The value can be wrapped in double quotes. Matching ignores case.
+#[derive(Debug, Clone)]
+pub struct TrackFilter {
+    pub field: FilterField,
+    pub pattern: FilterPattern,
+    pub exclude: bool,
+}
+
+impl TrackFilter {
+    /// Check if the track passes this filter.
+    pub fn accepts(&self, track: &Track) -> bool {
+        let matches = match self.field {
+            FilterField::Artist => self.pattern.matches(&track.artist),
+            FilterField::Title => self.pattern.matches(&track.title),
+            FilterField::Track => self.pattern.matches(&track.artist) || self.pattern.matches(&track.title),
+        };
+        matches != self.exclude
+    }
+}
+
+impl FilterPattern {
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            FilterPattern::Contains(value) => text.to_lowercase().contains(value),
+            FilterPattern::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+impl FromStr for FilterField {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<FilterField> {
+        match input.to_lowercase().trim() {
+            "artist" => Ok(FilterField::Artist),
+            "title" => Ok(FilterField::Title),
+            "track" => Ok(FilterField::Track),
+            _ => Err(PlaylistError::InvalidArgument(format!(
+                "Unsupported filter field: '{input}'"
+            ))),
+        }
+    }
+}
+
+/// Parse filter expression such as `artist~="guetta"` or `title!=jingle`.
+impl FromStr for TrackFilter {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<TrackFilter> {
+        let operator_index = input
+            .find(['=', '~', '!'])
+            .ok_or_else(|| PlaylistError::InvalidArgument(format!("Missing operator in filter: '{input}'")))?;
+        let (field, rest) = input.split_at(operator_index);
+        let field = FilterField::from_str(field)?;
+        let (exclude, is_regex, value) = if let Some(value) = rest.strip_prefix("!~=") {
+            (true, true, value)
+        } else if let Some(value) = rest.strip_prefix("~=") {
+            (false, true, value)
+        } else if let Some(value) = rest.strip_prefix("!=") {
+            (true, false, value)
+        } else if let Some(value) = rest.strip_prefix('=') {
+            (false, false, value)
+        } else {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Unsupported operator in filter: '{input}'"
+            )));
+        };
+
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        if value.is_empty() {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Empty value in filter: '{input}'"
+            )));
+        }
+
+        let pattern = if is_regex {
+            FilterPattern::Regex(
+                RegexBuilder::new(value)
+                    .case_insensitive(true)
+                    .build()
+                    .map_err(|error| {
+                        PlaylistError::InvalidArgument(format!("Invalid regex in filter: '{input}': {error}"))
+                    })?,
+            )
+        } else {
+            FilterPattern::Contains(value.to_lowercase())
+        };
+
+        Ok(TrackFilter {
+            field,
+            pattern,
+            exclude,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(artist: &str, title: &str) -> Track {
+        Track::new(artist.to_string(), title.to_string())
+    }
+
+    #[test]
+    fn parse_filters() {
+        let filter = TrackFilter::from_str(r#"artist~="guetta""#).unwrap();
+        assert_eq!(filter.field, FilterField::Artist);
+        assert!(matches!(filter.pattern, FilterPattern::Regex(_)));
+        assert!(!filter.exclude);
+
+        let filter = TrackFilter::from_str("title!=jingle").unwrap();
+        assert_eq!(filter.field, FilterField::Title);
+        assert!(matches!(filter.pattern, FilterPattern::Contains(ref value) if value == "jingle"));
+        assert!(filter.exclude);
+
+        assert!(TrackFilter::from_str("track!~=^ID$").unwrap().exclude);
+        assert!(TrackFilter::from_str("album=test").is_err());
+        assert!(TrackFilter::from_str("artist").is_err());
+        assert!(TrackFilter::from_str("artist=").is_err());
+        assert!(TrackFilter::from_str("artist~=(").is_err());
+    }
+
+    #[test]
+    fn filter_tracks() {
+        let filter = TrackFilter::from_str(r#"artist~="guetta""#).unwrap();
+        assert!(filter.accepts(&track("David Guetta", "Titanium")));
+        assert!(!filter.accepts(&track("Sia", "Titanium")));
+
+        let filter = TrackFilter::from_str("track!=station ident").unwrap();
+        assert!(filter.accepts(&track("David Guetta", "Titanium")));
+        assert!(!filter.accepts(&track("Radio", "Station Ident 3")));
+
+        let filter = TrackFilter::from_str("title!~=^jingle\\b").unwrap();
+        assert!(!filter.accepts(&track("Radio", "Jingle 1")));
+        assert!(filter.accepts(&track("Artist", "Not a jingle")));
+    }
+}
@@ -1,17 +1,117 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
+use chrono::NaiveDateTime;
+
+use super::error::Result;
+use super::options::ParseOptions;
 use super::playlist::Playlist;
-use super::track::Track;
-use super::types::{FileFormat, PlaylistType};
+use super::reader::{PlaylistInput, PlaylistReader, Row, Rows};
+use super::track::{Track, METADATA_FIELDS};
+use super::types::{FileFormat, InputFormat, PlaylistType};
 use super::utils;
 
-/// Read a formatted CSV playlist file.
-pub fn read_formatted_csv(path: &Path, data: Vec<BTreeMap<String, String>>) -> anyhow::Result<Playlist> {
-    // TODO: fix data reading
-    let playlist_name = path.file_stem().unwrap().to_string_lossy().to_string();
-    let playlist_date = None;
-    let tracks = parse_formatted_tracks_from_data(&data);
+/// Columns written by this tool, including the time column from older versions.
+const FORMATTED_COLUMNS: [&str; 12] = [
+    "#",
+    "Artist",
+    "",
+    "Title",
+    "Playtime",
+    "Start time",
+    "End time",
+    "Time",
+    "DJ",
+    "Plays",
+    "Mashup",
+    "Notes",
+];
+
+/// Header names accepted for the title column, compared case-insensitively.
+const TITLE_ALIASES: [&str; 4] = ["Title", "Song", "Name", "Track Title"];
+
+/// Timestamp formats accepted for the start and end time columns:
+/// the default output format, ISO 8601, and ISO 8601 with a space separator.
+const TIMESTAMP_FORMATS: [&str; 3] = ["%Y.%m.%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M:%S"];
+
+/// Check if CSV headers are from a playlist formatted by this tool or a lightly hand-edited one.
+///
+/// Headers are compared case-insensitively and the title column can use any of the title aliases.
+/// The exact lowercase `name` and `artist` headers of a Serato export are not treated as formatted.
+pub fn is_formatted_header<'a>(headers: impl IntoIterator<Item = &'a str>) -> bool {
+    let headers: Vec<&str> = headers.into_iter().map(str::trim).collect();
+    if headers.contains(&"name") && headers.contains(&"artist") {
+        return false;
+    }
+    let has_artist = headers.iter().any(|header| header.eq_ignore_ascii_case("Artist"));
+    let has_title = headers
+        .iter()
+        .any(|header| TITLE_ALIASES.iter().any(|alias| alias.eq_ignore_ascii_case(header)));
+    has_artist && has_title
+}
+
+/// Get the canonical name for a formatted CSV column.
+///
+/// Known columns are matched case-insensitively, title aliases map to `Title`,
+/// and unknown columns are returned unchanged.
+fn canonical_column(header: &str) -> String {
+    let header = header.trim();
+    if TITLE_ALIASES.iter().any(|alias| alias.eq_ignore_ascii_case(header)) {
+        return "Title".to_string();
+    }
+    FORMATTED_COLUMNS
+        .iter()
+        .chain(METADATA_FIELDS.iter())
+        .find(|column| column.eq_ignore_ascii_case(header))
+        .map_or_else(|| header.to_string(), ToString::to_string)
+}
+
+/// Rename the header columns to their canonical names.
+///
+/// A column that already uses the canonical name takes precedence over an alias.
+fn canonical_columns(headers: &BTreeMap<String, usize>) -> BTreeMap<String, usize> {
+    let mut canonical: BTreeMap<String, usize> = BTreeMap::new();
+    for (column, index) in headers {
+        let name = canonical_column(column);
+        if name == *column || !headers.contains_key(&name) {
+            canonical.entry(name).or_insert(*index);
+        }
+    }
+    canonical
+}
+
+/// Reader for CSV files written by this tool.
+pub struct FormattedCsvReader;
+
+impl PlaylistReader for FormattedCsvReader {
+    fn name(&self) -> &str {
+        "Formatted CSV"
+    }
+
+    fn input_format(&self) -> Option<InputFormat> {
+        Some(InputFormat::FormattedCsv)
+    }
+
+    fn detect(&self, input: &PlaylistInput) -> bool {
+        input.file_format == FileFormat::Csv && is_formatted_header(input.headers.keys().map(String::as_str))
+    }
+
+    fn read(&self, mut input: PlaylistInput, options: &ParseOptions) -> Result<Playlist> {
+        let columns = Arc::new(canonical_columns(&input.headers));
+        let rows = std::mem::replace(&mut input.rows, Box::new(std::iter::empty()));
+        input.rows = Box::new(rows.map(move |row| row.map(|row| row.with_columns(Arc::clone(&columns)))));
+        if options.strict {
+            input.check_rows(0, formatted_row_issues);
+        }
+        read_formatted_csv(&input.path, input.name, input.rows)
+    }
+}
+
+/// Read a formatted CSV playlist file from rows with canonical column names.
+pub fn read_formatted_csv(path: &Path, name: String, rows: Rows) -> Result<Playlist> {
+    let tracks = parse_formatted_tracks(rows)?;
+    let playlist_date = tracks.iter().find_map(|t| t.start_time);
     let total_duration = utils::get_total_playtime(&tracks);
     let max_artist_length: usize = tracks.iter().map(|t| t.artist_length()).max().unwrap_or(0);
     let max_title_length: usize = tracks.iter().map(|t| t.title_length()).max().unwrap_or(0);
@@ -21,29 +121,162 @@ pub fn read_formatted_csv(path: &Path, data: Vec<BTreeMap<String, String>>) -> a
         date: playlist_date,
         file: PathBuf::from(path),
         file_format: FileFormat::Csv,
-        name: playlist_name,
+        name,
         playlist_type: PlaylistType::Formatted,
         tracks,
         max_artist_length,
         max_title_length,
         max_playtime_length,
+        timezone: None,
         total_duration,
     })
 }
 
-/// Parse track data from dictionary
-pub fn parse_formatted_tracks_from_data(data: &[BTreeMap<String, String>]) -> Vec<Track> {
+/// Parse tracks from rows with canonical column names.
+///
+/// Rows without an artist or a title are skipped.
+pub fn parse_formatted_tracks(rows: impl Iterator<Item = Result<Row>>) -> Result<Vec<Track>> {
     let mut tracks: Vec<Track> = Vec::new();
-    for row in data.iter() {
-        let artist = row.get("Artist").unwrap_or(&"".to_string()).to_string();
-        let name = row.get("Title").unwrap_or(&"".to_string()).to_string();
-        // TODO: parse times
-        // let playtime = row.get("playtime");
-        // let start_time = row.get("start time");
-        // let end_time = row.get("end time");
+    for row in rows {
+        let row = row?;
+        let artist = row.get("Artist").unwrap_or_default().to_string();
+        let name = row.get("Title").unwrap_or_default().to_string();
         if !artist.is_empty() && !name.is_empty() {
-            tracks.push(Track::new(artist, name))
+            let start_time = column_value(&row, "Start time").and_then(parse_timestamp);
+            let end_time = column_value(&row, "End time").and_then(parse_timestamp);
+            let play_time = column_value(&row, "Playtime").and_then(|playtime| {
+                utils::parse_playtime(playtime)
+                    .map_err(|error| tracing::warn!("{error}"))
+                    .ok()
+            });
+            let mut track = Track::builder()
+                .artist(artist)
+                .title(name)
+                .start_time(start_time)
+                .end_time(end_time)
+                .play_time(play_time)
+                .number(column_value(&row, "#").and_then(|number| number.parse().ok()))
+                .build();
+            for field in METADATA_FIELDS {
+                if let Some(value) = row.get(field) {
+                    track.set_metadata(field, value);
+                }
+            }
+            track.notes = row
+                .get("Notes")
+                .map(|notes| notes.trim())
+                .filter(|notes| !notes.is_empty())
+                .map(ToString::to_string);
+            track.dj = column_value(&row, "DJ").map(ToString::to_string);
+            if let Some(plays) = column_value(&row, "Plays").and_then(|plays| plays.parse().ok()) {
+                track.plays = plays;
+            }
+            if let Some(mashups) = row.get("Mashup") {
+                track.mashups = mashups
+                    .split(';')
+                    .filter(|name| !name.trim().is_empty())
+                    .map(Track::from_name)
+                    .collect();
+            }
+            track.add_extra_columns(&row, &FORMATTED_COLUMNS);
+            tracks.push(track)
         }
     }
-    tracks
+    Ok(tracks)
+}
+
+/// Get the trimmed value of a column if it is not empty.
+fn column_value<'a>(row: &'a Row, column: &str) -> Option<&'a str> {
+    row.get(column)
+        .map(|value| value.trim())
+        .filter(|value| !value.is_empty())
+}
+
+/// Describe the values in a formatted track row with canonical column names that can't be parsed.
+pub fn formatted_row_issues(row: &Row) -> Vec<String> {
+    let mut issues: Vec<String> = ["Start time", "End time"]
+        .into_iter()
+        .filter_map(|column| {
+            let value = column_value(row, column)?;
+            timestamp_from_str(value)
+                .is_none()
+                .then(|| format!("invalid {} '{value}'", column.to_lowercase()))
+        })
+        .collect();
+    if let Some(playtime) = column_value(row, "Playtime") {
+        if utils::parse_playtime(playtime).is_err() {
+            issues.push(format!("invalid playtime '{playtime}'"));
+        }
+    }
+    issues
+}
+
+/// Parse a start or end timestamp in one of the supported formats.
+fn parse_timestamp(input: &str) -> Option<NaiveDateTime> {
+    let timestamp = timestamp_from_str(input);
+    if timestamp.is_none() {
+        tracing::warn!("Unrecognized timestamp: '{input}'");
+    }
+    timestamp
+}
+
+fn timestamp_from_str(input: &str) -> Option<NaiveDateTime> {
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formatted_header_detection() {
+        assert!(is_formatted_header(["Artist", "", "Title", "Playtime"]));
+        assert!(is_formatted_header(["artist", "", "title"]));
+        assert!(is_formatted_header(["ARTIST", "Song"]));
+        assert!(is_formatted_header(["#", "Artist", "", "Name", "Notes"]));
+        assert!(is_formatted_header(["Artist", "Track Title"]));
+        assert!(!is_formatted_header([
+            "name",
+            "artist",
+            "start time",
+            "end time",
+            "playtime"
+        ]));
+        assert!(!is_formatted_header(["Title", "Playtime"]));
+    }
+
+    #[test]
+    fn row_issues() {
+        let row = Row::from_iter([
+            ("Artist", "A"),
+            ("Title", "T"),
+            ("Playtime", "5:75"),
+            ("Start time", "2024-03-08T20:00:00"),
+            ("End time", "yesterday"),
+        ]);
+        assert_eq!(
+            formatted_row_issues(&row),
+            vec!["invalid end time 'yesterday'", "invalid playtime '5:75'"]
+        );
+    }
+
+    #[test]
+    fn canonical_header_columns() {
+        let headers: BTreeMap<String, usize> = ["artist", "Song", "start time", "bpm", "Comment"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, header)| (header.to_string(), index))
+            .collect();
+        let columns = canonical_columns(&headers);
+        assert_eq!(
+            columns.keys().collect::<Vec<_>>(),
+            vec!["Artist", "BPM", "Comment", "Start time", "Title"]
+        );
+        assert_eq!(columns.get("Title"), Some(&1));
+
+        let headers = BTreeMap::from([("Title".to_string(), 0), ("Name".to_string(), 1)]);
+        assert_eq!(canonical_columns(&headers).get("Title"), Some(&0));
+    }
 }
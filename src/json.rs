@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+
+use chrono::NaiveDateTime;
+use schemars::{JsonSchema, Schema};
+use serde::{Deserialize, Serialize};
+
+use super::error::{PlaylistError, Result};
+use super::options::FormatOptions;
+use super::playlist::Playlist;
+use super::track::Track;
+
+/// Version of the JSON export format.
+///
+/// Incremented when a field is removed, renamed, or changes type.
+/// Adding a new field is not a breaking change and keeps the version.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Timestamp format used for all times in the JSON export.
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+/// Playlist in the JSON export format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[schemars(title = "Playlist", description = "Playlist exported by playlist-formatter")]
+pub struct JsonPlaylist {
+    /// Version of the export format, incremented on breaking changes
+    #[schemars(extend("const" = SCHEMA_VERSION))]
+    pub schema_version: u32,
+    pub name: String,
+    /// DJ software the playlist was exported from, or `Formatted`
+    pub playlist_type: String,
+    /// Start time of the first track, as `YYYY-MM-DDTHH:MM:SS`
+    pub date: Option<String>,
+    pub total_duration_seconds: Option<i64>,
+    pub tracks: Vec<JsonTrack>,
+}
+
+/// Track in the JSON export format.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct JsonTrack {
+    /// Track number as shown in the other output formats
+    pub number: Option<usize>,
+    pub artist: String,
+    pub title: String,
+    /// Start time as `YYYY-MM-DDTHH:MM:SS`
+    pub start_time: Option<String>,
+    /// End time as `YYYY-MM-DDTHH:MM:SS`
+    pub end_time: Option<String>,
+    pub playtime_seconds: Option<i64>,
+    pub bpm: Option<f64>,
+    pub key: Option<String>,
+    pub genre: Option<String>,
+    pub album: Option<String>,
+    pub label: Option<String>,
+    pub year: Option<u16>,
+    /// Star rating from 1 to 5
+    pub rating: Option<u8>,
+    /// Full length of the audio file
+    pub length_seconds: Option<i64>,
+    /// Date the track was added to the library, as `YYYY-MM-DD`
+    pub date_added: Option<String>,
+    pub deck: Option<u8>,
+    pub dj: Option<String>,
+    /// Number of separate plays merged into this track
+    pub plays: usize,
+    pub notes: Option<String>,
+    /// Secondary tracks played on top of this track
+    pub mashups: Vec<JsonTrack>,
+    /// Values from input columns that are not otherwise used
+    pub extras: BTreeMap<String, String>,
+}
+
+impl JsonPlaylist {
+    pub fn from_playlist(playlist: &Playlist, options: &FormatOptions) -> Self {
+        let numbered = options.has_number_column();
+        Self {
+            schema_version: SCHEMA_VERSION,
+            name: playlist.name.clone(),
+            playlist_type: playlist.playlist_type.to_string(),
+            date: playlist.date.map(format_timestamp),
+            total_duration_seconds: playlist.total_duration.map(|d| d.num_seconds()),
+            tracks: playlist
+                .tracks
+                .iter()
+                .enumerate()
+                .map(|(index, track)| {
+                    JsonTrack::from_track(
+                        track,
+                        numbered.then(|| playlist.track_number(index, options)),
+                        options.include_extras,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    /// Parse a JSON export, checking that it uses the current schema version.
+    pub fn from_json(input: &str) -> Result<Self> {
+        let value: serde_json::Value = serde_json::from_str(input)?;
+        match value.get("schema_version").and_then(serde_json::Value::as_u64) {
+            Some(version) if version == u64::from(SCHEMA_VERSION) => Ok(serde_json::from_value(value)?),
+            Some(version) => Err(PlaylistError::UnsupportedFormat(format!(
+                "Unsupported JSON schema version {version}, expected {SCHEMA_VERSION}"
+            ))),
+            None => Err(PlaylistError::MissingField {
+                kind: "JSON export".to_string(),
+                field: "schema_version".to_string(),
+                suggestion: None,
+                found: value
+                    .as_object()
+                    .map(|object| object.keys().cloned().collect())
+                    .unwrap_or_default(),
+            }),
+        }
+    }
+}
+
+impl JsonTrack {
+    fn from_track(track: &Track, number: Option<usize>, include_extras: bool) -> Self {
+        Self {
+            number,
+            artist: track.artist.clone(),
+            title: track.title.clone(),
+            start_time: track.start_time.map(format_timestamp),
+            end_time: track.end_time.map(format_timestamp),
+            playtime_seconds: track.play_time.map(|d| d.num_seconds()),
+            bpm: track.bpm,
+            key: track.key.clone(),
+            genre: track.genre.clone(),
+            album: track.album.clone(),
+            label: track.label.clone(),
+            year: track.year,
+            rating: track.rating,
+            length_seconds: track.length.map(|d| d.num_seconds()),
+            date_added: track.date_added.map(|date| date.format("%Y-%m-%d").to_string()),
+            deck: track.deck,
+            dj: track.dj.clone(),
+            plays: track.plays,
+            notes: track.notes.clone(),
+            mashups: track
+                .mashups
+                .iter()
+                .map(|mashup| Self::from_track(mashup, None, include_extras))
+                .collect(),
+            extras: if include_extras {
+                track.extras.clone()
+            } else {
+                BTreeMap::new()
+            },
+        }
+    }
+}
+
+/// Get the JSON Schema document for the JSON export format.
+pub fn schema() -> Schema {
+    let mut schema = schemars::schema_for!(JsonPlaylist);
+    schema.insert("version".to_string(), SCHEMA_VERSION.into());
+    schema
+}
+
+fn format_timestamp(timestamp: NaiveDateTime) -> String {
+    timestamp.format(TIMESTAMP_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schema_declares_version() {
+        let schema = serde_json::to_value(schema()).unwrap();
+        assert_eq!(schema["version"], SCHEMA_VERSION);
+        assert_eq!(schema["title"], "Playlist");
+        assert_eq!(schema["properties"]["schema_version"]["const"], SCHEMA_VERSION);
+        assert!(schema["$defs"]["JsonTrack"]["properties"]["mashups"].is_object());
+    }
+
+    #[test]
+    fn reject_other_versions() {
+        let json = r#"{"schema_version": 99, "name": "", "playlist_type": "Serato", "tracks": []}"#;
+        assert!(matches!(
+            JsonPlaylist::from_json(json),
+            Err(PlaylistError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            JsonPlaylist::from_json(r#"{"name": ""}"#),
+            Err(PlaylistError::MissingField { .. })
+        ));
+    }
+}
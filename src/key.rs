@@ -0,0 +1,234 @@
+use std::str::FromStr;
+use std::sync::LazyLock;
+
+use regex::Regex;
+
+use super::error::{PlaylistError, Result};
+use super::track::Track;
+use super::types::KeyNotation;
+
+static RE_STANDARD_KEY: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^([A-Ga-g])([#♯b♭]?)\s*(m|min|minor|M|maj|major)?$")
+        .expect("Failed to create regex pattern for standard key")
+});
+static RE_CAMELOT_KEY: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^(\d{1,2})\s*([ABab])$").expect("Failed to create regex pattern for Camelot key"));
+
+/// Standard names for major keys indexed by pitch class starting from C.
+const MAJOR_NAMES: [&str; 12] = ["C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B"];
+/// Standard names for minor keys indexed by pitch class starting from C.
+const MINOR_NAMES: [&str; 12] = [
+    "Cm", "C#m", "Dm", "Ebm", "Em", "Fm", "F#m", "Gm", "Abm", "Am", "Bbm", "Bm",
+];
+
+/// Musical key of a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MusicalKey {
+    /// Pitch class of the root note, where 0 is C and 11 is B
+    pitch_class: u8,
+    minor: bool,
+}
+
+impl MusicalKey {
+    /// Get the Camelot wheel number from 1 to 12.
+    pub const fn camelot_number(&self) -> u8 {
+        // Major keys move one step on the wheel per fifth, starting from C at 8B.
+        // Minor keys share the number of their relative major key.
+        let major_pitch_class = if self.minor {
+            (self.pitch_class + 3) % 12
+        } else {
+            self.pitch_class
+        };
+        (major_pitch_class * 7 + 7) % 12 + 1
+    }
+
+    /// Get the Camelot notation, for example `8A` for A minor.
+    pub fn camelot(&self) -> String {
+        format!("{}{}", self.camelot_number(), if self.minor { 'A' } else { 'B' })
+    }
+
+    /// Get the standard notation, for example `F#m`.
+    pub fn standard(&self) -> String {
+        let names = if self.minor { MINOR_NAMES } else { MAJOR_NAMES };
+        names[self.pitch_class as usize].to_string()
+    }
+
+    /// Format key using the given notation.
+    pub fn to_notation(&self, notation: KeyNotation) -> String {
+        match notation {
+            KeyNotation::Standard => self.standard(),
+            KeyNotation::Camelot => self.camelot(),
+        }
+    }
+
+    /// Check if mixing from this key to the other key is harmonically compatible.
+    ///
+    /// Compatible keys are the same key, the relative major or minor key with the same Camelot number,
+    /// and the keys one step away on the Camelot wheel with the same letter.
+    pub const fn is_compatible(&self, other: &MusicalKey) -> bool {
+        let (a, b) = (self.camelot_number(), other.camelot_number());
+        if a == b {
+            return true;
+        }
+        self.minor == other.minor && (a % 12 + 1 == b || b % 12 + 1 == a)
+    }
+
+    /// Create a key from a Camelot wheel number and letter.
+    fn from_camelot(number: u8, minor: bool) -> Option<Self> {
+        if !(1..=12).contains(&number) {
+            return None;
+        }
+        // Inverse of the Camelot number calculation since 7 is its own inverse modulo 12
+        let major_pitch_class = (7 * (number - 1) + 11) % 12;
+        let pitch_class = if minor {
+            (major_pitch_class + 9) % 12
+        } else {
+            major_pitch_class
+        };
+        Some(Self { pitch_class, minor })
+    }
+}
+
+/// Parse a key from standard notation like `F#m`, `Bb`, or `A minor`, or Camelot notation like `11A`.
+impl FromStr for MusicalKey {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<MusicalKey> {
+        let input = input.trim();
+        if let Some(caps) = RE_CAMELOT_KEY.captures(input) {
+            let minor = caps[2].eq_ignore_ascii_case("A");
+            return caps[1]
+                .parse()
+                .ok()
+                .and_then(|number| Self::from_camelot(number, minor))
+                .ok_or_else(|| PlaylistError::InvalidData(format!("Invalid Camelot key: '{input}'")));
+        }
+        let caps = RE_STANDARD_KEY
+            .captures(input)
+            .ok_or_else(|| PlaylistError::InvalidData(format!("Unrecognized key: '{input}'")))?;
+        let natural: u8 = match caps[1].to_ascii_uppercase().as_str() {
+            "C" => 0,
+            "D" => 2,
+            "E" => 4,
+            "F" => 5,
+            "G" => 7,
+            "A" => 9,
+            _ => 11,
+        };
+        let pitch_class = match &caps[2] {
+            "#" | "♯" => (natural + 1) % 12,
+            "b" | "♭" => (natural + 11) % 12,
+            _ => natural,
+        };
+        let minor = matches!(caps.get(3).map(|m| m.as_str()), Some("m" | "min" | "minor"));
+        Ok(MusicalKey { pitch_class, minor })
+    }
+}
+
+/// Get the indices of tracks whose key is incompatible with the previous track.
+///
+/// Transitions where either track has no recognized key are skipped.
+pub fn key_clashes(tracks: &[Track]) -> Vec<usize> {
+    key_transitions(tracks)
+        .filter(|(_, from, to)| !from.is_compatible(to))
+        .map(|(index, _, _)| index)
+        .collect()
+}
+
+/// Get the number of transitions between consecutive tracks that both have a recognized key.
+pub fn key_transition_count(tracks: &[Track]) -> usize {
+    key_transitions(tracks).count()
+}
+
+/// Iterate over consecutive track keys as (index of the second track, previous key, key) tuples.
+fn key_transitions(tracks: &[Track]) -> impl Iterator<Item = (usize, MusicalKey, MusicalKey)> + '_ {
+    let keys: Vec<Option<MusicalKey>> = tracks
+        .iter()
+        .map(|track| track.key.as_deref().and_then(|key| MusicalKey::from_str(key).ok()))
+        .collect();
+    (1..tracks.len()).filter_map(move |index| match (keys[index - 1], keys[index]) {
+        (Some(from), Some(to)) => Some((index, from, to)),
+        _ => None,
+    })
+}
+
+impl std::fmt::Display for MusicalKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.standard())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_standard_key() {
+        assert_eq!(MusicalKey::from_str("F#m").unwrap().camelot(), "11A");
+        assert_eq!(MusicalKey::from_str("Gbm").unwrap().camelot(), "11A");
+        assert_eq!(MusicalKey::from_str("Am").unwrap().camelot(), "8A");
+        assert_eq!(MusicalKey::from_str("A minor").unwrap().camelot(), "8A");
+        assert_eq!(MusicalKey::from_str("C").unwrap().camelot(), "8B");
+        assert_eq!(MusicalKey::from_str("Cmaj").unwrap().camelot(), "8B");
+        assert_eq!(MusicalKey::from_str("B").unwrap().camelot(), "1B");
+        assert_eq!(MusicalKey::from_str("Bb").unwrap().camelot(), "6B");
+        assert_eq!(MusicalKey::from_str("Abm").unwrap().camelot(), "1A");
+        assert_eq!(MusicalKey::from_str("E").unwrap().camelot(), "12B");
+        assert!(MusicalKey::from_str("H").is_err());
+        assert!(MusicalKey::from_str("").is_err());
+    }
+
+    #[test]
+    fn parse_camelot_key() {
+        assert_eq!(MusicalKey::from_str("11A").unwrap().standard(), "F#m");
+        assert_eq!(MusicalKey::from_str("8B").unwrap().standard(), "C");
+        assert_eq!(MusicalKey::from_str("1a").unwrap().standard(), "Abm");
+        assert_eq!(MusicalKey::from_str("3B").unwrap().standard(), "Db");
+        assert!(MusicalKey::from_str("13A").is_err());
+        assert!(MusicalKey::from_str("0B").is_err());
+    }
+
+    #[test]
+    fn compatible_keys() {
+        let key = |text: &str| MusicalKey::from_str(text).unwrap();
+        assert!(key("8A").is_compatible(&key("8A")));
+        assert!(key("8A").is_compatible(&key("8B")));
+        assert!(key("8A").is_compatible(&key("9A")));
+        assert!(key("8A").is_compatible(&key("7A")));
+        assert!(key("12B").is_compatible(&key("1B")));
+        assert!(key("1A").is_compatible(&key("12A")));
+        assert!(!key("8A").is_compatible(&key("10A")));
+        assert!(!key("8A").is_compatible(&key("9B")));
+        assert!(!key("Am").is_compatible(&key("F#m")));
+    }
+
+    #[test]
+    fn key_clashes_between_tracks() {
+        let track = |key: Option<&str>| {
+            let mut track = Track::new("Artist".to_string(), "Title".to_string());
+            track.key = key.map(ToString::to_string);
+            track
+        };
+        let tracks = vec![
+            track(Some("Am")),
+            track(Some("Em")),
+            track(Some("Bb")),
+            track(None),
+            track(Some("C")),
+            track(Some("unknown")),
+        ];
+        assert_eq!(key_clashes(&tracks), vec![2]);
+        assert_eq!(key_transition_count(&tracks), 2);
+    }
+
+    #[test]
+    fn camelot_round_trip() {
+        for number in 1..=12 {
+            for letter in ["A", "B"] {
+                let camelot = format!("{number}{letter}");
+                let key = MusicalKey::from_str(&camelot).unwrap();
+                assert_eq!(key.camelot(), camelot);
+                assert_eq!(MusicalKey::from_str(&key.standard()).unwrap(), key);
+            }
+        }
+    }
+}
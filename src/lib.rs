@@ -1,7 +1,30 @@
+pub mod builder;
+#[cfg(feature = "fs")]
+pub mod convert;
+pub mod diff;
+pub mod error;
+#[cfg(feature = "net")]
+pub mod fetch;
+pub mod filter;
 pub mod formatted;
+pub mod json;
+pub mod key;
+pub mod normalize;
+pub mod notes;
+pub mod options;
 pub mod playlist;
+pub mod reader;
 pub mod rekordbox;
 pub mod serato;
+pub mod stats;
+pub mod timezone;
 pub mod track;
 pub mod types;
 pub mod utils;
+pub mod validate;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod writer;
+
+pub use builder::{PlaylistBuilder, TrackBuilder};
+pub use error::PlaylistError;
@@ -0,0 +1,157 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use clap::ValueEnum;
+use colored::Colorize;
+use strum_macros::Display;
+
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::types::PlaylistType;
+use playlist_formatter::utils;
+
+/// Column to sort the playlist archive listing by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Display, ValueEnum)]
+pub enum ListSort {
+    Name,
+    #[default]
+    Date,
+    Tracks,
+}
+
+/// Summary of one formatted playlist in the archive.
+#[derive(Debug, Clone)]
+pub struct PlaylistEntry {
+    pub name: String,
+    pub date: Option<NaiveDateTime>,
+    pub tracks: usize,
+    pub path: PathBuf,
+}
+
+/// Print a table of the formatted playlists in the given directory and its subdirectories.
+pub fn list_playlists(dir: &Path, sort: ListSort, reverse: bool) -> Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!("Directory does not exist: '{}'", dir.display());
+    }
+    let mut entries = collect_entries(dir)?;
+    if entries.is_empty() {
+        println!("No formatted playlists found in {}", dir.display());
+        return Ok(());
+    }
+    sort_entries(&mut entries, sort, reverse);
+    print_table(dir, &entries);
+    Ok(())
+}
+
+/// Parse all formatted playlist files in the directory tree.
+fn collect_entries(dir: &Path) -> Result<Vec<PlaylistEntry>> {
+    let mut entries = Vec::new();
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(current) = dirs.pop() {
+        for entry in
+            std::fs::read_dir(&current).with_context(|| format!("Failed to read directory: '{}'", current.display()))?
+        {
+            let path = entry?.path();
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+            if utils::playlist_format(&path).is_err() {
+                continue;
+            }
+            match Playlist::new(&path) {
+                Ok(playlist) if playlist.playlist_type == PlaylistType::Formatted => {
+                    let date = playlist
+                        .date
+                        .or_else(|| utils::extract_datetime_from_name(&playlist.name))
+                        .or_else(|| utils::file_modified_datetime(&path));
+                    entries.push(PlaylistEntry {
+                        name: playlist.name,
+                        date,
+                        tracks: playlist.tracks.len(),
+                        path,
+                    });
+                }
+                Ok(_) => tracing::debug!("Skipping unformatted playlist: {}", path.display()),
+                Err(error) => tracing::debug!("Skipping {}: {error}", path.display()),
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn sort_entries(entries: &mut [PlaylistEntry], sort: ListSort, reverse: bool) {
+    match sort {
+        ListSort::Name => entries.sort_by_cached_key(|e| e.name.to_lowercase()),
+        ListSort::Date => entries.sort_by_key(|e| e.date),
+        ListSort::Tracks => entries.sort_by_key(|e| e.tracks),
+    }
+    if reverse {
+        entries.reverse();
+    }
+}
+
+fn print_table(dir: &Path, entries: &[PlaylistEntry]) {
+    let name_width = entries
+        .iter()
+        .map(|e| utils::display_width(&e.name))
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+    let header = format!("{:<10}   {:<name_width$}   {:>6}   PATH", "DATE", "NAME", "TRACKS");
+    println!("{}", header.bold());
+    println!("{}", "-".repeat(header.chars().count() + 10));
+    for entry in entries {
+        let date = entry.date.map(|d| d.format("%Y.%m.%d").to_string()).unwrap_or_default();
+        let path = entry.path.strip_prefix(dir).unwrap_or(&entry.path);
+        println!(
+            "{:<10}   {}   {:>6}   {}",
+            date,
+            utils::pad_to_width(&entry.name, name_width),
+            entry.tracks,
+            path.display().to_string().dimmed()
+        );
+    }
+    println!("\nPlaylists: {}", entries.len().to_string().green());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    fn entry(name: &str, day: u32, tracks: usize) -> PlaylistEntry {
+        PlaylistEntry {
+            name: name.to_string(),
+            date: NaiveDate::from_ymd_opt(2024, 3, day).and_then(|d| d.and_hms_opt(20, 0, 0)),
+            tracks,
+            path: PathBuf::from(format!("{name}.csv")),
+        }
+    }
+
+    #[test]
+    fn sort_archive_entries() {
+        let mut entries = vec![entry("b", 3, 10), entry("A", 1, 30), entry("c", 2, 20)];
+        sort_entries(&mut entries, ListSort::Date, false);
+        assert_eq!(
+            entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            ["A", "c", "b"]
+        );
+        sort_entries(&mut entries, ListSort::Name, false);
+        assert_eq!(
+            entries.iter().map(|e| e.name.as_str()).collect::<Vec<_>>(),
+            ["A", "b", "c"]
+        );
+        sort_entries(&mut entries, ListSort::Tracks, true);
+        assert_eq!(entries.iter().map(|e| e.tracks).collect::<Vec<_>>(), [30, 20, 10]);
+    }
+
+    #[test]
+    fn collect_formatted_playlists() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("files");
+        let entries = collect_entries(&dir).unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].name, "formatted");
+    }
+}
@@ -0,0 +1,142 @@
+use std::fmt;
+use std::io::IsTerminal;
+
+use serde_json::{Map, Value};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_log::NormalizeEvent;
+use tracing_subscriber::fmt::format::{FmtSpan, JsonFields, Writer};
+use tracing_subscriber::fmt::{FmtContext, FormatEvent, FormatFields, FormattedFields};
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::cli::{Level, LogFormat};
+
+/// Set up log output to stderr.
+///
+/// Records from dependencies that use the `log` crate are forwarded to the same output.
+/// Closing a span logs the time spent in it, so parse, detect, and write phases are timed at debug level.
+pub fn init_logger(log_level: &Level, log_format: LogFormat) {
+    let log_level_filter = log_level.to_log_filter();
+    let builder = tracing_subscriber::fmt()
+        .with_max_level(log_level_filter)
+        .with_span_events(FmtSpan::CLOSE)
+        .with_writer(std::io::stderr)
+        .with_ansi(log_format == LogFormat::Text && std::io::stderr().is_terminal());
+    match log_format {
+        LogFormat::Text => builder.event_format(TextFormat).init(),
+        LogFormat::Json => builder.fmt_fields(JsonFields::new()).event_format(JsonFormat).init(),
+    }
+
+    tracing::debug!("Using log level: {}", log_level_filter);
+}
+
+/// Info messages are printed as is, other levels with the level and the enclosing spans.
+struct TextFormat;
+
+impl<S, N> FormatEvent<S, N> for TextFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        if *metadata.level() != tracing::Level::INFO {
+            write!(writer, "[{}]: ", metadata.level())?;
+            for span in ctx.event_scope().into_iter().flat_map(|scope| scope.from_root()) {
+                write!(writer, "{}", span.name())?;
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<FormattedFields<N>>().filter(|f| !f.is_empty()) {
+                    write!(writer, "{{{fields}}}")?;
+                }
+                write!(writer, ": ")?;
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// One JSON object per line, with the event fields and enclosing spans as nested values.
+struct JsonFormat;
+
+impl<S, N> FormatEvent<S, N> for JsonFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(&self, ctx: &FmtContext<'_, S, N>, mut writer: Writer<'_>, event: &Event<'_>) -> fmt::Result {
+        let normalized = event.normalized_metadata();
+        let metadata = normalized.as_ref().unwrap_or_else(|| event.metadata());
+        let mut visitor = JsonVisitor::default();
+        event.record(&mut visitor);
+        let mut fields = visitor.0;
+        let message = fields.remove("message").unwrap_or_default();
+        let spans: Vec<Value> = ctx
+            .event_scope()
+            .into_iter()
+            .flat_map(|scope| scope.from_root())
+            .map(|span| {
+                let extensions = span.extensions();
+                let mut entry: Map<String, Value> = extensions
+                    .get::<FormattedFields<N>>()
+                    .and_then(|fields| serde_json::from_str(fields).ok())
+                    .unwrap_or_default();
+                entry.insert("name".to_string(), span.name().into());
+                Value::Object(entry)
+            })
+            .collect();
+        let mut entry = serde_json::json!({
+            "level": metadata.level().as_str(),
+            "timestamp": chrono::Local::now().to_rfc3339(),
+            "message": message,
+            "file": metadata.file(),
+            "line": metadata.line(),
+        });
+        if !fields.is_empty() {
+            entry["fields"] = Value::Object(fields);
+        }
+        if !spans.is_empty() {
+            entry["spans"] = Value::Array(spans);
+        }
+        writeln!(writer, "{entry}")
+    }
+}
+
+/// Collects event fields as JSON values, skipping the metadata fields of forwarded `log` records.
+#[derive(Default)]
+struct JsonVisitor(Map<String, Value>);
+
+impl JsonVisitor {
+    fn insert(&mut self, field: &Field, value: Value) {
+        if !field.name().starts_with("log.") {
+            self.0.insert(field.name().to_string(), value);
+        }
+    }
+}
+
+impl Visit for JsonVisitor {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.insert(field, value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.insert(field, value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.insert(field, value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.insert(field, format!("{value:?}").into());
+    }
+}
@@ -1,60 +1,444 @@
+mod batch;
 mod cli;
+mod config;
+mod doctor;
+mod edit;
+mod list;
+mod logging;
+mod mangen;
+mod progress;
+mod report;
+mod tui;
+mod watch;
 
-use std::io::Write;
 use std::path::{Path, PathBuf};
+use std::process::ExitCode;
 
-use anyhow::Result;
-use clap::Parser;
-use log::LevelFilter;
+use anyhow::{Context, Result};
+use chrono::TimeDelta;
+use clap::{CommandFactory, Parser};
 
-use crate::cli::{Args, CliConfig, FormattingStyle, Level};
+use colored::Colorize;
 
+use crate::cli::{Args, CliConfig, Command, FormattingStyle};
+use crate::config::{SaveSettings, UserConfig};
+
+use playlist_formatter::json;
+use playlist_formatter::notes::TrackNotes;
+use playlist_formatter::options::{FormatOptions, ParseOptions};
 use playlist_formatter::playlist::Playlist;
+use playlist_formatter::stats::StatsOptions;
+use playlist_formatter::types::{InputFormat, MergeStrategy, OutputFormat, SplitPoint};
+use playlist_formatter::utils;
+use playlist_formatter::validate::Severity;
 
-fn main() -> Result<()> {
+fn main() -> ExitCode {
     let args = Args::parse();
-    init_logger(&args.log);
-    let absolute_input_path = parse_input_path(&args.file)?;
-    let config = CliConfig::from_args(args);
-    let playlist = Playlist::new(&absolute_input_path)?;
+    logging::init_logger(&args.log_level(), args.log_format.unwrap_or_default());
+    let error_format = args.error_format.unwrap_or_default();
+    let input_file = args.input_file();
+    match run(args) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            report::print_error(&error, error_format, input_file.as_deref());
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run the main program with the given arguments.
+fn run(args: Args) -> Result<()> {
+    // Doctor checks the config file itself, so an invalid config is reported instead of failing here
+    let user_config = match args.command {
+        Some(Command::Doctor) => UserConfig::default(),
+        _ => UserConfig::load(args.config.as_deref())?,
+    };
+    let parse_options = args.parse_options();
+    if let Some(command) = args.command {
+        return run_command(
+            command,
+            &user_config,
+            &parse_options,
+            args.config.as_deref(),
+            args.quiet,
+        );
+    }
+    let input = args.file.clone().unwrap_or_default();
+    let config = CliConfig::from_args(args, user_config);
+    let mut playlist = read_input_playlist(&input, &config)?;
+    if !config.quiet {
+        for warning in playlist.quality_warnings() {
+            tracing::warn!("{warning}");
+        }
+    }
+    if let Some(timezone) = config.timezone {
+        playlist.convert_timezone(timezone);
+    }
+    if config.normalize {
+        playlist.normalize_tracks();
+    }
+    if let Some(suffixes) = &config.strip_suffixes {
+        playlist.strip_suffixes(suffixes);
+    }
+    if config.fix_case {
+        playlist.fix_case();
+    }
+    if let Some(mode) = config.transliterate {
+        playlist.transliterate_artists(mode);
+    }
+    if !config.djs.is_empty() {
+        let count = playlist.assign_djs(&config.djs);
+        tracing::info!("DJ: assigned {count} of {} tracks", playlist.tracks.len());
+    }
+    // Track numbers refer to the parsed playlist, so renames are applied before removing tracks
+    if !config.rename_tracks.is_empty() {
+        playlist.rename_tracks(&config.rename_tracks)?;
+    }
+    if !config.remove_tracks.is_empty() {
+        playlist.remove_tracks(&config.remove_tracks)?;
+    }
+    if config.skip_first > 0 || config.skip_last > 0 {
+        playlist.skip_tracks(config.skip_first, config.skip_last)?;
+    }
+    if let Some(set_start) = config.set_start {
+        playlist.set_start_times(set_start)?;
+    }
+    if config.from.is_some() || config.to.is_some() {
+        playlist.trim(config.from, config.to)?;
+    }
+    if !config.filters.is_empty() {
+        playlist.filter_tracks(&config.filters);
+    }
+    if let Some(sort) = config.sort {
+        playlist.sort_tracks(sort, config.reverse);
+    } else if config.reverse {
+        playlist.tracks.reverse();
+    }
+    if let Some(notation) = config.key_notation {
+        playlist.convert_keys(notation);
+    }
+    let format = config.format_options();
+
+    if config.interactive {
+        if let Some(output_format) = tui::run(&mut playlist, config.output_format.clone())? {
+            let output_path = config.resolved_output_path(&playlist, &output_format);
+            config.save_settings().save(&playlist, &output_path)?;
+        }
+        return Ok(());
+    }
 
     if config.style == FormattingStyle::Pretty {
-        playlist.print_info();
+        playlist.print_info(&format);
     }
     if !config.quiet {
         match &config.style {
-            FormattingStyle::Basic => playlist.print_simple_playlist(),
-            FormattingStyle::Numbered => playlist.print_numbered_playlist(),
-            FormattingStyle::Pretty => playlist.print_pretty_playlist(),
+            FormattingStyle::Basic => playlist.print_simple_playlist(&format),
+            FormattingStyle::Numbered => playlist.print_numbered_playlist(&format),
+            FormattingStyle::Pretty => playlist.print_pretty_playlist(&format),
         }
     }
     if config.save {
-        playlist.save_to_file(config.output_path, config.force, config.default, &config.output_format)?
+        let output_path = config.resolved_output_path(&playlist, &config.output_format);
+        config.save_settings().save(&playlist, &output_path)?
+    }
+
+    Ok(())
+}
+
+/// Run a subcommand.
+///
+/// Playlists are read with the same parse options as the main command.
+fn run_command(
+    command: Command,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+    config_path: Option<&Path>,
+    quiet: bool,
+) -> Result<()> {
+    match command {
+        Command::Validate { file, max_gap } => validate_playlist(&file, max_gap, parse_options),
+        Command::Diff { old, new, json } => diff_playlists(&old, &new, json, parse_options),
+        Command::Merge {
+            files,
+            output,
+            name,
+            strategy,
+            output_format,
+            force,
+            no_force,
+        } => merge_playlists(
+            &files,
+            parse_options,
+            output,
+            name,
+            strategy,
+            &user_config.output_format_or_default(output_format),
+            user_config.force_or_default(force, no_force),
+        ),
+        Command::Convert {
+            input,
+            output,
+            from,
+            to,
+            force,
+            no_force,
+        } => convert_playlist(
+            &input,
+            &output,
+            from.or(parse_options.input_format),
+            to,
+            user_config.force_or_default(force, no_force),
+        ),
+        Command::Doctor => doctor::run_doctor(config_path),
+        Command::Stats {
+            file,
+            json,
+            bpm_jump,
+            max_gap,
+            max_overlap,
+        } => print_stats(
+            &file,
+            json,
+            parse_options,
+            &StatsOptions {
+                bpm_jump,
+                max_gap,
+                max_overlap,
+            },
+        ),
+        Command::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Args::command(),
+                env!("CARGO_BIN_NAME"),
+                &mut std::io::stdout(),
+            );
+            Ok(())
+        }
+        Command::List { dir, sort, reverse } => {
+            let dir = dir
+                .or_else(|| user_config.save_dir.clone())
+                .or_else(utils::dropbox_save_dir)
+                .context("No save directory configured, give the directory to list")?;
+            list::list_playlists(&dir, sort, reverse)
+        }
+        Command::Schema => {
+            println!("{}", serde_json::to_string_pretty(&json::schema())?);
+            Ok(())
+        }
+        Command::Mangen { out_dir } => {
+            mangen::write_man_page(&Args::command().name(env!("CARGO_BIN_NAME")), out_dir.as_deref())
+        }
+        Command::Batch { inputs, save } => batch::convert_files(
+            &inputs,
+            &user_config.save_settings(&save),
+            user_config,
+            parse_options,
+            quiet,
+        ),
+        Command::Edit { file, output, save } => {
+            let absolute_input_path = parse_input_path(&file)?;
+            let mut playlist = Playlist::new_with_options(&absolute_input_path, parse_options)?;
+            edit::edit_playlist(&mut playlist)?;
+            let settings = user_config.save_settings(&save);
+            playlist.print_pretty_playlist(&settings.format);
+            let output_path = user_config.resolve_output_path(&playlist, output, &settings);
+            settings.save(&playlist, &output_path)
+        }
+        Command::Split { file, at, save } => split_playlist(
+            &file,
+            &at,
+            &user_config.save_settings(&save),
+            user_config,
+            parse_options,
+            quiet,
+        ),
+        Command::Watch { dir, save } => watch::watch_directory(
+            Path::new(dir.trim()),
+            &user_config.save_settings(&save),
+            user_config,
+            parse_options,
+        ),
+    }
+}
+
+/// Split playlist file into parts and save each part.
+fn split_playlist(
+    file: &str,
+    points: &[SplitPoint],
+    settings: &SaveSettings,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+    quiet: bool,
+) -> Result<()> {
+    let absolute_input_path = parse_input_path(file)?;
+    let spinner = progress::parse_spinner(&absolute_input_path, quiet);
+    let playlist = Playlist::new_with_options(&absolute_input_path, parse_options)?;
+    spinner.finish_and_clear();
+    for part in playlist.split(points)? {
+        let duration = part.total_duration.map(utils::formatted_duration).unwrap_or_default();
+        println!("{}: {} tracks {}", part.name.green(), part.tracks.len(), duration);
+        let output_path = user_config.resolve_output_path(&part, None, settings);
+        settings.save(&part, &output_path)?;
+    }
+    Ok(())
+}
+
+/// Combine multiple playlist files into one playlist.
+fn merge_playlists(
+    files: &[String],
+    parse_options: &ParseOptions,
+    output: Option<String>,
+    name: Option<String>,
+    strategy: MergeStrategy,
+    output_format: &OutputFormat,
+    force: bool,
+) -> Result<()> {
+    let playlists = files
+        .iter()
+        .map(|file| parse_input_path(file).and_then(|path| Ok(Playlist::new_with_options(&path, parse_options)?)))
+        .collect::<Result<Vec<Playlist>>>()?;
+
+    let mut playlist = Playlist::merge_playlists(playlists, strategy)?;
+    if let Some(name) = name {
+        playlist.name = name;
     }
 
+    let format = FormatOptions::for_format(output_format);
+    playlist.print_info(&format);
+    playlist.print_pretty_playlist(&format);
+    if output.is_some() {
+        playlist.save_to_file(output, force, false, output_format)?;
+    }
     Ok(())
 }
 
-fn init_logger(log_level: &Option<Level>) {
-    // Get logging level to use
-    let log_level_filter = match log_level {
-        None => LevelFilter::Info,
-        Some(ref level) => level.to_log_filter(),
+/// Convert playlist file to another format as is.
+fn convert_playlist(
+    input: &str,
+    output: &str,
+    from: Option<InputFormat>,
+    to: Option<OutputFormat>,
+    force: bool,
+) -> Result<()> {
+    let absolute_input_path = parse_input_path(input)?;
+    let output_path = PathBuf::from(output.trim());
+    let output_format = match to {
+        Some(format) => format,
+        None => output_path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .ok_or_else(|| anyhow::anyhow!("Output file has no extension, use the {} option", "to".bold()))?
+            .parse::<OutputFormat>()?,
     };
-    // Init logger with timestamps
-    env_logger::Builder::new()
-        .format(|formatter, record| match record.level() {
-            log::Level::Info => {
-                writeln!(formatter, "{}", record.args())
-            }
-            _ => {
-                writeln!(formatter, "[{}]: {}", record.level(), record.args())
-            }
-        })
-        .filter(None, log_level_filter)
-        .init();
-
-    log::debug!("Using log level: {}", log_level_filter);
+    if output_path.is_file() && !force {
+        anyhow::bail!(
+            "Output file already exists: {}. Use the {} option overwrite it",
+            output_path.display(),
+            "force".bold()
+        );
+    }
+
+    let playlist = Playlist::new_with_options(&absolute_input_path, &ParseOptions::raw(from))?;
+    tracing::info!(
+        "Converting {} tracks to {}: {}",
+        playlist.tracks.len(),
+        output_format,
+        output_path.display()
+    );
+    Ok(playlist.write_file(&output_path, &output_format)?)
+}
+
+/// Print playlist statistics.
+fn print_stats(file: &str, json: bool, parse_options: &ParseOptions, options: &StatsOptions) -> Result<()> {
+    let absolute_input_path = parse_input_path(file)?;
+    let playlist = Playlist::new_with_options(&absolute_input_path, parse_options)?;
+    let stats = playlist.statistics_with_options(options);
+    if json {
+        println!("{}", stats.to_json()?);
+    } else {
+        playlist.print_info(&FormatOptions::default());
+        stats.print();
+    }
+    Ok(())
+}
+
+/// Print the differences between two versions of a playlist.
+fn diff_playlists(old: &str, new: &str, json: bool, parse_options: &ParseOptions) -> Result<()> {
+    let old = Playlist::new_with_options(&parse_input_path(old)?, parse_options)?;
+    let new = Playlist::new_with_options(&parse_input_path(new)?, parse_options)?;
+    let diff = old.diff(&new);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff)?);
+    } else if diff.is_empty() {
+        println!("{}", "No differences found".green());
+    } else {
+        diff.print();
+    }
+    if diff.is_empty() {
+        return Ok(());
+    }
+    anyhow::bail!(
+        "Playlists differ: {} added, {} removed, {} moved, {} changed",
+        diff.added.len(),
+        diff.removed.len(),
+        diff.moved.len(),
+        diff.changed.len()
+    )
+}
+
+/// Parse playlist and report any issues found.
+///
+/// Returns an error if there were any problems so this can be used to gate automation.
+fn validate_playlist(file: &str, max_gap: TimeDelta, parse_options: &ParseOptions) -> Result<()> {
+    let absolute_input_path = parse_input_path(file)?;
+    let playlist = Playlist::new_with_options(&absolute_input_path, parse_options)?;
+    let issues = playlist.validate_with_options(&StatsOptions {
+        max_gap,
+        ..StatsOptions::default()
+    });
+    if issues.is_empty() {
+        println!(
+            "{}",
+            format!("No issues found in {} tracks", playlist.tracks.len()).green()
+        );
+        return Ok(());
+    }
+    for issue in &issues {
+        match issue.severity {
+            Severity::Error => println!("{}", issue.to_string().red()),
+            Severity::Warning => println!("{}", issue.to_string().yellow()),
+            Severity::Info => println!("{issue}"),
+        }
+    }
+    anyhow::bail!("Found {} issue(s) in playlist: {}", issues.len(), playlist.name)
+}
+
+/// Read the input playlist from a local file or download it from a URL.
+///
+/// Notes from a sidecar file next to a local playlist file are applied to the tracks.
+fn read_input_playlist(input: &str, config: &CliConfig) -> Result<Playlist> {
+    #[cfg(feature = "net")]
+    if playlist_formatter::fetch::is_url(input) {
+        tracing::info!("Playlist URL: {}", input.trim());
+        let playlist = playlist_formatter::fetch::fetch_playlist_blocking(input, &config.parse_options)
+            .with_context(|| format!("Failed to download playlist: '{}'", input.trim()))?;
+        return Ok(playlist);
+    }
+    let absolute_input_path = parse_input_path(input)?;
+    let spinner = progress::parse_spinner(&absolute_input_path, config.quiet);
+    let options = ParseOptions {
+        progress: progress::parse_progress(&spinner, &absolute_input_path),
+        ..config.parse_options.clone()
+    };
+    let mut playlist = Playlist::new_with_options(&absolute_input_path, &options)?;
+    spinner.finish_and_clear();
+    let notes_path = TrackNotes::sidecar_path(&absolute_input_path);
+    if notes_path.is_file() {
+        let count = playlist.apply_notes_file(&notes_path)?;
+        tracing::info!("Notes: {count} tracks from {}", notes_path.display());
+    }
+    Ok(playlist)
 }
 
 fn parse_input_path(input: &str) -> Result<PathBuf> {
@@ -70,6 +454,6 @@ fn parse_input_path(input: &str) -> Result<PathBuf> {
         );
     }
     let absolute_input_path = dunce::canonicalize(filepath)?;
-    log::info!("Playlist file: {}", absolute_input_path.display());
+    tracing::info!("Playlist file: {}", absolute_input_path.display());
     Ok(absolute_input_path)
 }
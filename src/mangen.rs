@@ -0,0 +1,47 @@
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use clap::Command;
+
+/// Write a roff man page for the command to the given directory, or to stdout if no directory is given.
+pub fn write_man_page(cmd: &Command, out_dir: Option<&Path>) -> Result<()> {
+    let page = render(cmd)?;
+    match out_dir {
+        Some(dir) => {
+            std::fs::create_dir_all(dir)?;
+            let path = dir.join(format!("{}.1", cmd.get_name()));
+            std::fs::write(&path, page).with_context(|| format!("Failed to write man page: '{}'", path.display()))?;
+            println!("{}", path.display());
+        }
+        None => std::io::stdout().write_all(&page)?,
+    }
+    Ok(())
+}
+
+/// Render a roff man page for the command, including its options and subcommands.
+pub fn render(cmd: &Command) -> Result<Vec<u8>> {
+    let mut buffer: Vec<u8> = Vec::new();
+    clap_mangen::Man::new(cmd.clone()).render(&mut buffer)?;
+    Ok(buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use clap::CommandFactory;
+
+    use crate::cli::Args;
+
+    #[test]
+    fn render_man_page() {
+        let page = String::from_utf8(render(&Args::command().name("playfmt")).unwrap()).unwrap();
+        assert!(page.starts_with(".ie \\n(.g .ds Aq"));
+        assert!(page.contains(".TH playfmt 1"));
+        assert!(page.contains(".SH SYNOPSIS"));
+        assert!(page.contains("\\fB\\-l\\fR, \\fB\\-\\-log\\fR"));
+        assert!(page.contains("validate"));
+        assert!(!page.contains("mangen"));
+    }
+}
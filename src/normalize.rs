@@ -0,0 +1,390 @@
+use std::sync::LazyLock;
+
+use regex::Regex;
+use titlecase::titlecase;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+
+use super::track::Track;
+use super::types::TransliterateMode;
+
+static RE_FEATURING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:featuring|feat|ft)\b\.?\s*").expect("Failed to create regex pattern for featuring")
+});
+static RE_SQUARE_BRACKETS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\[([^\[\]]*)\]").expect("Failed to create regex pattern for square brackets"));
+static RE_CURLY_BRACKETS: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{([^{}]*)\}").expect("Failed to create regex pattern for curly brackets"));
+static RE_DASH_SUFFIX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?) - ([^()]*\b(?i:mix|remix|edit|dub|rework|bootleg|version|vip)\b[^()]*)$")
+        .expect("Failed to create regex pattern for dash suffix")
+});
+static RE_TITLE_FEATURING: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(.+?)\s+feat\. ([^()]+?)(\s*\(.*)?$").expect("Failed to create regex pattern for title featuring")
+});
+
+/// Title suffixes removed by default when stripping suffixes.
+pub const DEFAULT_STRIP_SUFFIXES: [&str; 3] = ["Original Mix", "Clean", "Dirty"];
+
+/// Words that keep their own capitalization when fixing case.
+const PROTECTED_WORDS: [&str; 11] = ["DJ", "MC", "ID", "VIP", "EP", "LP", "UK", "USA", "feat.", "ft.", "vs."];
+
+static RE_ROMAN_NUMERAL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)^(?:x{0,3})(?:ix|iv|v?i{0,3})$").expect("Failed to create regex pattern for roman numerals")
+});
+
+/// Standardize featuring credits, remix brackets, and whitespace for all tracks.
+///
+/// Returns the number of tracks that were changed.
+pub fn normalize_tracks(tracks: &mut [Track]) -> usize {
+    let mut count = 0;
+    for track in tracks {
+        let artist = normalize_artist(&track.artist);
+        let title = normalize_title(&track.title);
+        if artist != track.artist || title != track.title {
+            tracing::debug!("Normalized: {track} -> {artist} - {title}");
+            track.artist = artist;
+            track.title = title;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Normalize an artist name: `ft.`, `feat` and `featuring` become `feat.`.
+pub fn normalize_artist(artist: &str) -> String {
+    collapse_whitespace(&normalize_featuring(&collapse_whitespace(artist)))
+}
+
+/// Normalize a track title.
+///
+/// Featuring credits become `(feat. X)`, square and curly brackets become parentheses,
+/// and a dash-separated remix suffix such as `Title - Extended Mix` is put in parentheses.
+pub fn normalize_title(title: &str) -> String {
+    let title = collapse_whitespace(title);
+    let title = RE_SQUARE_BRACKETS.replace_all(&title, "($1)");
+    let title = RE_CURLY_BRACKETS.replace_all(&title, "($1)");
+    let title = RE_DASH_SUFFIX.replace(&title, "$1 ($2)");
+    let title = normalize_featuring(&title);
+    let title = RE_TITLE_FEATURING.replace(&title, "$1 (feat. $2)$3");
+    collapse_whitespace(&title.replace("( ", "(").replace(" )", ")"))
+}
+
+/// Remove the given suffixes from all track titles and return the number of tracks that were changed.
+pub fn strip_track_suffixes(tracks: &mut [Track], suffixes: &[String]) -> usize {
+    let Some(regex) = suffix_regex(suffixes) else {
+        return 0;
+    };
+    let mut count = 0;
+    for track in tracks {
+        let title = strip_suffixes_with(&track.title, &regex);
+        if title != track.title {
+            tracing::debug!("Stripped suffix: {} -> {title}", track.title);
+            track.title = title;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Remove the given suffixes from a title, for example `Song (Original Mix)` becomes `Song`.
+///
+/// Matching ignores case, and the suffix can be in parentheses, square brackets,
+/// or separated with a dash at the end of the title.
+pub fn strip_suffixes(title: &str, suffixes: &[String]) -> String {
+    suffix_regex(suffixes).map_or_else(|| title.to_string(), |regex| strip_suffixes_with(title, &regex))
+}
+
+fn strip_suffixes_with(title: &str, regex: &Regex) -> String {
+    let stripped = regex.replace_all(title, "");
+    if stripped.trim().is_empty() {
+        return title.to_string();
+    }
+    collapse_whitespace(&stripped)
+}
+
+/// Build a regex matching any of the suffixes, or `None` if there are no suffixes.
+fn suffix_regex(suffixes: &[String]) -> Option<Regex> {
+    let alternatives: Vec<String> = suffixes
+        .iter()
+        .map(|suffix| suffix.trim())
+        .filter(|suffix| !suffix.is_empty())
+        .map(regex::escape)
+        .collect();
+    if alternatives.is_empty() {
+        return None;
+    }
+    let alternatives = alternatives.join("|");
+    Regex::new(&format!(
+        r"(?i)\s*[(\[]\s*(?:{alternatives})\s*[)\]]|\s+-\s+(?:{alternatives})\s*$"
+    ))
+    .ok()
+}
+
+/// Convert all-caps and all-lowercase artists and titles to title case.
+///
+/// Returns the number of tracks that were changed.
+pub fn fix_track_case(tracks: &mut [Track]) -> usize {
+    let mut count = 0;
+    for track in tracks {
+        let artist = fix_case(&track.artist);
+        let title = fix_case(&track.title);
+        if artist != track.artist || title != track.title {
+            tracing::debug!("Fixed case: {track} -> {artist} - {title}");
+            track.artist = artist;
+            track.title = title;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Convert all-caps or all-lowercase text to title case.
+///
+/// Text with mixed case is returned as is, since it is most likely already capitalized on purpose.
+/// Protected words such as `DJ`, `ID`, and `feat.`, and Roman numerals keep their usual form.
+pub fn fix_case(text: &str) -> String {
+    let has_upper = text.chars().any(char::is_uppercase);
+    let has_lower = text.chars().any(char::is_lowercase);
+    if has_upper == has_lower {
+        return text.to_string();
+    }
+    titlecase(&text.to_lowercase())
+        .split(' ')
+        .map(protect_word)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Restore the capitalization of a protected word or Roman numeral, keeping surrounding brackets and punctuation.
+fn protect_word(word: &str) -> String {
+    let start = word.find(|c: char| c.is_alphanumeric()).unwrap_or(word.len());
+    let (prefix, rest) = word.split_at(start);
+    let core = rest.trim_end_matches(|c: char| !c.is_alphanumeric() && c != '.');
+    let suffix = &rest[core.len()..];
+    let core_without_dot = core.trim_end_matches('.');
+    if let Some(protected) = PROTECTED_WORDS.iter().find(|protected| {
+        protected.eq_ignore_ascii_case(core)
+            || (!protected.ends_with('.') && protected.eq_ignore_ascii_case(core_without_dot))
+    }) {
+        let dot = if protected.ends_with('.') {
+            ""
+        } else {
+            &core[core_without_dot.len()..]
+        };
+        return format!("{prefix}{protected}{dot}{suffix}");
+    }
+    if !core_without_dot.is_empty() && RE_ROMAN_NUMERAL.is_match(core_without_dot) {
+        return format!("{prefix}{}{suffix}", core.to_uppercase());
+    }
+    word.to_string()
+}
+
+/// Convert text to Unicode Normalization Form C so that accented characters are a single code point.
+pub fn nfc(text: String) -> String {
+    if is_nfc(&text) {
+        text
+    } else {
+        text.nfc().collect()
+    }
+}
+
+/// Remove emoji and control characters from all artists and titles.
+///
+/// Returns the number of tracks that were changed.
+pub fn strip_track_emoji(tracks: &mut [Track]) -> usize {
+    let mut count = 0;
+    for track in tracks {
+        let artist = strip_emoji(&track.artist);
+        let title = strip_emoji(&track.title);
+        if artist != track.artist || title != track.title {
+            tracing::debug!("Stripped emoji: {track} -> {artist} - {title}");
+            track.artist = artist;
+            track.title = title;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Remove emoji, pictographic symbols, and control characters from text.
+pub fn strip_emoji(text: &str) -> String {
+    if !text.chars().any(|c| c.is_control() || is_emoji(c)) {
+        return text.to_string();
+    }
+    collapse_whitespace(
+        &text
+            .chars()
+            .map(|c| if c.is_control() { ' ' } else { c })
+            .filter(|&c| !is_emoji(c))
+            .collect::<String>(),
+    )
+}
+
+/// Check if the character is an emoji, a pictographic symbol, or an emoji modifier.
+const fn is_emoji(c: char) -> bool {
+    matches!(
+        c,
+        '\u{1F000}'..='\u{1FAFF}'
+            | '\u{2600}'..='\u{27BF}'
+            | '\u{2B00}'..='\u{2BFF}'
+            | '\u{200D}'
+            | '\u{20E3}'
+            | '\u{FE00}'..='\u{FE0F}'
+            | '\u{E0020}'..='\u{E007F}'
+    )
+}
+
+/// Add or substitute a romanized form for artist names written in a non-Latin script.
+///
+/// Returns the number of tracks that were changed.
+pub fn transliterate_track_artists(tracks: &mut [Track], mode: TransliterateMode) -> usize {
+    let mut count = 0;
+    for track in tracks {
+        if let Some(artist) = transliterate(&track.artist, mode) {
+            tracing::debug!("Transliterated: {} -> {artist}", track.artist);
+            track.artist = artist;
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Romanize text written in a non-Latin script such as Cyrillic, Japanese, or Korean.
+///
+/// Returns `None` for text that only uses Latin characters, so accented names are left as is.
+pub fn transliterate(text: &str, mode: TransliterateMode) -> Option<String> {
+    if !text.chars().any(is_non_latin_letter) {
+        return None;
+    }
+    let romanized = collapse_whitespace(&any_ascii::any_ascii(text));
+    if romanized.is_empty() || romanized == text {
+        return None;
+    }
+    match mode {
+        TransliterateMode::Append => Some(format!("{text} ({romanized})")),
+        TransliterateMode::Replace => Some(romanized),
+    }
+}
+
+/// Check if the character is a letter outside the Latin script blocks.
+fn is_non_latin_letter(c: char) -> bool {
+    c.is_alphabetic() && !matches!(c, '\0'..='\u{024F}' | '\u{1E00}'..='\u{1EFF}')
+}
+
+/// Replace the different featuring abbreviations with `feat. `.
+fn normalize_featuring(text: &str) -> String {
+    RE_FEATURING.replace_all(text, "feat. ").into_owned()
+}
+
+/// Replace repeated whitespace with a single space and trim the ends.
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_artist() {
+        assert_eq!(normalize_artist("Artist  ft. Other"), "Artist feat. Other");
+        assert_eq!(normalize_artist("Artist Featuring Other"), "Artist feat. Other");
+        assert_eq!(normalize_artist("Artist feat Other "), "Artist feat. Other");
+        assert_eq!(normalize_artist("Daft Punk"), "Daft Punk");
+        assert_eq!(normalize_artist("Craft Work"), "Craft Work");
+    }
+
+    #[test]
+    fn test_normalize_title() {
+        assert_eq!(normalize_title("Song [Extended Mix]"), "Song (Extended Mix)");
+        assert_eq!(normalize_title("Song {VIP}"), "Song (VIP)");
+        assert_eq!(normalize_title("Song - Artist Remix"), "Song (Artist Remix)");
+        assert_eq!(normalize_title("Song ft. Singer"), "Song (feat. Singer)");
+        assert_eq!(
+            normalize_title("Song  featuring Singer [Club Mix]"),
+            "Song (feat. Singer) (Club Mix)"
+        );
+        assert_eq!(normalize_title("Song (feat. Singer)"), "Song (feat. Singer)");
+        assert_eq!(normalize_title("Part 1 - Part 2"), "Part 1 - Part 2");
+        assert_eq!(normalize_title("Left"), "Left");
+    }
+
+    #[test]
+    fn test_strip_suffixes() {
+        let suffixes: Vec<String> = DEFAULT_STRIP_SUFFIXES.iter().map(ToString::to_string).collect();
+        assert_eq!(strip_suffixes("Song (Original Mix)", &suffixes), "Song");
+        assert_eq!(strip_suffixes("Song [clean]", &suffixes), "Song");
+        assert_eq!(
+            strip_suffixes("Song (Dirty) (Club Edit)", &suffixes),
+            "Song (Club Edit)"
+        );
+        assert_eq!(strip_suffixes("Song - Original Mix", &suffixes), "Song");
+        assert_eq!(strip_suffixes("Clean Bandit", &suffixes), "Clean Bandit");
+        assert_eq!(strip_suffixes("(Clean)", &suffixes), "(Clean)");
+        assert_eq!(
+            strip_suffixes("Song (Extended Mix)", &["Extended Mix".to_string()]),
+            "Song"
+        );
+        assert_eq!(strip_suffixes("Song (Clean)", &[]), "Song (Clean)");
+    }
+
+    #[test]
+    fn test_fix_case() {
+        assert_eq!(fix_case("DJ SNAKE"), "DJ Snake");
+        assert_eq!(
+            fix_case("TURN DOWN FOR WHAT (CLUB MIX)"),
+            "Turn Down for What (Club Mix)"
+        );
+        assert_eq!(fix_case("rocky ii"), "Rocky II");
+        assert_eq!(fix_case("ARTIST FT. SINGER"), "Artist ft. Singer");
+        assert_eq!(fix_case("id - id"), "ID - ID");
+        assert_eq!(fix_case("A VS. B"), "A vs. B");
+        assert_eq!(fix_case("deadmau5"), "Deadmau5");
+        assert_eq!(fix_case("Mixed Case stays"), "Mixed Case stays");
+        assert_eq!(fix_case("1999"), "1999");
+    }
+
+    #[test]
+    fn test_nfc() {
+        let decomposed = "Beyonce\u{301}".to_string();
+        assert_eq!(decomposed.chars().count(), 8);
+        let composed = nfc(decomposed);
+        assert_eq!(composed, "Beyonc\u{e9}");
+        assert_eq!(composed.chars().count(), 7);
+    }
+
+    #[test]
+    fn test_strip_emoji() {
+        assert_eq!(strip_emoji("Song \u{1F525}\u{1F525}"), "Song");
+        assert_eq!(strip_emoji("\u{2B50} Star \u{2764}\u{FE0F} Track"), "Star Track");
+        assert_eq!(strip_emoji("Tab\tSeparated\u{7}"), "Tab Separated");
+        assert_eq!(strip_emoji("R\u{f6}yksopp - \u{b2}"), "R\u{f6}yksopp - \u{b2}");
+    }
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(
+            transliterate("Кино", TransliterateMode::Append),
+            Some("Кино (Kino)".to_string())
+        );
+        assert_eq!(
+            transliterate("Кино", TransliterateMode::Replace),
+            Some("Kino".to_string())
+        );
+        assert!(transliterate("きゃりーぱみゅぱみゅ", TransliterateMode::Replace).is_some());
+        assert!(transliterate("아이유", TransliterateMode::Replace).is_some());
+        assert_eq!(transliterate("Röyksopp", TransliterateMode::Append), None);
+        assert_eq!(transliterate("Daft Punk", TransliterateMode::Replace), None);
+    }
+
+    #[test]
+    fn test_normalize_tracks() {
+        let mut tracks = vec![
+            Track::new("A ft B".to_string(), "Song [Remix]".to_string()),
+            Track::new("C".to_string(), "Clean".to_string()),
+        ];
+        assert_eq!(normalize_tracks(&mut tracks), 1);
+        assert_eq!(tracks[0].artist, "A feat. B");
+        assert_eq!(tracks[0].title, "Song (Remix)");
+    }
+}
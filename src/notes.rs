@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use super::error::{PlaylistError, Result};
+
+use super::track::Track;
+
+/// Track notes read from a sidecar file.
+///
+/// The sidecar is a flat TOML table where each key is either a track number
+/// or an `Artist - Title` string, and the value is the note for that track:
+///
+/// ```toml
+/// 3 = "ID from promo"
+/// "Nelly Furtado - Say It Right (Faros Remix)" = "request"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackNotes {
+    by_number: BTreeMap<usize, String>,
+    by_name: BTreeMap<String, String>,
+}
+
+impl TrackNotes {
+    /// Parse notes from TOML text.
+    pub fn from_toml(text: &str) -> Result<Self> {
+        let table: BTreeMap<String, String> = toml::from_str(text)
+            .map_err(|error| PlaylistError::InvalidData(format!("Failed to parse notes: {error}")))?;
+        let mut notes = Self::default();
+        for (key, value) in table {
+            match key.trim().parse::<usize>() {
+                Ok(number) => notes.by_number.insert(number, value),
+                Err(_) => notes.by_name.insert(key.trim().to_lowercase(), value),
+            };
+        }
+        Ok(notes)
+    }
+
+    /// Read notes from the given sidecar file.
+    #[cfg(feature = "fs")]
+    pub fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|error| PlaylistError::io(format!("Failed to read notes file: '{}'", path.display()), error))?;
+        Self::from_toml(&text)
+            .map_err(|error| PlaylistError::InvalidData(format!("Invalid notes file: '{}': {error}", path.display())))
+    }
+
+    /// Get the sidecar notes file path for a playlist file, for example `playlist.notes.toml`.
+    pub fn sidecar_path(playlist_file: &Path) -> PathBuf {
+        let stem = playlist_file
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        playlist_file.with_file_name(format!("{stem}.notes.toml"))
+    }
+
+    /// Set notes for the matching tracks and return the number of tracks that got a note.
+    ///
+    /// Track numbers count from one. A track number takes precedence over a name match.
+    pub fn apply(&self, tracks: &mut [Track]) -> usize {
+        let mut count = 0;
+        for (index, track) in tracks.iter_mut().enumerate() {
+            let name = format!("{} - {}", track.artist, track.title).to_lowercase();
+            if let Some(note) = self.by_number.get(&(index + 1)).or_else(|| self.by_name.get(&name)) {
+                track.notes = Some(note.clone());
+                count += 1;
+            }
+        }
+        count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_number.is_empty() && self.by_name.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_apply_notes() {
+        let notes = TrackNotes::from_toml(
+            r#"
+            2 = "ID from promo"
+            "artist - first" = "request"
+            "#,
+        )
+        .unwrap();
+        let mut tracks = vec![
+            Track::new("Artist".to_string(), "First".to_string()),
+            Track::new("Artist".to_string(), "Second".to_string()),
+            Track::new("Artist".to_string(), "Third".to_string()),
+        ];
+        assert_eq!(notes.apply(&mut tracks), 2);
+        assert_eq!(tracks[0].notes.as_deref(), Some("request"));
+        assert_eq!(tracks[1].notes.as_deref(), Some("ID from promo"));
+        assert_eq!(tracks[2].notes, None);
+    }
+
+    #[test]
+    fn invalid_notes() {
+        assert!(TrackNotes::from_toml("3 = 4").is_err());
+        assert!(TrackNotes::from_toml("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn sidecar_path() {
+        assert_eq!(
+            TrackNotes::sidecar_path(Path::new("/music/set 1.csv")),
+            PathBuf::from("/music/set 1.notes.toml")
+        );
+    }
+}
@@ -0,0 +1,206 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::TimeDelta;
+
+use super::types::{
+    DateFormat, DedupePolicy, DurationFormat, FooterField, InputFormat, OutputFormat, SearchLink, TimeFormat,
+};
+use super::utils;
+
+/// Options for reading and parsing a playlist file.
+#[derive(Debug, Clone, Default)]
+pub struct ParseOptions {
+    /// Use the given input format instead of detecting it from the file
+    pub input_format: Option<InputFormat>,
+    /// How duplicate tracks are handled
+    pub dedupe: DedupePolicy,
+    /// Drop tracks played for a shorter time than this
+    pub min_playtime: Option<TimeDelta>,
+    /// Don't compute missing start, end, or play times from the other two
+    pub keep_missing_times: bool,
+    /// Merge a track played again within this time after it ended, even with other tracks in between
+    pub merge_reloads: Option<TimeDelta>,
+    /// Remove emoji and control characters from artists and titles
+    pub strip_emoji: bool,
+    /// Collect tracks played on top of another track as mashups of that track
+    pub mashups: bool,
+    /// Treat recoverable parse issues like invalid timestamps, short rows, and unknown columns as errors
+    pub strict: bool,
+    /// Receives the number of rows parsed, and the files completed in
+    /// [`convert_many`](crate::convert::convert_many)
+    pub progress: Option<ProgressCallback>,
+}
+
+impl ParseOptions {
+    /// Options for a faithful conversion without any normalization.
+    pub fn raw(input_format: Option<InputFormat>) -> Self {
+        Self {
+            input_format,
+            dedupe: DedupePolicy::Off,
+            min_playtime: None,
+            keep_missing_times: true,
+            merge_reloads: None,
+            strip_emoji: false,
+            mashups: false,
+            strict: false,
+            progress: None,
+        }
+    }
+}
+
+/// Options for how a playlist is written to output files and printed.
+///
+/// Passed to the writers and the print functions together with the playlist,
+/// so the playlist itself is not changed.
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    /// Output format used by [`Playlist::save_with_options`](crate::playlist::Playlist::save_with_options)
+    pub output_format: OutputFormat,
+    /// Custom number for the first track, also adds a number column
+    pub start_number: Option<usize>,
+    /// Use the track numbers from the source playlist where available, also adds a number column
+    pub original_numbers: bool,
+    /// Write the values from input columns that are not otherwise used
+    pub include_extras: bool,
+    /// Add the deck column
+    pub show_deck: bool,
+    /// Add an elapsed time column
+    pub show_elapsed: bool,
+    /// Shift added to elapsed times, for recordings that did not start with the first track
+    pub cue_offset: TimeDelta,
+    /// Round track playtimes to a multiple of this, totals stay exact
+    pub playtime_rounding: Option<TimeDelta>,
+    pub duration_format: DurationFormat,
+    pub time_format: TimeFormat,
+    pub date_format: DateFormat,
+    /// How duplicate tracks are merged before writing
+    pub dedupe: DedupePolicy,
+    /// Values in the totals footer, or `None` for the default of only the total duration in saved files
+    pub footer: Option<Vec<FooterField>>,
+    /// Mark tracks with a key incompatible with the previous track in pretty output
+    pub show_key_clashes: bool,
+    /// Show a start time column in pretty output
+    pub show_start_times: bool,
+    /// Link track names in terminal output to a web search
+    pub search_links: Option<SearchLink>,
+    /// Mark tracks with a BPM change over this threshold from the previous track in pretty output
+    pub bpm_jump: Option<f64>,
+    /// Show an overlap column in pretty output, highlighting overlaps longer than this
+    pub long_overlap: Option<TimeDelta>,
+    /// Shorten long artists and titles in pretty output to fit this width, instead of the terminal width
+    pub max_width: Option<usize>,
+    /// Add a sheet with set statistics to XLSX output
+    pub stats_sheet: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        Self {
+            output_format: OutputFormat::default(),
+            start_number: None,
+            original_numbers: false,
+            include_extras: true,
+            show_deck: false,
+            show_elapsed: false,
+            cue_offset: TimeDelta::zero(),
+            playtime_rounding: None,
+            duration_format: DurationFormat::default(),
+            time_format: TimeFormat::default(),
+            date_format: DateFormat::default(),
+            dedupe: DedupePolicy::Off,
+            footer: None,
+            show_key_clashes: false,
+            show_start_times: false,
+            search_links: None,
+            bpm_jump: None,
+            long_overlap: None,
+            max_width: None,
+            stats_sheet: false,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Default options for the given output format.
+    pub fn for_format(output_format: &OutputFormat) -> Self {
+        Self {
+            output_format: output_format.clone(),
+            ..Self::default()
+        }
+    }
+
+    /// Check if output files get a track number column.
+    pub fn has_number_column(&self) -> bool {
+        self.start_number.is_some() || self.original_numbers
+    }
+
+    /// Format a duration using the duration format.
+    pub fn formatted_duration(&self, duration: TimeDelta) -> String {
+        utils::format_duration(duration, self.duration_format)
+    }
+
+    /// Format a track playtime, rounded if rounding is enabled.
+    pub fn formatted_playtime(&self, play_time: TimeDelta) -> String {
+        match self.playtime_rounding {
+            Some(step) => self.formatted_duration(utils::round_duration(play_time, step)),
+            None => self.formatted_duration(play_time),
+        }
+    }
+}
+
+/// Options for saving a playlist to a file.
+#[derive(Debug, Clone, Default)]
+pub struct SaveOptions {
+    pub format: FormatOptions,
+    /// Replace an output file that already exists
+    pub overwrite: bool,
+}
+
+/// Options for converting playlist files with [`convert`](crate::convert::convert).
+#[derive(Debug, Clone, Default)]
+pub struct ConvertOptions {
+    /// How the input files are parsed
+    pub parse: ParseOptions,
+    /// Replace output files that already exist
+    pub overwrite: bool,
+}
+
+/// Progress of a long operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Number of rows read from an input file so far
+    RowsParsed { file: PathBuf, rows: usize },
+    /// Input file finished converting, successfully or not
+    FileCompleted {
+        file: PathBuf,
+        completed: usize,
+        total: usize,
+    },
+}
+
+/// Callback for progress events, for example to update a progress bar.
+///
+/// The library never prints progress itself.
+/// The callback is called from the thread doing the work,
+/// so it should return quickly.
+#[derive(Clone)]
+pub struct ProgressCallback(Arc<dyn Fn(&ProgressEvent) + Send + Sync>);
+
+impl ProgressCallback {
+    pub fn new(callback: impl Fn(&ProgressEvent) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(callback))
+    }
+
+    /// Send an event to the callback.
+    pub fn report(&self, event: &ProgressEvent) {
+        (self.0)(event);
+    }
+}
+
+impl fmt::Debug for ProgressCallback {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ProgressCallback")
+    }
+}
@@ -1,32 +1,73 @@
+#[cfg(feature = "cli")]
 use std::cmp::max;
-use std::collections::BTreeMap;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, BTreeSet};
+#[cfg(feature = "fs")]
 use std::env;
+#[cfg(feature = "fs")]
 use std::ffi::OsStr;
+#[cfg(feature = "fs")]
 use std::fs::File;
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Cursor, IsTerminal, Read, Write};
+use std::ops::Index;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::string::String;
+use std::sync::Arc;
 
-use anyhow::{anyhow, Context, Result};
-use chrono::{NaiveDateTime, TimeDelta};
+#[cfg(all(feature = "fs", feature = "xlsx"))]
+use calamine::{Data, Reader as _, Xlsx};
+#[cfg(feature = "xlsx")]
+use chrono::{DateTime, Utc};
+use chrono::{Local, NaiveDateTime, NaiveTime, TimeDelta};
+use chrono_tz::Tz;
+#[cfg(feature = "cli")]
 use colored::Colorize;
-use csv::Reader;
+use csv::{Reader, StringRecord};
 use encoding_rs_io::DecodeReaderBytes;
-use rust_xlsxwriter::{Format, FormatAlign, FormatBorder, RowNum, Workbook};
+#[cfg(all(feature = "fs", feature = "xlsx"))]
+use rust_xlsxwriter::ColNum;
+#[cfg(feature = "xlsx")]
+use rust_xlsxwriter::{DocProperties, Format, FormatAlign, FormatBorder, RowNum, Workbook, Worksheet};
 
-use super::track::Track;
-use super::types::{FileFormat, OutputFormat, PlaylistType};
-use super::{formatted, rekordbox, serato, utils};
+use super::diff::PlaylistDiff;
+use super::error::{PlaylistError, Result};
+use super::filter::TrackFilter;
+use super::json::JsonPlaylist;
+#[cfg(feature = "cli")]
+use super::key;
+use super::key::MusicalKey;
+#[cfg(feature = "fs")]
+use super::notes::TrackNotes;
+#[cfg(feature = "fs")]
+use super::options::SaveOptions;
+use super::options::{FormatOptions, ParseOptions};
+use super::reader::{PlaylistInput, ProgressRows, ReaderRegistry, Row, Rows, DEFAULT_READERS};
+use super::serato::SeratoTxtColumns;
+#[cfg(feature = "cli")]
+use super::stats;
+use super::stats::{PlaylistStats, StatsOptions};
+use super::track::{Track, METADATA_FIELDS};
+use super::types::{
+    DedupePolicy, DjAssignment, FileFormat, FooterField, KeyNotation, MergeStrategy, OutputFormat, PlaylistType,
+    SortKey, SplitPoint, TimePosition, TrackRename, TransliterateMode,
+};
+use super::validate::{self, Severity, ValidationIssue};
+#[cfg(feature = "fs")]
+use super::writer::WriterRegistry;
+use super::writer::{PlaylistWriter, DEFAULT_WRITERS};
+use super::{normalize, serato, timezone, utils};
 
 /// Holds imported playlist data
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Playlist {
     pub date: Option<NaiveDateTime>,
     pub file_format: FileFormat,
     pub file: PathBuf,
     pub name: String,
     pub playlist_type: PlaylistType,
+    /// Time zone of the track times, if known
+    pub timezone: Option<Tz>,
     pub total_duration: Option<TimeDelta>,
     pub tracks: Vec<Track>,
     // helpers for formatting
@@ -35,115 +76,1095 @@ pub struct Playlist {
     pub max_playtime_length: usize,
 }
 
+/// Maximum difference in start times for the same track logged by two machines to count as one play.
+const DUPLICATE_LOG_WINDOW: TimeDelta = TimeDelta::minutes(2);
+
+/// Narrowest the artist and title columns are shortened to when pretty output does not fit.
+#[cfg(feature = "cli")]
+const MIN_NAME_COLUMN_WIDTH: usize = 12;
+
+/// Width of the start time column in pretty output, for times like `23:59:59`.
+#[cfg(feature = "cli")]
+const START_TIME_WIDTH: usize = 8;
+
 impl Playlist {
     /// Initialize playlist from given filepath
+    #[cfg(feature = "fs")]
     pub fn new(file: &Path) -> Result<Playlist> {
-        match utils::playlist_format(file)? {
-            FileFormat::Csv => Self::read_csv(file),
-            FileFormat::Txt => Self::read_txt(file),
+        Self::new_with_options(file, &ParseOptions::default())
+    }
+
+    /// Initialize playlist from given filepath using the given parsing options
+    #[cfg(feature = "fs")]
+    pub fn new_with_options(file: &Path, options: &ParseOptions) -> Result<Playlist> {
+        Self::new_with_readers(file, options, &DEFAULT_READERS)
+    }
+
+    /// Initialize playlist from given filepath using the given readers to detect and parse the playlist type.
+    ///
+    /// The file is read as CSV or TXT rows based on the file extension or the input format,
+    /// and the first reader that detects the rows parses them.
+    #[cfg(feature = "fs")]
+    pub fn new_with_readers(file: &Path, options: &ParseOptions, readers: &ReaderRegistry) -> Result<Playlist> {
+        let file_format = Self::input_file_format(file, options)?;
+        let source =
+            File::open(file).map_err(|e| PlaylistError::io(format!("Failed to open file: '{}'", file.display()), e))?;
+        Self::read_with_readers(file, file_format, source, options, readers)
+    }
+
+    /// Parse playlist file contents, for example an uploaded file or the text of a file read elsewhere.
+    ///
+    /// The file name is used for detecting the file type from the extension and as the playlist name.
+    pub fn from_str(contents: &str, file_name: &str, options: &ParseOptions) -> Result<Playlist> {
+        Self::from_reader(Cursor::new(contents.to_string()), file_name, options)
+    }
+
+    /// Parse playlist file contents from a reader.
+    ///
+    /// Unlike [`Playlist::from_str`], this also accepts the UTF-16 text files exported by Rekordbox.
+    pub fn from_reader(source: impl Read + 'static, file_name: &str, options: &ParseOptions) -> Result<Playlist> {
+        let path = Path::new(file_name);
+        let file_format = Self::input_file_format(path, options)?;
+        Self::read_with_readers(path, file_format, source, options, &DEFAULT_READERS)
+    }
+
+    /// Get the file type from the input format, or from the file extension if it is not given.
+    fn input_file_format(path: &Path, options: &ParseOptions) -> Result<FileFormat> {
+        match options.input_format {
+            Some(input_format) => Ok(input_format.file_format()),
+            None => utils::playlist_format(path),
         }
     }
 
+    /// Read the playlist rows from the source and parse them with the matching reader.
+    fn read_with_readers(
+        path: &Path,
+        file_format: FileFormat,
+        source: impl Read + 'static,
+        options: &ParseOptions,
+        readers: &ReaderRegistry,
+    ) -> Result<Playlist> {
+        let _span = tracing::debug_span!("parse", file = %path.display()).entered();
+        let mut input = match file_format {
+            FileFormat::Csv => Self::read_csv_input(path, source, options)?,
+            FileFormat::Txt => Self::read_txt_input(path, source, options)?,
+        };
+        if let Some(progress) = &options.progress {
+            let rows = std::mem::replace(&mut input.rows, Box::new(std::iter::empty()));
+            input.rows = Box::new(ProgressRows::new(rows, path.to_path_buf(), progress.clone()));
+        }
+        let mut playlist = readers.read(input, options)?;
+        if options.strict {
+            let unknown_columns = playlist.extra_columns();
+            if !unknown_columns.is_empty() {
+                return Err(PlaylistError::InvalidData(format!(
+                    "Line 1: unknown columns in '{}': {}",
+                    path.display(),
+                    unknown_columns
+                        .iter()
+                        .map(|column| format!("'{column}'"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                )));
+            }
+        }
+        if options.strip_emoji {
+            normalize::strip_track_emoji(&mut playlist.tracks);
+            playlist.update_totals();
+        }
+        if !options.keep_missing_times {
+            playlist.tracks.iter_mut().for_each(Track::fill_missing_times);
+            playlist.update_totals();
+        }
+        if let Some(window) = options.merge_reloads {
+            playlist.tracks = utils::merge_reloads(std::mem::take(&mut playlist.tracks), window);
+            playlist.update_totals();
+        }
+        if options.mashups {
+            playlist.tracks = utils::merge_mashups(std::mem::take(&mut playlist.tracks));
+            playlist.update_totals();
+        }
+        Ok(playlist)
+    }
+
+    /// Combine multiple playlists into one.
+    ///
+    /// The earliest playlist date is used for the combined playlist,
+    /// and the file info and type are taken from the first playlist.
+    pub fn merge_playlists(playlists: Vec<Playlist>, strategy: MergeStrategy) -> Result<Playlist> {
+        let mut playlists = playlists.into_iter();
+        let mut merged = playlists
+            .next()
+            .ok_or_else(|| PlaylistError::InvalidArgument("No playlists to merge".to_string()))?;
+        for playlist in playlists {
+            merged.merge(playlist);
+        }
+
+        if matches!(strategy, MergeStrategy::Interleave | MergeStrategy::Timestamp) {
+            if merged.tracks.iter().all(|t| t.start_time.is_some()) {
+                merged.tracks.sort_by_key(|t| t.start_time);
+                if strategy == MergeStrategy::Timestamp {
+                    merged.tracks =
+                        utils::merge_logged_duplicates(std::mem::take(&mut merged.tracks), DUPLICATE_LOG_WINDOW);
+                }
+            } else {
+                tracing::warn!("Not all tracks have a start time, concatenating playlists instead");
+            }
+        }
+
+        merged.update_totals();
+        Ok(merged)
+    }
+
+    /// Split playlist into consecutive parts at the given points.
+    ///
+    /// Each part gets its own name suffix, date, and recomputed totals.
+    /// Elapsed times are measured from the first track start time,
+    /// or from the summed playtimes if the tracks have no start times.
+    pub fn split(&self, points: &[SplitPoint]) -> Result<Vec<Playlist>> {
+        let elapsed = self.elapsed_times();
+        let mut boundaries = Vec::with_capacity(points.len());
+        for point in points {
+            let index = match point {
+                SplitPoint::Track(number) => number.checked_sub(1).ok_or_else(|| {
+                    PlaylistError::InvalidArgument(format!("Split point is outside the playlist: {point}"))
+                })?,
+                SplitPoint::Elapsed(time) => {
+                    let elapsed = elapsed.as_ref().ok_or_else(|| {
+                        PlaylistError::InvalidArgument(
+                            "Playlist has no timing information to split by time".to_string(),
+                        )
+                    })?;
+                    elapsed.iter().position(|e| e >= time).unwrap_or(self.tracks.len())
+                }
+            };
+            if index == 0 || index >= self.tracks.len() {
+                return Err(PlaylistError::InvalidArgument(format!(
+                    "Split point is outside the playlist: {point}"
+                )));
+            }
+            boundaries.push(index);
+        }
+        boundaries.sort_unstable();
+        boundaries.dedup();
+        boundaries.push(self.tracks.len());
+
+        let mut parts = Vec::with_capacity(boundaries.len());
+        let mut start = 0;
+        for (number, end) in boundaries.into_iter().enumerate() {
+            let tracks = self.tracks[start..end].to_vec();
+            let date = tracks.first().and_then(|t| t.start_time).or(self.date);
+            let mut part = Playlist {
+                date,
+                file_format: self.file_format.clone(),
+                file: self.file.clone(),
+                name: format!("{} part {}", self.name, number + 1),
+                playlist_type: self.playlist_type.clone(),
+                timezone: self.timezone,
+                total_duration: None,
+                tracks,
+                max_artist_length: 0,
+                max_title_length: 0,
+                max_playtime_length: 0,
+            };
+            part.update_totals();
+            parts.push(part);
+            start = end;
+        }
+        Ok(parts)
+    }
+
+    /// Show track times in the given time zone.
+    ///
+    /// Times are converted from the time zone of the playlist.
+    /// If the playlist has no time zone info, the times are assumed to already be in the given zone.
+    pub fn convert_timezone(&mut self, to: Tz) {
+        let Some(from) = self.timezone else {
+            tracing::debug!("No time zone info, assuming times are in {}", to.name());
+            self.timezone = Some(to);
+            return;
+        };
+        if from == to {
+            return;
+        }
+        let convert = |time: Option<NaiveDateTime>| time.map(|t| timezone::convert(t, from, to));
+        self.date = convert(self.date);
+        for track in &mut self.tracks {
+            track.start_time = convert(track.start_time);
+            track.end_time = convert(track.end_time);
+        }
+        self.timezone = Some(to);
+    }
+
+    /// Set track start and end times by accumulating playtimes from the given set start time.
+    ///
+    /// The date is taken from the playlist date, or today if the playlist has no date.
+    /// Existing track times are replaced. Every track needs a playtime.
+    pub fn set_start_times(&mut self, set_start: NaiveTime) -> Result<()> {
+        if let Some(index) = self.tracks.iter().position(|t| t.play_time.is_none()) {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Track {} has no playtime to compute start times from",
+                index + 1
+            )));
+        }
+        let date = self.date.map_or_else(|| Local::now().date_naive(), |date| date.date());
+        let start = NaiveDateTime::new(date, set_start);
+        let mut current = start;
+        for track in &mut self.tracks {
+            track.start_time = Some(current);
+            current += track.play_time.unwrap_or_default();
+            track.end_time = Some(current);
+        }
+        self.date = Some(start);
+        Ok(())
+    }
+
+    /// Convert track keys to the given notation, leaving unrecognized keys as is.
+    pub fn convert_keys(&mut self, notation: KeyNotation) {
+        for track in &mut self.tracks {
+            if let Some(key) = track.key.as_deref().and_then(|key| MusicalKey::from_str(key).ok()) {
+                track.key = Some(key.to_notation(notation));
+            }
+        }
+    }
+
+    /// Read track notes from the given sidecar file and return the number of tracks that got a note.
+    #[cfg(feature = "fs")]
+    pub fn apply_notes_file(&mut self, path: &Path) -> Result<usize> {
+        let notes = TrackNotes::read(path)?;
+        Ok(notes.apply(&mut self.tracks))
+    }
+
+    /// Standardize featuring credits, remix brackets, and whitespace in artists and titles.
+    pub fn normalize_tracks(&mut self) {
+        let count = normalize::normalize_tracks(&mut self.tracks);
+        tracing::debug!("Normalized {count} tracks");
+        self.update_totals();
+    }
+
+    /// Convert all-caps and all-lowercase artists and titles to title case.
+    pub fn fix_case(&mut self) {
+        let count = normalize::fix_track_case(&mut self.tracks);
+        tracing::debug!("Fixed case for {count} tracks");
+        self.update_totals();
+    }
+
+    /// Add or substitute a romanized form for artist names written in a non-Latin script.
+    pub fn transliterate_artists(&mut self, mode: TransliterateMode) {
+        let count = normalize::transliterate_track_artists(&mut self.tracks, mode);
+        tracing::debug!("Transliterated {count} artists");
+        self.update_totals();
+    }
+
+    /// Remove the given suffixes such as `(Original Mix)` from track titles.
+    pub fn strip_suffixes(&mut self, suffixes: &[String]) {
+        let count = normalize::strip_track_suffixes(&mut self.tracks, suffixes);
+        tracing::debug!("Stripped suffixes from {count} tracks");
+        self.update_totals();
+    }
+
+    /// Change the artist and title of the given tracks.
+    pub fn rename_tracks(&mut self, renames: &[TrackRename]) -> Result<()> {
+        for rename in renames {
+            let count = self.tracks.len();
+            let track = rename
+                .number
+                .checked_sub(1)
+                .and_then(|index| self.tracks.get_mut(index));
+            let track = track.ok_or_else(|| {
+                PlaylistError::InvalidArgument(format!(
+                    "Track {} does not exist, playlist has {count} tracks",
+                    rename.number
+                ))
+            })?;
+            tracing::debug!("Renaming track {}: {track}", rename.number);
+            track.artist.clone_from(&rename.artist);
+            track.title.clone_from(&rename.title);
+        }
+        self.update_totals();
+        Ok(())
+    }
+
+    /// Set the DJ of each track from the first assignment whose time range or deck matches.
+    ///
+    /// Returns the number of tracks that were assigned a DJ.
+    pub fn assign_djs(&mut self, assignments: &[DjAssignment]) -> usize {
+        let mut count = 0;
+        for track in &mut self.tracks {
+            track.dj = assignments
+                .iter()
+                .find(|assignment| assignment.slot.contains(track.start_time, track.deck))
+                .map(|assignment| assignment.name.clone());
+            if track.dj.is_some() {
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Remove tracks with the given track numbers, counting from one.
+    pub fn remove_tracks(&mut self, numbers: &[usize]) -> Result<()> {
+        let mut numbers = numbers.to_vec();
+        numbers.sort_unstable();
+        numbers.dedup();
+        if let Some(&number) = numbers.iter().find(|&&n| n == 0 || n > self.tracks.len()) {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Track {number} does not exist, playlist has {} tracks",
+                self.tracks.len()
+            )));
+        }
+        for number in numbers.into_iter().rev() {
+            let track = self.tracks.remove(number - 1);
+            tracing::debug!("Removed track {number}: {track}");
+        }
+        self.update_totals();
+        Ok(())
+    }
+
+    /// Add a track to the end of the playlist.
+    pub fn push_track(&mut self, track: Track) {
+        self.tracks.push(track);
+        self.update_totals();
+    }
+
+    /// Remove and return the track at the given index, counting from zero.
+    ///
+    /// Returns `None` if there is no track at the index.
+    pub fn remove_track(&mut self, index: usize) -> Option<Track> {
+        if index >= self.tracks.len() {
+            return None;
+        }
+        let track = self.tracks.remove(index);
+        self.update_totals();
+        Some(track)
+    }
+
+    /// Keep only the tracks for which the predicate returns true.
+    pub fn retain(&mut self, predicate: impl FnMut(&Track) -> bool) {
+        self.tracks.retain(predicate);
+        self.update_totals();
+    }
+
+    /// Sort tracks with the given comparison function, keeping the order of equal tracks.
+    pub fn sort_by(&mut self, compare: impl FnMut(&Track, &Track) -> Ordering) {
+        self.tracks.sort_by(compare);
+    }
+
+    /// Merge consecutive plays of the same track and return the number of tracks removed.
+    pub fn dedup_consecutive(&mut self) -> usize {
+        let count = self.tracks.len();
+        self.tracks = utils::dedupe_tracks(std::mem::take(&mut self.tracks), DedupePolicy::Consecutive);
+        self.update_totals();
+        count - self.tracks.len()
+    }
+
+    /// Append the tracks of another playlist, keeping the earlier of the two playlist dates.
+    pub fn merge(&mut self, other: Playlist) {
+        self.date = match (self.date, other.date) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.tracks.extend(other.tracks);
+        self.update_totals();
+    }
+
+    /// Keep only the tracks accepted by all the given filters.
+    pub fn filter_tracks(&mut self, filters: &[TrackFilter]) {
+        let count = self.tracks.len();
+        self.tracks
+            .retain(|track| filters.iter().all(|filter| filter.accepts(track)));
+        tracing::debug!("Filtered out {} tracks", count - self.tracks.len());
+        self.update_totals();
+    }
+
+    /// Sort tracks by the given field.
+    ///
+    /// Text fields are compared case-insensitively. The sort is stable also in reverse,
+    /// so tracks with equal values keep their chronological order.
+    pub fn sort_tracks(&mut self, key: SortKey, reverse: bool) {
+        match key {
+            SortKey::Artist => {
+                Self::sort_by_key(&mut self.tracks, reverse, |t| {
+                    (t.artist.to_lowercase(), t.title.to_lowercase())
+                });
+            }
+            SortKey::Title => {
+                Self::sort_by_key(&mut self.tracks, reverse, |t| {
+                    (t.title.to_lowercase(), t.artist.to_lowercase())
+                });
+            }
+            SortKey::Playtime => Self::sort_by_key(&mut self.tracks, reverse, |t| t.play_time),
+            SortKey::StartTime => Self::sort_by_key(&mut self.tracks, reverse, |t| t.start_time),
+        }
+    }
+
+    /// Stable sort by the given key, reversing the key comparison instead of the sorted tracks.
+    fn sort_by_key<K: Ord>(tracks: &mut [Track], reverse: bool, key: impl Fn(&Track) -> K) {
+        if reverse {
+            tracks.sort_by_cached_key(|t| Reverse(key(t)));
+        } else {
+            tracks.sort_by_cached_key(key);
+        }
+    }
+
+    /// Drop the given number of tracks from the start and the end of the playlist.
+    pub fn skip_tracks(&mut self, first: usize, last: usize) -> Result<()> {
+        let skipped = first.saturating_add(last);
+        if skipped >= self.tracks.len() {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Can't skip {skipped} tracks from a playlist with {} tracks",
+                self.tracks.len()
+            )));
+        }
+        self.tracks.truncate(self.tracks.len() - last);
+        self.tracks.drain(..first);
+        if first > 0 {
+            if let Some(start_time) = self.tracks.first().and_then(|t| t.start_time) {
+                self.date = Some(start_time);
+            }
+        }
+        self.update_totals();
+        Ok(())
+    }
+
+    /// Keep only the tracks that start within the given time window.
+    ///
+    /// The start is inclusive and the end exclusive.
+    /// The playlist date and totals are updated to match the remaining tracks.
+    pub fn trim(&mut self, from: Option<TimePosition>, to: Option<TimePosition>) -> Result<()> {
+        let elapsed = self.elapsed_times().ok_or_else(|| {
+            PlaylistError::InvalidArgument("Playlist has no timing information to trim by time".to_string())
+        })?;
+        let from = from.map(|position| self.elapsed_time_for(position)).transpose()?;
+        let to = to.map(|position| self.elapsed_time_for(position)).transpose()?;
+        let tracks: Vec<Track> = std::mem::take(&mut self.tracks)
+            .into_iter()
+            .zip(elapsed)
+            .filter(|(_, elapsed)| from.is_none_or(|from| *elapsed >= from) && to.is_none_or(|to| *elapsed < to))
+            .map(|(track, _)| track)
+            .collect();
+        if tracks.is_empty() {
+            return Err(PlaylistError::InvalidArgument(
+                "No tracks within the given time range".to_string(),
+            ));
+        }
+        if let Some(start_time) = tracks.first().and_then(|t| t.start_time) {
+            self.date = Some(start_time);
+        }
+        self.tracks = tracks;
+        self.update_totals();
+        Ok(())
+    }
+
+    /// Convert time position to elapsed time from the start of the playlist.
+    ///
+    /// Clock times before the first track start are interpreted as being on the next day.
+    fn elapsed_time_for(&self, position: TimePosition) -> Result<TimeDelta> {
+        match position {
+            TimePosition::Elapsed(elapsed) => Ok(elapsed),
+            TimePosition::Clock(time) => {
+                let first_start = self.tracks.first().and_then(|t| t.start_time).ok_or_else(|| {
+                    PlaylistError::InvalidArgument(
+                        "Playlist has no track start times to trim by clock time".to_string(),
+                    )
+                })?;
+                let mut clock_time = first_start.date().and_time(time);
+                if clock_time < first_start {
+                    clock_time += TimeDelta::days(1);
+                }
+                Ok(clock_time - first_start)
+            }
+        }
+    }
+
+    /// Formatted elapsed time from the start of the set for each track,
+    /// if the elapsed column is enabled and the timing is known.
+    ///
+    /// The cue offset is added to each time, and negative times are shown as zero.
+    pub fn elapsed_column(&self, options: &FormatOptions) -> Option<Vec<String>> {
+        if !options.show_elapsed {
+            return None;
+        }
+        let elapsed = self.elapsed_times();
+        if elapsed.is_none() {
+            tracing::warn!("Elapsed times need start times or playtimes for all tracks");
+        }
+        Some(
+            elapsed?
+                .into_iter()
+                .map(|elapsed| utils::formatted_elapsed(elapsed + options.cue_offset))
+                .collect(),
+        )
+    }
+
+    /// Elapsed time from the start of the playlist for each track, if the timing is known.
+    fn elapsed_times(&self) -> Option<Vec<TimeDelta>> {
+        let first_start = self.tracks.first().and_then(|t| t.start_time);
+        if let Some(first_start) = first_start {
+            if self.tracks.iter().all(|t| t.start_time.is_some()) {
+                return Some(
+                    self.tracks
+                        .iter()
+                        .filter_map(|t| t.start_time)
+                        .map(|start| start - first_start)
+                        .collect(),
+                );
+            }
+        }
+        if self.tracks.iter().all(|t| t.play_time.is_some()) {
+            let mut total = TimeDelta::zero();
+            return Some(
+                self.tracks
+                    .iter()
+                    .map(|t| {
+                        let elapsed = total;
+                        total += t.play_time.unwrap_or_default();
+                        elapsed
+                    })
+                    .collect(),
+            );
+        }
+        None
+    }
+
+    /// Recompute total duration and formatting helpers after the tracks have changed.
+    pub fn update_totals(&mut self) {
+        self.total_duration = utils::get_total_playtime(&self.tracks);
+        self.max_artist_length = self.tracks.iter().map(|t| t.artist_length()).max().unwrap_or(0);
+        self.max_title_length = self.tracks.iter().map(|t| t.title_length()).max().unwrap_or(0);
+        self.max_playtime_length = utils::get_max_playtime_length(&self.tracks);
+    }
+
+    /// Iterate over the tracks in play order.
+    pub fn iter(&self) -> std::slice::Iter<'_, Track> {
+        self.tracks.iter()
+    }
+
+    /// Get the number of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Check if the playlist has no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
     /// Print playlist information (but not the tracks themselves)
-    pub fn print_info(&self) {
+    #[cfg(feature = "cli")]
+    pub fn print_info(&self, options: &FormatOptions) {
         println!("Playlist: {}", self.name.green());
         println!("Filepath: {}", self.file.display());
+        let date = match (self.date, self.time_range()) {
+            (_, Some((start, end))) if start.date() != end.date() => format!(
+                "{} - {}",
+                Self::formatted_date(start, options),
+                Self::formatted_date(end, options)
+            )
+            .magenta(),
+            (Some(date), _) => Self::formatted_date(date, options).magenta(),
+            (None, _) => "None".to_string().yellow(),
+        };
         println!(
             "Format: {}, Type: {}, Date: {}",
             self.file_format.to_string().cyan(),
             self.playlist_type.to_string().cyan(),
-            if let Some(date) = self.date {
-                date.format("%Y.%m.%d %H:%M").to_string().magenta()
-            } else {
-                "None".to_string().yellow()
-            }
+            date
         );
-        print!("Tracks: {}", self.tracks.len());
+        if let Some(timezone) = self.timezone {
+            println!("Timezone: {}", timezone.name().cyan());
+        }
+        let statistics = self.statistics();
+        if let (Some(min), Some(max), Some(average)) = (statistics.min_bpm, statistics.max_bpm, statistics.average_bpm)
+        {
+            println!("BPM: {}", stats::format_bpm_range(min, max, average).cyan());
+            if let Some(sparkline) = statistics.bpm_sparkline() {
+                println!("Tempo: {}", sparkline.cyan());
+            }
+        }
+        if !statistics.genres.is_empty() {
+            println!("Genres: {}", stats::format_value_counts(&statistics.genres, 5).cyan());
+        }
+        if !statistics.keys.is_empty() {
+            println!("Keys: {}", stats::format_value_counts(&statistics.keys, 5).cyan());
+        }
+        print!("Tracks: {}", statistics.track_count);
         if let Some(duration) = self.total_duration {
-            print!(", Total duration: {}", utils::formatted_duration(duration));
-            let average = TimeDelta::try_seconds(duration.num_seconds() / self.tracks.len() as i64).unwrap();
-            print!(" (avg. {} per track)", utils::formatted_duration(average));
+            print!(", Total duration: {}", options.formatted_duration(duration));
+            if let Some(average) = statistics.average_playtime_seconds.and_then(TimeDelta::try_seconds) {
+                print!(" (avg. {} per track)", options.formatted_duration(average));
+            }
         };
         println!("\n");
     }
 
+    /// Get the first track start time and the last track end time,
+    /// or the last start time if the last track has no end time.
+    pub fn time_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let start = self.tracks.iter().find_map(|t| t.start_time)?;
+        let end = self.tracks.iter().rev().find_map(|t| t.end_time.or(t.start_time))?;
+        Some((start, end.max(start)))
+    }
+
     /// Print a simple playlist without any formatting.
-    pub fn print_simple_playlist(&self) {
+    pub fn print_simple_playlist(&self, options: &FormatOptions) {
         for track in &self.tracks {
-            println!("{track}");
+            println!("{}", Self::search_linked(track.display_name(), track, options));
+            for mashup in &track.mashups {
+                println!("  w/ {}", mashup.name());
+            }
         }
     }
 
     /// Print a simple playlist with track numbers.
-    pub fn print_numbered_playlist(&self) {
-        let index_width = self.tracks.len().to_string().chars().count();
+    pub fn print_numbered_playlist(&self, options: &FormatOptions) {
+        let index_width = self.track_number_width(options);
         for (index, track) in self.tracks.iter().enumerate() {
-            println!("{:>0index_width$}: {}", index + 1, track, index_width = index_width);
+            println!(
+                "{:>0index_width$}: {}",
+                self.track_number(index, options),
+                Self::search_linked(track.display_name(), track, options)
+            );
+            for mashup in &track.mashups {
+                println!("{:index_width$}    w/ {}", "", mashup.name());
+            }
+        }
+    }
+
+    /// Link the text to a web search for the track if search links are enabled and the output is a terminal.
+    fn search_linked(text: String, track: &Track, options: &FormatOptions) -> String {
+        match &options.search_links {
+            Some(search) if std::io::stdout().is_terminal() => {
+                utils::hyperlink(&text, &search.url(&format!("{} {}", track.artist, track.title)))
+            }
+            _ => text,
         }
     }
 
+    /// Get the text for each footer field that has a value.
+    fn footer_parts(&self, fields: &[FooterField], options: &FormatOptions) -> Vec<String> {
+        let average = fields
+            .contains(&FooterField::Average)
+            .then(|| self.statistics().average_playtime_seconds)
+            .flatten()
+            .and_then(TimeDelta::try_seconds);
+        fields
+            .iter()
+            .filter_map(|field| match field {
+                FooterField::Tracks => Some(format!("{} tracks", self.tracks.len())),
+                FooterField::Duration => self
+                    .total_duration
+                    .map(|duration| format!("total {}", options.formatted_duration(duration))),
+                FooterField::Average => {
+                    average.map(|average| format!("avg. {} per track", options.formatted_duration(average)))
+                }
+            })
+            .collect()
+    }
+
+    /// Get the summary text and the total duration for the totals row of saved files.
+    ///
+    /// The summary goes in the artist column, so the row is not read back as a track.
+    /// Returns `None` when there is nothing to show.
+    fn file_footer(&self, options: &FormatOptions) -> Option<(String, String)> {
+        let fields = options.footer.as_deref().unwrap_or(&[FooterField::Duration]);
+        let duration = self
+            .total_duration
+            .filter(|_| fields.contains(&FooterField::Duration))
+            .map(|duration| options.formatted_duration(duration))
+            .unwrap_or_default();
+        let other_fields: Vec<FooterField> = fields
+            .iter()
+            .copied()
+            .filter(|&field| field != FooterField::Duration)
+            .collect();
+        let summary = self.footer_parts(&other_fields, options).join(", ");
+        (!summary.is_empty() || !duration.is_empty()).then_some((summary, duration))
+    }
+
+    /// Get the displayed number for the track at the given index.
+    ///
+    /// Uses the number from the source playlist if original numbers are enabled and the track has one.
+    pub fn track_number(&self, index: usize, options: &FormatOptions) -> usize {
+        let original = self
+            .tracks
+            .get(index)
+            .and_then(|track| track.number)
+            .filter(|_| options.original_numbers);
+        original.unwrap_or_else(|| options.start_number.unwrap_or(1) + index)
+    }
+
+    /// Get the number of characters needed for the largest track number.
+    fn track_number_width(&self, options: &FormatOptions) -> usize {
+        (0..self.tracks.len().max(1))
+            .map(|index| self.track_number(index, options).to_string().chars().count())
+            .max()
+            .unwrap_or(1)
+    }
+
     /// Print a nicely formatted playlist.
-    pub fn print_pretty_playlist(&self) {
-        let index_width = self.tracks.len().to_string().chars().count();
+    #[cfg(feature = "cli")]
+    pub fn print_pretty_playlist(&self, options: &FormatOptions) {
+        let index_width = self.track_number_width(options);
         let playtime_width = if self.max_playtime_length > 0 {
             max(self.max_playtime_length, "PLAYTIME".to_string().chars().count())
         } else {
             0
         };
 
-        let header = if self.max_playtime_length > 0 {
-            format!(
-                "{:<index_width$}   {:<artist_width$}   {:<title_width$}   {:>playtime_width$}",
-                "#",
-                "ARTIST",
-                "TITLE",
-                "PLAYTIME",
-                index_width = index_width,
-                artist_width = self.max_artist_length,
-                title_width = self.max_title_length,
-                playtime_width = playtime_width
-            )
+        let elapsed = self.elapsed_column(options);
+        let elapsed_width = elapsed
+            .iter()
+            .flatten()
+            .map(|e| utils::display_width(e))
+            .max()
+            .unwrap_or(0)
+            .max("ELAPSED".len());
+        let elapsed_header = elapsed
+            .as_ref()
+            .map(|_| format!("   {:<elapsed_width$}", "ELAPSED"))
+            .unwrap_or_default();
+        let start_times = self.start_time_column(options);
+        let start_header = start_times
+            .as_ref()
+            .map(|_| format!("   {:<START_TIME_WIDTH$}", "START"))
+            .unwrap_or_default();
+        let time_columns_header = format!("{start_header}{elapsed_header}");
+
+        // Columns after the title, the artist and title widths are known once the other columns fit
+        let mut header = if playtime_width > 0 {
+            format!("   {:>playtime_width$}", "PLAYTIME")
         } else {
-            format!(
-                "{:<index_width$}   {:<artist_width$}   {:<title_width$}",
-                "#",
-                "ARTIST",
-                "TITLE",
-                index_width = index_width,
-                artist_width = self.max_artist_length,
-                title_width = self.max_title_length,
-            )
+            String::new()
+        };
+        let overlaps: Option<Vec<Option<TimeDelta>>> = options.long_overlap.map(|_| {
+            (0..self.tracks.len())
+                .map(|index| stats::track_overlap(&self.tracks, index))
+                .collect()
+        });
+        if overlaps.is_some() {
+            header = format!("{header}   {:>7}", "OVERLAP");
+        }
+        let dj_width = self
+            .tracks
+            .iter()
+            .filter_map(|t| t.dj.as_deref().map(utils::display_width))
+            .max()
+            .map(|width| max(width, "DJ".len()));
+        if let Some(width) = dj_width {
+            header = format!("{header}   {:<width$}", "DJ");
+        }
+        let metadata_columns: Vec<usize> = self
+            .metadata_columns()
+            .into_iter()
+            .filter(|&column| options.show_deck || METADATA_FIELDS[column] != "Deck")
+            .collect();
+        let key_clashes: BTreeSet<usize> = if options.show_key_clashes {
+            key::key_clashes(&self.tracks).into_iter().collect()
+        } else {
+            BTreeSet::new()
         };
+        let tempo_jumps: BTreeSet<usize> = options
+            .bpm_jump
+            .map(|threshold| stats::tempo_jumps(&self.tracks, threshold).into_iter().collect())
+            .unwrap_or_default();
+        // Keys clashing with and tempo jumps from the previous track are marked with an exclamation mark
+        let metadata_rows: Vec<[Option<String>; METADATA_FIELDS.len()]> = self
+            .tracks
+            .iter()
+            .enumerate()
+            .map(|(index, track)| {
+                let mut metadata = track.metadata();
+                for (value, field) in metadata.iter_mut().zip(METADATA_FIELDS) {
+                    let marked = match field {
+                        "Key" => key_clashes.contains(&index),
+                        "BPM" => tempo_jumps.contains(&index),
+                        _ => false,
+                    };
+                    if marked {
+                        *value = value.take().map(|value| format!("{value} !"));
+                    }
+                }
+                metadata
+            })
+            .collect();
+        let metadata_widths: Vec<usize> = metadata_columns
+            .iter()
+            .map(|&column| {
+                metadata_rows
+                    .iter()
+                    .filter_map(|metadata| metadata[column].as_deref().map(utils::display_width))
+                    .max()
+                    .unwrap_or(0)
+                    .max(METADATA_FIELDS[column].len())
+            })
+            .collect();
+        for (&column, &width) in metadata_columns.iter().zip(&metadata_widths) {
+            header = format!("{header}   {:<width$}", METADATA_FIELDS[column].to_uppercase());
+        }
+        let notes_width = self
+            .tracks
+            .iter()
+            .filter_map(|t| t.notes.as_deref().map(utils::display_width))
+            .max()
+            .map(|width| max(width, "NOTES".len()));
+        if let Some(width) = notes_width {
+            header = format!("{header}   {:<width$}", "NOTES");
+        }
 
-        let header_width = header.chars().count();
+        let (artist_width, title_width) = self.name_column_widths(
+            options,
+            index_width + time_columns_header.len() + 6 + utils::display_width(&header),
+        );
+        let header = format!(
+            "{:<index_width$}{time_columns_header}   {:<artist_width$}   {:<title_width$}{header}",
+            "#", "ARTIST", "TITLE"
+        );
+        let header_width = utils::display_width(&header);
         let divider = "-".repeat(header_width);
 
         println!("{}", header.bold());
         println!("{divider}");
 
         for (index, track) in self.tracks.iter().enumerate() {
-            let playtime = if let Some(d) = track.play_time {
-                utils::formatted_duration(d).green()
+            let playtime = if playtime_width == 0 {
+                String::new()
+            } else if let Some(d) = track.play_time {
+                format!("   {:>playtime_width$}", options.formatted_playtime(d).green())
             } else {
-                "".normal()
+                format!("   {:>playtime_width$}", "")
+            };
+            let metadata: String = metadata_columns
+                .iter()
+                .zip(&metadata_widths)
+                .map(|(&column, &width)| {
+                    format!(
+                        "   {}",
+                        utils::pad_to_width(metadata_rows[index][column].as_deref().unwrap_or_default(), width)
+                    )
+                })
+                .collect();
+            let notes = match (notes_width, &track.notes) {
+                (Some(_), Some(notes)) => format!("   {}", notes.yellow()),
+                _ => String::new(),
+            };
+            let start = start_times
+                .as_ref()
+                .map(|times| format!("   {:<START_TIME_WIDTH$}", times[index]))
+                .unwrap_or_default();
+            let elapsed = elapsed
+                .as_ref()
+                .map(|e| format!("   {:<elapsed_width$}", e[index]))
+                .unwrap_or_default();
+            let dj = dj_width
+                .map(|width| {
+                    format!(
+                        "   {}",
+                        utils::pad_to_width(track.dj.as_deref().unwrap_or_default(), width)
+                    )
+                })
+                .unwrap_or_default();
+            let overlap = match (&overlaps, options.long_overlap) {
+                (Some(overlaps), Some(threshold)) => {
+                    let text = overlaps[index].map(utils::formatted_duration).unwrap_or_default();
+                    if overlaps[index].is_some_and(|overlap| overlap > threshold) {
+                        format!("   {:>7}", text.red())
+                    } else {
+                        format!("   {text:>7}")
+                    }
+                }
+                _ => String::new(),
             };
+            // Padding is added after the link so only the title itself is clickable
+            let title = utils::truncate_to_width(&track.display_title(), title_width);
+            let title = format!(
+                "{}{}",
+                Self::search_linked(title.clone(), track, options),
+                " ".repeat(title_width.saturating_sub(utils::display_width(&title)))
+            );
             println!(
-                "{:>0index_width$}   {:<artist_width$}   {:<title_width$}   {:>playtime_width$}",
-                index + 1,
-                track.artist,
-                track.title,
+                "{:>0index_width$}{}{}   {}   {}{}{}{}{}{}",
+                self.track_number(index, options),
+                start.blue(),
+                elapsed.cyan(),
+                utils::pad_to_width(&utils::truncate_to_width(&track.artist, artist_width), artist_width),
+                title,
                 playtime,
+                overlap,
+                dj.magenta(),
+                metadata.cyan(),
+                notes,
                 index_width = index_width,
-                artist_width = self.max_artist_length,
-                title_width = self.max_title_length,
-                playtime_width = playtime_width
             );
+            for mashup in &track.mashups {
+                println!(
+                    "{:index_width$}{}   {}",
+                    "",
+                    " ".repeat(time_columns_header.len()),
+                    format!("  w/ {}", mashup.name()).dimmed(),
+                );
+            }
         }
 
         println!("{divider}");
+        let footer = self.footer_parts(options.footer.as_deref().unwrap_or_default(), options);
+        if !footer.is_empty() {
+            println!("{}", footer.join(", "));
+        }
+        if let Some(threshold) = options.bpm_jump {
+            if self.tracks.iter().any(|t| t.bpm.is_some()) {
+                println!(
+                    "Tempo jumps: {} over {} BPM",
+                    tempo_jumps.len().to_string().red(),
+                    utils::formatted_bpm(threshold)
+                );
+            }
+        }
+        if options.show_key_clashes {
+            let transitions = key::key_transition_count(&self.tracks);
+            if transitions > 0 {
+                println!(
+                    "Key clashes: {} of {transitions} transitions",
+                    key_clashes.len().to_string().red()
+                );
+            }
+        }
+    }
+
+    /// Formatted start time for each track, if the start time column is enabled and the times are known.
+    #[cfg(feature = "cli")]
+    fn start_time_column(&self, options: &FormatOptions) -> Option<Vec<String>> {
+        if !options.show_start_times {
+            return None;
+        }
+        if self.tracks.iter().all(|track| track.start_time.is_none()) {
+            tracing::warn!("Start time column needs start times for the tracks");
+            return None;
+        }
+        Some(
+            self.tracks
+                .iter()
+                .map(|track| {
+                    track
+                        .start_time
+                        .map(|time| time.format("%H:%M:%S").to_string())
+                        .unwrap_or_default()
+                })
+                .collect(),
+        )
+    }
+
+    /// Get the artist and title column widths for pretty output.
+    ///
+    /// When the full table is wider than the maximum width or the terminal,
+    /// the title column is shortened first and then the artist column, down to a minimum width.
+    #[cfg(feature = "cli")]
+    fn name_column_widths(&self, options: &FormatOptions, other_columns_width: usize) -> (usize, usize) {
+        let mut artist_width = self.max_artist_length;
+        let mut title_width = self.max_title_length;
+        if let Some(max_width) = options.max_width.or_else(utils::terminal_width) {
+            let mut excess = (other_columns_width + artist_width + title_width).saturating_sub(max_width);
+            let title_cut = excess.min(title_width.saturating_sub(MIN_NAME_COLUMN_WIDTH));
+            title_width -= title_cut;
+            excess -= title_cut;
+            artist_width -= excess.min(artist_width.saturating_sub(MIN_NAME_COLUMN_WIDTH));
+        }
+        (artist_width, title_width)
+    }
+
+    /// Format a playlist date with the time of day for printing.
+    #[cfg(feature = "cli")]
+    fn formatted_date(date: NaiveDateTime, options: &FormatOptions) -> String {
+        format!("{} {}", options.date_format.format(date), date.format("%H:%M"))
+    }
+
+    /// Get the indices of the metadata fields that at least one track has a value for.
+    ///
+    /// Indices refer to [`METADATA_FIELDS`].
+    pub fn metadata_columns(&self) -> Vec<usize> {
+        (0..METADATA_FIELDS.len())
+            .filter(|&column| self.tracks.iter().any(|t| t.metadata()[column].is_some()))
+            .collect()
+    }
+
+    /// Get the names of the extra input columns written to saved files.
+    fn output_extra_columns(&self, options: &FormatOptions) -> Vec<String> {
+        if options.include_extras {
+            self.extra_columns()
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Get the names of the extra input columns that at least one track has a value for.
+    pub fn extra_columns(&self) -> Vec<String> {
+        self.tracks
+            .iter()
+            .flat_map(|t| t.extras.keys().cloned())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Check if any track has notes.
+    pub fn has_notes(&self) -> bool {
+        self.tracks.iter().any(|t| t.notes.is_some())
+    }
+
+    /// Check if any track has a DJ assigned.
+    pub fn has_djs(&self) -> bool {
+        self.tracks.iter().any(|t| t.dj.is_some())
+    }
+
+    /// Check if any track was played more than once.
+    pub fn has_repeats(&self) -> bool {
+        self.tracks.iter().any(|t| t.plays > 1)
+    }
+
+    /// Check if any track has mashups.
+    pub fn has_mashups(&self) -> bool {
+        self.tracks.iter().any(|t| !t.mashups.is_empty())
+    }
+
+    /// Calculate statistics for the tracks, like playtimes, artist counts, and BPM and key aggregates.
+    pub fn statistics(&self) -> PlaylistStats {
+        PlaylistStats::from_tracks(&self.tracks)
+    }
+
+    /// Calculate statistics using the given thresholds for flagging transitions.
+    pub fn statistics_with_options(&self, options: &StatsOptions) -> PlaylistStats {
+        PlaylistStats::from_tracks_with_options(&self.tracks, options)
+    }
+
+    /// Check the tracks for common problems like missing fields, duplicates, and gaps.
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        validate::validate_tracks(&self.tracks, &StatsOptions::default())
+    }
+
+    /// Check the tracks for common problems, using the gap threshold from the options.
+    pub fn validate_with_options(&self, options: &StatsOptions) -> Vec<ValidationIssue> {
+        validate::validate_tracks(&self.tracks, options)
+    }
+
+    /// Get the tracks added, removed, moved, and changed in the other playlist compared to this one.
+    pub fn diff(&self, other: &Playlist) -> PlaylistDiff {
+        PlaylistDiff::new(&self.tracks, &other.tracks)
+    }
+
+    /// Get data quality warnings for the parsed tracks.
+    ///
+    /// These are the validation issues with at least warning severity, for example empty artists,
+    /// consecutive duplicate tracks, zero playtimes, and timestamps out of order.
+    pub fn quality_warnings(&self) -> Vec<String> {
+        self.validate()
+            .into_iter()
+            .filter(|issue| issue.severity >= Severity::Warning)
+            .map(|issue| format!("Track {}: {}", issue.track_index + 1, issue.message))
+            .collect()
     }
 
     /// Get output file path.
+    #[cfg(feature = "fs")]
     pub fn get_output_file_path(
         &self,
         filepath: Option<String>,
@@ -180,7 +1201,32 @@ impl Playlist {
         }
     }
 
+    /// Get output file path inside a year and month subdirectory, for example `2024/03/`.
+    ///
+    /// Uses the playlist date, falling back to the input file modification date.
+    #[cfg(feature = "fs")]
+    pub fn get_organized_output_file_path(
+        &self,
+        filepath: Option<String>,
+        use_default_dir: bool,
+        output_format: &OutputFormat,
+    ) -> PathBuf {
+        let path = self.get_output_file_path(filepath, use_default_dir, output_format);
+        let Some(date) = self.date.or_else(|| utils::file_modified_datetime(&self.file)) else {
+            tracing::warn!("No date found for playlist, not using a date subdirectory");
+            return path;
+        };
+        match (path.parent(), path.file_name()) {
+            (Some(parent), Some(file_name)) => parent
+                .join(date.format("%Y").to_string())
+                .join(date.format("%m").to_string())
+                .join(file_name),
+            _ => path,
+        }
+    }
+
     /// Write playlist to given file.
+    #[cfg(feature = "fs")]
     pub fn save_to_file(
         &self,
         filepath: Option<String>,
@@ -189,27 +1235,230 @@ impl Playlist {
         output_format: &OutputFormat,
     ) -> Result<()> {
         let path = self.get_output_file_path(filepath, use_default_dir, output_format);
-        log::info!("Saving to: {}", path.display());
+        self.save_to_path(&path, overwrite_existing)
+    }
+
+    /// Write playlist to the given output file path.
+    ///
+    /// The output format is determined from the file extension.
+    /// Missing parent directories are created.
+    #[cfg(feature = "fs")]
+    pub fn save_to_path(&self, path: &Path, overwrite_existing: bool) -> Result<()> {
+        self.save_with_writers(path, overwrite_existing, &DEFAULT_WRITERS)
+    }
+
+    /// Write the playlist to the given output file path using the given options.
+    ///
+    /// The output format is taken from the options regardless of the file extension.
+    /// Missing parent directories are created.
+    #[cfg(feature = "fs")]
+    pub fn save_with_options(&self, path: &Path, options: &SaveOptions) -> Result<()> {
+        let writer = DEFAULT_WRITERS.for_format(&options.format.output_format)?;
+        self.save_with_writer(path, options.overwrite, writer, &options.format)
+    }
+
+    /// Write playlist to the given output file path using the writer registered for the file extension.
+    #[cfg(feature = "fs")]
+    pub fn save_with_writers(&self, path: &Path, overwrite_existing: bool, writers: &WriterRegistry) -> Result<()> {
+        let extension = path
+            .extension()
+            .ok_or_else(|| PlaylistError::UnsupportedFormat("Output file has no extension".to_string()))?
+            .to_str()
+            .ok_or_else(|| {
+                PlaylistError::UnsupportedFormat("Output file extension cannot be converted to string".to_string())
+            })?;
+        let writer = writers.find(extension)?;
+        let options = writer
+            .output_format()
+            .map_or_else(FormatOptions::default, |output_format| {
+                FormatOptions::for_format(&output_format)
+            });
+        self.save_with_writer(path, overwrite_existing, writer, &options)
+    }
+
+    /// Write playlist to the given output file path with the given writer.
+    #[cfg(feature = "fs")]
+    fn save_with_writer(
+        &self,
+        path: &Path,
+        overwrite_existing: bool,
+        writer: &dyn PlaylistWriter,
+        options: &FormatOptions,
+    ) -> Result<()> {
+        tracing::info!("Saving to: {}", path.display());
         if path.is_file() {
             if !overwrite_existing {
-                log::error!("Output file already exists: {}", path.display());
-                anyhow::bail!("use the {} option overwrite an existing output file", "force".bold());
+                tracing::error!("Output file already exists: {}", path.display());
+                return Err(PlaylistError::InvalidArgument(
+                    "use the force option to overwrite an existing output file".to_string(),
+                ));
             }
-            log::info!("Overwriting existing file");
+            tracing::info!("Overwriting existing file");
         }
 
-        let extension = path
-            .extension()
-            .ok_or_else(|| anyhow!("Output file has no extension"))?
-            .to_str()
-            .ok_or_else(|| anyhow!("Output file extension cannot be converted to string"))?
-            .to_lowercase();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty() && !p.exists()) {
+            tracing::debug!("Creating directory: {}", parent.display());
+            Self::create_dir(parent)?;
+        }
+        let data = self.write_with(writer, options)?;
+        std::fs::write(path, data)
+            .map_err(|e| PlaylistError::io(format!("Failed to write file: '{}'", path.display()), e))
+    }
 
-        match extension.as_str() {
-            "csv" => self.write_csv_file(&path),
-            "txt" => self.write_txt_file(&path),
-            "xlsx" => self.write_excel_file(&path),
-            _ => anyhow::bail!("Unsupported file extension"),
+    /// Append playlist to an existing archive file, or create the archive if it does not exist yet.
+    ///
+    /// CSV archives get new rows with the playlist date and name,
+    /// so multiple sets can be kept in the same file.
+    /// Excel archives get a new worksheet for each playlist.
+    #[cfg(feature = "fs")]
+    pub fn append_to_path(&self, path: &Path, options: &FormatOptions) -> Result<()> {
+        let output_format = OutputFormat::from_path(path);
+        let is_excel = cfg!(feature = "xlsx") && output_format == Some(OutputFormat::Xlsx);
+        if output_format != Some(OutputFormat::Csv) && !is_excel {
+            return Err(PlaylistError::UnsupportedFormat(format!(
+                "Appending is only supported for CSV and XLSX files: '{}'",
+                path.display()
+            )));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty() && !p.exists()) {
+            tracing::debug!("Creating directory: {}", parent.display());
+            Self::create_dir(parent)?;
+        }
+        #[cfg(feature = "xlsx")]
+        if is_excel {
+            return self.append_to_excel(path, options);
+        }
+
+        let header = ["Date", "Set", "Artist", "", "Title", "Playtime"];
+        let exists = path.is_file()
+            && std::fs::metadata(path)
+                .map_err(|e| PlaylistError::io(format!("Failed to read file: '{}'", path.display()), e))?
+                .len()
+                > 0;
+        if exists {
+            let file = File::open(path)
+                .map_err(|e| PlaylistError::io(format!("Failed to open CSV file: '{}'", path.display()), e))?;
+            let mut reader = Reader::from_reader(file);
+            if reader.headers()?.iter().ne(header) {
+                return Err(PlaylistError::InvalidData(format!(
+                    "Existing file is not a playlist archive: '{}'",
+                    path.display()
+                )));
+            }
+            tracing::info!("Appending to: {}", path.display());
+        } else {
+            tracing::info!("Creating archive: {}", path.display());
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| PlaylistError::io(format!("Failed to open file: '{}'", path.display()), e))?;
+        let mut writer = csv::Writer::from_writer(file);
+        if !exists {
+            writer.write_record(header)?;
+        }
+        let date = self.date.map(|d| options.date_format.format(d)).unwrap_or_default();
+        for track in &self.tracks {
+            writer.write_record([
+                date.as_str(),
+                self.name.as_str(),
+                track.artist.as_str(),
+                "-",
+                track.title.as_str(),
+                &track.play_time.map_or(String::new(), |d| options.formatted_playtime(d)),
+            ])?;
+        }
+        writer
+            .flush()
+            .map_err(|e| PlaylistError::io(format!("Failed to write file: '{}'", path.display()), e))
+    }
+
+    /// Write playlist to the given path using the given output format regardless of the file extension.
+    #[cfg(feature = "fs")]
+    pub fn write_file(&self, path: &Path, output_format: &OutputFormat) -> Result<()> {
+        let data = self.to_bytes(output_format)?;
+        std::fs::write(path, data)
+            .map_err(|e| PlaylistError::io(format!("Failed to write file: '{}'", path.display()), e))
+    }
+
+    /// Create a directory and its missing parents.
+    #[cfg(feature = "fs")]
+    fn create_dir(path: &Path) -> Result<()> {
+        std::fs::create_dir_all(path)
+            .map_err(|e| PlaylistError::io(format!("Failed to create directory: '{}'", path.display()), e))
+    }
+
+    /// Write the playlist in the given output format to a writer,
+    /// for example a buffer, a network response, or an archive entry.
+    pub fn write_to(&self, writer: &mut impl Write, output_format: &OutputFormat) -> Result<()> {
+        let data = self.to_bytes(output_format)?;
+        writer
+            .write_all(&data)
+            .map_err(|e| PlaylistError::io(format!("Failed to write {output_format} output"), e))
+    }
+
+    /// Render the playlist in a text-based output format.
+    ///
+    /// Excel files are binary and can only be written with [`Playlist::write_to`] or saved to a file.
+    pub fn render_string(&self, output_format: &OutputFormat) -> Result<String> {
+        self.render_string_with_options(&FormatOptions::for_format(output_format))
+    }
+
+    /// Render the playlist in a text-based output format using the given options.
+    pub fn render_string_with_options(&self, options: &FormatOptions) -> Result<String> {
+        let writer = DEFAULT_WRITERS.for_format(&options.output_format)?;
+        if writer.is_binary() {
+            return Err(PlaylistError::UnsupportedFormat(format!(
+                "{} output is binary and can't be rendered as a string",
+                options.output_format
+            )));
+        }
+        let data = self.write_with(writer, options)?;
+        String::from_utf8(data).map_err(|_| PlaylistError::InvalidData("Output is not valid UTF-8".to_string()))
+    }
+
+    /// Get the file contents for the given output format.
+    pub fn to_bytes(&self, output_format: &OutputFormat) -> Result<Vec<u8>> {
+        self.to_bytes_with_options(&FormatOptions::for_format(output_format))
+    }
+
+    /// Get the file contents using the given options.
+    pub fn to_bytes_with_options(&self, options: &FormatOptions) -> Result<Vec<u8>> {
+        let writer = DEFAULT_WRITERS.for_format(&options.output_format)?;
+        self.write_with(writer, options)
+    }
+
+    /// Get the file contents from the writer.
+    ///
+    /// Duplicates are merged in a copy of the tracks when the options have a dedupe policy.
+    fn write_with(&self, writer: &dyn PlaylistWriter, options: &FormatOptions) -> Result<Vec<u8>> {
+        let _span = tracing::debug_span!("write", format = writer.extension()).entered();
+        if options.dedupe == DedupePolicy::Off {
+            return writer.write(self, options);
+        }
+        let mut deduped = self.clone();
+        deduped.tracks = utils::dedupe_tracks(deduped.tracks, options.dedupe);
+        deduped.update_totals();
+        writer.write(&deduped, options)
+    }
+
+    /// Check if the given file already has the same contents this playlist would be saved as with the given options.
+    ///
+    /// The output format is taken from the file extension.
+    #[cfg(feature = "fs")]
+    pub fn is_up_to_date(&self, path: &Path, options: &FormatOptions) -> bool {
+        let Some(format) = OutputFormat::from_path(path) else {
+            return false;
+        };
+        let options = FormatOptions {
+            output_format: format,
+            ..options.clone()
+        };
+        match (std::fs::read(path), self.to_bytes_with_options(&options)) {
+            (Ok(existing), Ok(data)) => existing == data,
+            _ => false,
         }
     }
 
@@ -218,6 +1467,7 @@ impl Playlist {
     /// This will first try to use the Dropbox playlist directory if it exists on disk.
     /// After that, it will try the get the directory of the input file.
     /// Otherwise, returns an empty path so the file will go to the current working directory.
+    #[cfg(feature = "fs")]
     fn default_save_dir(&self) -> PathBuf {
         utils::dropbox_save_dir().unwrap_or_else(|| {
             dunce::canonicalize(&self.file)
@@ -228,187 +1478,496 @@ impl Playlist {
                     )
                 })
                 .unwrap_or_else(|error| {
-                    log::error!("Failed to resolve full path to input file: {}", error);
+                    tracing::error!("Failed to resolve full path to input file: {}", error);
                     env::current_dir().unwrap_or_else(|_| PathBuf::new())
                 })
         })
     }
 
-    /// Write tracks to CSV file
-    fn write_csv_file(&self, filepath: &Path) -> Result<()> {
-        let mut writer = csv::Writer::from_path(filepath)?;
-        writer.write_record(["Artist", "", "Title", "Playtime", "Start time", "End time"])?;
-        for track in &self.tracks {
+    /// Get tracks as CSV file contents
+    pub(crate) fn csv_bytes(&self, options: &FormatOptions) -> Result<Vec<u8>> {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        let number_header = options.has_number_column().then(|| "#".to_string());
+        let elapsed = self.elapsed_column(options);
+        let metadata_columns = self.metadata_columns();
+        let extra_columns = self.output_extra_columns(options);
+        let dj_header = self.has_djs().then(|| "DJ".to_string());
+        let plays_header = self.has_repeats().then(|| "Plays".to_string());
+        let mashup_header = self.has_mashups().then(|| "Mashup".to_string());
+        let notes_header = self.has_notes().then(|| "Notes".to_string());
+        writer.write_record(
+            number_header
+                .into_iter()
+                .chain(elapsed.as_ref().map(|_| "Elapsed".to_string()))
+                .chain(["Artist", "", "Title", "Playtime", "Start time", "End time"].map(String::from))
+                .chain(
+                    metadata_columns
+                        .iter()
+                        .map(|&column| METADATA_FIELDS[column].to_string()),
+                )
+                .chain(extra_columns.iter().cloned())
+                .chain(dj_header)
+                .chain(plays_header)
+                .chain(mashup_header)
+                .chain(notes_header),
+        )?;
+        for (index, track) in self.tracks.iter().enumerate() {
             let duration = match track.play_time {
                 None => String::new(),
-                Some(d) => utils::formatted_duration(d),
-            };
-            let start_time = match track.start_time {
-                None => String::new(),
-                Some(t) => t.format("%Y.%m.%d %H:%M:%S").to_string(),
-            };
-            let end_time = match track.end_time {
-                None => String::new(),
-                Some(t) => t.format("%Y.%m.%d %H:%M:%S").to_string(),
+                Some(d) => options.formatted_playtime(d),
             };
-            writer.write_record([
-                track.artist.clone(),
-                "-".to_string(),
-                track.title.clone(),
-                duration,
-                start_time,
-                end_time,
-            ])?;
+            let start_time = track
+                .start_time
+                .map_or(String::new(), |t| options.time_format.format(t));
+            let end_time = track.end_time.map_or(String::new(), |t| options.time_format.format(t));
+            let number = options
+                .has_number_column()
+                .then(|| self.track_number(index, options).to_string());
+            let metadata = track.metadata();
+            let dj = self.has_djs().then(|| track.dj.clone().unwrap_or_default());
+            let plays = self.has_repeats().then(|| track.plays.to_string());
+            let mashups = self.has_mashups().then(|| track.mashups_text());
+            let notes = self.has_notes().then(|| track.notes.clone().unwrap_or_default());
+            writer.write_record(
+                number
+                    .into_iter()
+                    .chain(elapsed.as_ref().map(|e| e[index].clone()))
+                    .chain([
+                        track.artist.clone(),
+                        "-".to_string(),
+                        track.title.clone(),
+                        duration,
+                        start_time,
+                        end_time,
+                    ])
+                    .chain(
+                        metadata_columns
+                            .iter()
+                            .map(|&column| metadata[column].clone().unwrap_or_default()),
+                    )
+                    .chain(
+                        extra_columns
+                            .iter()
+                            .map(|column| track.extras.get(column).cloned().unwrap_or_default()),
+                    )
+                    .chain(dj)
+                    .chain(plays)
+                    .chain(mashups)
+                    .chain(notes),
+            )?;
         }
-        // Add total TimeDelta
-        if let Some(t) = self.total_duration {
-            writer.write_record([
-                String::new(),
-                String::new(),
-                String::new(),
-                utils::formatted_duration(t),
-                String::new(),
-                String::new(),
-            ])?;
+        // Add totals row
+        if let Some((summary, duration)) = self.file_footer(options) {
+            let number = options.has_number_column().then(String::new);
+            let dj = self.has_djs().then(String::new);
+            let plays = self.has_repeats().then(String::new);
+            let mashups = self.has_mashups().then(String::new);
+            let notes = self.has_notes().then(String::new);
+            writer.write_record(
+                number
+                    .into_iter()
+                    .chain(elapsed.as_ref().map(|_| String::new()))
+                    .chain([
+                        summary,
+                        String::new(),
+                        String::new(),
+                        duration,
+                        String::new(),
+                        String::new(),
+                    ])
+                    .chain(metadata_columns.iter().map(|_| String::new()))
+                    .chain(extra_columns.iter().map(|_| String::new()))
+                    .chain(dj)
+                    .chain(plays)
+                    .chain(mashups)
+                    .chain(notes),
+            )?;
         }
-        writer.flush()?;
-        Ok(())
+        writer
+            .into_inner()
+            .map_err(|e| PlaylistError::io("Failed to write CSV data", e.into_error()))
     }
 
-    /// Write tracks to Excel file
-    fn write_excel_file(&self, filepath: &Path) -> Result<()> {
+    /// Get tracks as Excel file contents
+    #[cfg(feature = "xlsx")]
+    pub(crate) fn excel_bytes(&self, options: &FormatOptions) -> Result<Vec<u8>> {
         let mut workbook = Workbook::new();
+        // Use a fixed creation time so the same playlist always produces identical files
+        let creation_time = self.date.map_or(DateTime::UNIX_EPOCH, |date| {
+            DateTime::from_naive_utc_and_offset(date, Utc)
+        });
+        workbook.set_properties(&DocProperties::new().set_creation_datetime(&creation_time));
         let sheet = workbook.add_worksheet().set_name(self.name.clone())?;
+        self.write_excel_sheet(sheet, options)?;
+
+        // Add summary statistics on a separate sheet
+        if options.stats_sheet && !self.tracks.is_empty() {
+            let stats = self.statistics();
+            let name = Self::excel_sheet_name("Stats", std::slice::from_ref(&self.name));
+            let stats_sheet = workbook.add_worksheet().set_name(name)?;
+            let label_format = Format::new().set_bold();
+            for (i, (label, value)) in stats.summary().iter().enumerate() {
+                stats_sheet.write_string_with_format(i as RowNum, 0, label, &label_format)?;
+                stats_sheet.write_string(i as RowNum, 1, value)?;
+            }
+            stats_sheet.autofit();
+        }
 
+        Ok(workbook.save_to_buffer()?)
+    }
+
+    /// Write the tracks and the totals row to an Excel worksheet.
+    #[cfg(feature = "xlsx")]
+    fn write_excel_sheet(&self, sheet: &mut Worksheet, options: &FormatOptions) -> Result<()> {
         let header_format = Format::new()
             .set_bold()
             .set_border_bottom(FormatBorder::Medium)
             .set_background_color("C6E0B4");
 
+        // Optional track number and elapsed columns shift the other columns to the right
+        let elapsed = self.elapsed_column(options);
+        let elapsed_column: u16 = if options.has_number_column() { 1 } else { 0 };
+        let offset: u16 = elapsed_column + if elapsed.is_some() { 1 } else { 0 };
+
         // Write header
-        sheet.write_string_with_format(0, 0, "Artist", &header_format)?;
-        sheet.write_string_with_format(0, 1, "", &header_format)?;
-        sheet.write_string_with_format(0, 2, "Title", &header_format)?;
-        sheet.write_string_with_format(0, 3, "Playtime", &header_format)?;
-        sheet.write_string_with_format(0, 4, "Start Time", &header_format)?;
-        sheet.write_string_with_format(0, 5, "End Time", &header_format)?;
+        if options.has_number_column() {
+            sheet.write_string_with_format(0, 0, "#", &header_format)?;
+        }
+        if elapsed.is_some() {
+            sheet.write_string_with_format(0, elapsed_column, "Elapsed", &header_format)?;
+        }
+        sheet.write_string_with_format(0, offset, "Artist", &header_format)?;
+        sheet.write_string_with_format(0, offset + 1, "", &header_format)?;
+        sheet.write_string_with_format(0, offset + 2, "Title", &header_format)?;
+        sheet.write_string_with_format(0, offset + 3, "Playtime", &header_format)?;
+        sheet.write_string_with_format(0, offset + 4, "Start Time", &header_format)?;
+        sheet.write_string_with_format(0, offset + 5, "End Time", &header_format)?;
+        let metadata_columns = self.metadata_columns();
+        for (i, &column) in metadata_columns.iter().enumerate() {
+            sheet.write_string_with_format(0, offset + 6 + i as u16, METADATA_FIELDS[column], &header_format)?;
+        }
+        let extra_columns = self.output_extra_columns(options);
+        let extras_start = offset + 6 + metadata_columns.len() as u16;
+        for (i, column) in extra_columns.iter().enumerate() {
+            sheet.write_string_with_format(0, extras_start + i as u16, column, &header_format)?;
+        }
+        let dj_column = extras_start + extra_columns.len() as u16;
+        let has_djs = self.has_djs();
+        if has_djs {
+            sheet.write_string_with_format(0, dj_column, "DJ", &header_format)?;
+        }
+        let plays_column = dj_column + u16::from(has_djs);
+        let has_repeats = self.has_repeats();
+        if has_repeats {
+            sheet.write_string_with_format(0, plays_column, "Plays", &header_format)?;
+        }
+        let mashup_column = plays_column + u16::from(has_repeats);
+        let has_mashups = self.has_mashups();
+        if has_mashups {
+            sheet.write_string_with_format(0, mashup_column, "Mashup", &header_format)?;
+        }
+        let notes_column = mashup_column + u16::from(has_mashups);
+        let has_notes = self.has_notes();
+        if has_notes {
+            sheet.write_string_with_format(0, notes_column, "Notes", &header_format)?;
+        }
 
         let duration_format = Format::new().set_align(FormatAlign::Right).set_num_format("h:mm:ss");
 
         // Write tracks
         for (i, track) in self.tracks.iter().enumerate() {
             let row = (i + 1) as RowNum;
-            let duration = track.play_time.map_or(String::new(), utils::formatted_duration);
+            let duration = track.play_time.map_or(String::new(), |d| options.formatted_playtime(d));
             let start_time = track
                 .start_time
-                .map_or(String::new(), |t| t.format("%Y.%m.%d %H:%M:%S").to_string());
-            let end_time = track
-                .end_time
-                .map_or(String::new(), |t| t.format("%Y.%m.%d %H:%M:%S").to_string());
+                .map_or(String::new(), |t| options.time_format.format(t));
+            let end_time = track.end_time.map_or(String::new(), |t| options.time_format.format(t));
 
-            sheet.write_string(row, 0, &track.artist)?;
-            sheet.write_string(row, 1, "-")?;
-            sheet.write_string(row, 2, &track.title)?;
-            sheet.write_string_with_format(row, 3, &duration, &duration_format)?;
-            sheet.write_string(row, 4, &start_time)?;
-            sheet.write_string(row, 5, &end_time)?;
+            if options.has_number_column() {
+                sheet.write_number(row, 0, self.track_number(i, options) as f64)?;
+            }
+            if let Some(elapsed) = &elapsed {
+                sheet.write_string_with_format(row, elapsed_column, &elapsed[i], &duration_format)?;
+            }
+            sheet.write_string(row, offset, &track.artist)?;
+            sheet.write_string(row, offset + 1, "-")?;
+            sheet.write_string(row, offset + 2, &track.title)?;
+            sheet.write_string_with_format(row, offset + 3, &duration, &duration_format)?;
+            sheet.write_string(row, offset + 4, &start_time)?;
+            sheet.write_string(row, offset + 5, &end_time)?;
+            let metadata = track.metadata();
+            for (i, &column) in metadata_columns.iter().enumerate() {
+                let col = offset + 6 + i as u16;
+                match (METADATA_FIELDS[column], track) {
+                    ("BPM", Track { bpm: Some(bpm), .. }) => sheet.write_number(row, col, *bpm)?,
+                    ("Year", Track { year: Some(year), .. }) => sheet.write_number(row, col, *year)?,
+                    (
+                        "Rating",
+                        Track {
+                            rating: Some(rating), ..
+                        },
+                    ) => sheet.write_number(row, col, *rating)?,
+                    _ => sheet.write_string(row, col, metadata[column].as_deref().unwrap_or_default())?,
+                };
+            }
+            for (i, column) in extra_columns.iter().enumerate() {
+                if let Some(value) = track.extras.get(column) {
+                    sheet.write_string(row, extras_start + i as u16, value)?;
+                }
+            }
+            if let Some(dj) = track.dj.as_ref().filter(|_| has_djs) {
+                sheet.write_string(row, dj_column, dj)?;
+            }
+            if has_repeats {
+                sheet.write_number(row, plays_column, track.plays as f64)?;
+            }
+            if has_mashups && !track.mashups.is_empty() {
+                sheet.write_string(row, mashup_column, track.mashups_text())?;
+            }
+            if let Some(notes) = track.notes.as_ref().filter(|_| has_notes) {
+                sheet.write_string(row, notes_column, notes)?;
+            }
         }
 
-        // Add total TimeDelta at the end
-        if let Some(t) = self.total_duration {
+        // Add totals row at the end
+        if let Some((summary, duration)) = self.file_footer(options) {
             let total_row = (self.tracks.len() + 1) as RowNum;
-            let formatted_duration = utils::formatted_duration(t);
-            sheet.write_string_with_format(total_row, 3, &formatted_duration, &duration_format)?;
+            if !summary.is_empty() {
+                sheet.write_string(total_row, offset, &summary)?;
+            }
+            if !duration.is_empty() {
+                sheet.write_string_with_format(total_row, offset + 3, &duration, &duration_format)?;
+            }
         }
 
         sheet.autofit();
+        Ok(())
+    }
+
+    /// Append the playlist as a new worksheet to an Excel archive, or create the archive if it does not exist yet.
+    ///
+    /// Existing workbooks can't be edited in place, so the archive is rewritten
+    /// with the cell values of its current sheets. Their cell formatting is not kept.
+    #[cfg(all(feature = "fs", feature = "xlsx"))]
+    fn append_to_excel(&self, path: &Path, options: &FormatOptions) -> Result<()> {
+        let mut workbook = Workbook::new();
+        let mut sheet_names: Vec<String> = Vec::new();
+        if path.is_file() {
+            let mut archive: Xlsx<_> = calamine::open_workbook(path).map_err(|e| {
+                PlaylistError::InvalidData(format!("Failed to read Excel archive '{}': {e}", path.display()))
+            })?;
+            for (name, range) in archive.worksheets() {
+                let sheet = workbook.add_worksheet().set_name(&name)?;
+                Self::copy_excel_cells(sheet, &range)?;
+                sheet_names.push(name);
+            }
+            tracing::info!("Appending to: {}", path.display());
+        } else {
+            tracing::info!("Creating archive: {}", path.display());
+        }
+
+        let sheet = workbook
+            .add_worksheet()
+            .set_name(Self::excel_sheet_name(&self.name, &sheet_names))?;
+        self.write_excel_sheet(sheet, options)?;
+        let bytes = workbook.save_to_buffer()?;
+        std::fs::write(path, bytes)
+            .map_err(|e| PlaylistError::io(format!("Failed to write file: '{}'", path.display()), e))
+    }
 
-        workbook.save(filepath)?;
+    /// Copy cell values read from an existing Excel sheet.
+    #[cfg(all(feature = "fs", feature = "xlsx"))]
+    fn copy_excel_cells(sheet: &mut Worksheet, range: &calamine::Range<Data>) -> Result<()> {
+        let Some((start_row, start_column)) = range.start() else {
+            return Ok(());
+        };
+        let date_format = Format::new().set_num_format("yyyy-mm-dd hh:mm:ss");
+        let duration_format = Format::new().set_align(FormatAlign::Right).set_num_format("[h]:mm:ss");
+        for (row, column, value) in range.used_cells() {
+            let row = start_row + row as RowNum;
+            let column = (start_column as usize + column) as ColNum;
+            match value {
+                Data::String(text) | Data::DateTimeIso(text) | Data::DurationIso(text) => {
+                    sheet.write_string(row, column, text)?;
+                }
+                Data::Float(number) => {
+                    sheet.write_number(row, column, *number)?;
+                }
+                Data::Int(number) => {
+                    sheet.write_number(row, column, *number as f64)?;
+                }
+                Data::Bool(value) => {
+                    sheet.write_boolean(row, column, *value)?;
+                }
+                Data::DateTime(date) => {
+                    let format = if date.is_duration() {
+                        &duration_format
+                    } else {
+                        &date_format
+                    };
+                    sheet.write_number_with_format(row, column, date.as_f64(), format)?;
+                }
+                Data::Error(_) | Data::Empty => {}
+            }
+        }
         Ok(())
     }
 
-    /// Write tracks to TXT file
-    fn write_txt_file(&self, filepath: &Path) -> Result<()> {
-        let mut file = File::create(filepath)?;
+    /// Get a valid worksheet name for the playlist that is not already used in the workbook.
+    #[cfg(feature = "xlsx")]
+    fn excel_sheet_name(name: &str, existing: &[String]) -> String {
+        // Excel limits sheet names to 31 characters and does not allow some special characters
+        let base: String = name
+            .chars()
+            .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+            .take(31)
+            .collect();
+        let base = base.trim_matches([' ', '\'']);
+        let base = if base.is_empty() { "Playlist" } else { base };
+        let is_used = |candidate: &str| existing.iter().any(|n| n.eq_ignore_ascii_case(candidate));
+        let mut candidate = base.to_string();
+        let mut number = 2;
+        while is_used(&candidate) {
+            let suffix = format!(" ({number})");
+            let prefix: String = base.chars().take(31 - suffix.chars().count()).collect();
+            candidate = format!("{prefix}{suffix}");
+            number += 1;
+        }
+        candidate
+    }
+
+    /// Get tracks as TXT file contents
+    pub(crate) fn txt_bytes(&self, options: &FormatOptions) -> Vec<u8> {
+        let elapsed = self.elapsed_column(options);
+        let mut text = String::new();
+        for (index, track) in self.tracks.iter().enumerate() {
+            match &elapsed {
+                Some(elapsed) => text.push_str(&format!("{} {}\n", elapsed[index], track.display_name())),
+                None => text.push_str(&format!("{}\n", track.display_name())),
+            }
+            for mashup in &track.mashups {
+                text.push_str(&format!("  w/ {}\n", mashup.name()));
+            }
+        }
+        text.into_bytes()
+    }
+
+    /// Get playlist as versioned JSON file contents.
+    pub(crate) fn json_bytes(&self, options: &FormatOptions) -> Result<Vec<u8>> {
+        let mut data = serde_json::to_vec_pretty(&JsonPlaylist::from_playlist(self, options))?;
+        data.push(b'\n');
+        Ok(data)
+    }
+
+    /// Get tracks as extended M3U file contents.
+    ///
+    /// Each entry points to the file location read from the input.
+    /// Tracks without a location are left out, and a playlist without any locations is an error.
+    pub(crate) fn m3u_bytes(&self) -> Result<Vec<u8>> {
+        if !self.tracks.is_empty() && self.tracks.iter().all(|track| track.location().is_none()) {
+            return Err(PlaylistError::UnsupportedFormat(format!(
+                "M3U output requires track file locations, which are missing from: '{}'",
+                self.file.display()
+            )));
+        }
+        let mut text = format!("#EXTM3U\n#PLAYLIST:{}\n", self.name);
         for track in &self.tracks {
-            file.write_all(format!("{}\n", track).as_ref())?;
+            let Some(location) = track.location() else {
+                tracing::warn!("Skipping track without a file location: {track}");
+                continue;
+            };
+            let seconds = track.play_time.map_or(-1, |d| d.num_seconds());
+            text.push_str(&format!("#EXTINF:{seconds},{track}\n"));
+            if let Some(genre) = &track.genre {
+                text.push_str(&format!("#EXTGENRE:{genre}\n"));
+            }
+            text.push_str(&format!("{location}\n"));
         }
-        Ok(())
+        Ok(text.into_bytes())
     }
 
-    /// Read a .txt playlist file.
-    /// This can be either a Rekordbox or Serato exported playlist.
-    fn read_txt(path: &Path) -> Result<Playlist> {
-        let file = File::open(path)?;
+    /// Read the header of a .txt playlist, leaving the rows to be read as they are consumed.
+    fn read_txt_input(path: &Path, source: impl Read + 'static, options: &ParseOptions) -> Result<PlaylistInput> {
+        let file_path = path.to_path_buf();
         // Rekordbox encodes txt files in UTF-16 :(
-        let mut decoder = DecodeReaderBytes::new(file);
-        let mut dest = String::new();
-        decoder.read_to_string(&mut dest)?;
+        let mut lines = BufReader::new(DecodeReaderBytes::new(source))
+            .lines()
+            .map(move |line| {
+                line.map_err(|e| PlaylistError::io(format!("Failed to read file: '{}'", file_path.display()), e))
+            })
+            .peekable();
 
-        let lines = Self::read_txt_lines(&mut dest);
-        log::trace!("Lines ({}):", lines.len());
-        log::trace!("{:#?}", lines);
+        let header_line = lines.next().transpose()?.unwrap_or_default();
+        // Check if this is a Serato txt: Serato has a divider on the second line
+        let serato_columns = match lines.peek() {
+            Some(Ok(line)) if serato::is_divider_line(Self::first_txt_item(line)) => {
+                Some(SeratoTxtColumns::from_header(Self::first_txt_item(&header_line)))
+            }
+            _ => None,
+        };
+        let headers: Vec<String> = match &serato_columns {
+            Some(columns) => columns.names().to_vec(),
+            None => Self::split_txt_line(&header_line)
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+        };
 
         // Map each header name to the column index they correspond to in the data, for example:
         // {"#": 0, "Artist": 1, "Track Title": 2}
-        let header_map: BTreeMap<String, usize> = {
-            let headers = &lines[0];
-            headers
-                .iter()
-                .enumerate()
-                .map(|(index, value)| (value.to_string(), index))
-                .collect()
-        };
-        log::trace!("txt headers ({}): {:?}", header_map.keys().len(), header_map.keys());
+        let header_map: BTreeMap<String, usize> = headers
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (value.to_string(), index))
+            .collect();
+        tracing::trace!("txt headers ({}): {:?}", header_map.keys().len(), header_map.keys());
 
-        // Map track data to a dictionary (header key: track value)
-        let data: Vec<BTreeMap<String, String>> = {
-            lines[1..]
-                .iter()
-                .map(|line| {
-                    let mut items: BTreeMap<String, String> = BTreeMap::new();
-                    // header map contains the index of the value corresponding to the key
-                    for (key, index) in &header_map {
-                        let value = &line[*index];
-                        items.insert(key.to_string(), value.to_string());
+        // Rows share the header map and look up their values from it by index
+        let strict = options.strict;
+        let column_count = headers.len();
+        let columns = Arc::new(header_map.clone());
+        let rows = lines.enumerate().filter_map(move |(index, line)| {
+            // Line numbers start from one and the first line is the header
+            let number = index + 2;
+            let line = match line {
+                Ok(line) => line,
+                Err(error) => return Some(Err(error)),
+            };
+            let values = match &serato_columns {
+                // This is a Serato txt, need to do some extra parsing here...
+                Some(columns) => {
+                    let line = Self::first_txt_item(&line);
+                    if serato::is_divider_line(line) {
+                        return None;
                     }
-                    items
-                })
-                .collect()
-        };
-
-        log::trace!("Rows ({}):", data.len());
-        for row in &data {
-            log::trace!("{:#?}", row);
-        }
+                    StringRecord::from(columns.split(line))
+                }
+                None => Self::split_txt_line(&line),
+            };
+            if values.len() < column_count {
+                let message = format!("Line {number}: expected {column_count} fields, found {}", values.len());
+                if strict {
+                    return Some(Err(PlaylistError::InvalidData(message)));
+                }
+                tracing::warn!("{message}");
+            }
+            let row = Row::new(Arc::clone(&columns), values);
+            tracing::trace!("{row:?}");
+            Some(Ok(row))
+        });
 
-        // Drop the file extension from file name
-        let name = path
-            .with_extension("")
-            .file_name()
-            .ok_or_else(|| anyhow!("File name not found after dropping extension"))?
-            .to_str()
-            .ok_or_else(|| anyhow!("File name contains invalid Unicode"))?
-            .to_string();
-
-        // Check playlist type
-        if header_map.contains_key("name") {
-            log::debug!("Detected Serato TXT");
-            serato::read_serato_txt(path, name, &header_map, &data)
-        } else if header_map.contains_key("#") {
-            // Rekordbox txt: first line contains headers, line starts with '#'.
-            log::debug!("Detected Rekordbox TXT");
-            rekordbox::read_rekordbox_txt(path, name, &header_map, &data)
-        } else {
-            anyhow::bail!("Input file does not seem to be a valid Serato or Rekordbox txt playlist");
-        }
+        Ok(PlaylistInput {
+            path: path.to_path_buf(),
+            name: Self::file_stem_name(path)?,
+            file_format: FileFormat::Txt,
+            headers: header_map,
+            rows: Box::new(rows),
+        })
     }
 
-    /// Read a .csv playlist file.
-    /// This can be either a Serato playlist or an already formatted file.
-    fn read_csv(path: &Path) -> Result<Playlist> {
-        let mut reader =
-            Reader::from_path(path).with_context(|| format!("Failed to open CSV file: '{}'", path.display()))?;
+    /// Read the header of a .csv playlist, leaving the rows to be read as they are consumed.
+    fn read_csv_input(path: &Path, source: impl Read + 'static, options: &ParseOptions) -> Result<PlaylistInput> {
+        let mut reader = Reader::from_reader(source);
 
         // map each header name to the column index they correspond to in the data, for example:
         // {"name": 0, "artist": 1, "start time": 2}
@@ -421,63 +1980,87 @@ impl Playlist {
                 .collect()
         };
 
-        log::trace!("CSV headers ({}): {:?}", header_map.keys().len(), header_map.keys());
+        tracing::trace!("CSV headers ({}): {:?}", header_map.keys().len(), header_map.keys());
 
-        let data = Self::map_track_data(&mut reader, &header_map);
-        log::trace!("Rows ({}):", data.len());
-        for row in &data {
-            log::trace!("{:?}", row);
-        }
+        Ok(PlaylistInput {
+            path: path.to_path_buf(),
+            name: Self::file_stem_name(path)?,
+            file_format: FileFormat::Csv,
+            rows: Self::csv_rows(reader, Arc::new(header_map.clone()), options.strict),
+            headers: header_map,
+        })
+    }
 
-        // Check if this is an already-formatted CSV
-        let formatted_fields = ["Artist", "", "Title"];
-        if formatted_fields.into_iter().all(|field| header_map.contains_key(field)) {
-            formatted::read_formatted_csv(path, data)
-        } else {
-            // This should be a Serato CSV
-            let required_serato_fields = ["name", "artist"];
-            for field in required_serato_fields {
-                if !header_map.contains_key(field) {
-                    anyhow::bail!("Serato CSV missing required field: '{}'", field)
-                }
-            }
-            serato::read_serato_csv(path, data)
-        }
+    /// Get the file name without the extension.
+    fn file_stem_name(path: &Path) -> Result<String> {
+        Ok(path
+            .with_extension("")
+            .file_name()
+            .ok_or_else(|| PlaylistError::InvalidArgument("File name not found after dropping extension".to_string()))?
+            .to_str()
+            .ok_or_else(|| PlaylistError::InvalidArgument("File name contains invalid Unicode".to_string()))?
+            .to_string())
     }
 
-    /// Map track data to a dictionary (header key: track value).
-    fn map_track_data(
-        reader: &mut Reader<File>,
-        header_map: &BTreeMap<String, usize>,
-    ) -> Vec<BTreeMap<String, String>> {
-        reader
-            .records()
-            .filter_map(|s| s.ok())
-            .map(|record| {
-                let mut items: BTreeMap<String, String> = BTreeMap::new();
-                for (name, index) in header_map {
-                    let value = &record[*index];
-                    items.insert(name.to_string(), value.to_string());
+    /// Wrap each CSV record in a row that looks up values with the shared header map as it is read.
+    ///
+    /// Rows that can't be read, like rows with a different number of fields than the header,
+    /// are skipped with a warning, or returned as an error in strict mode.
+    fn csv_rows(reader: Reader<impl Read + 'static>, columns: Arc<BTreeMap<String, usize>>, strict: bool) -> Rows {
+        Box::new(reader.into_records().filter_map(move |record| {
+            let record = match record {
+                Ok(record) => record,
+                Err(error) => {
+                    let line = error.position().map_or(0, csv::Position::line);
+                    if strict {
+                        return Some(Err(PlaylistError::InvalidData(format!("Line {line}: {error}"))));
+                    }
+                    tracing::warn!("Skipping unreadable row on line {line}: {error}");
+                    return None;
                 }
-                items
-            })
-            .collect()
+            };
+            let row = Row::new(Arc::clone(&columns), record);
+            tracing::trace!("{row:?}");
+            Some(Ok(row))
+        }))
     }
 
-    /// Split txt content string to lines, and each line to separate items
-    fn read_txt_lines(text: &mut str) -> Vec<Vec<String>> {
-        // Convert to lines and split each line from tab. This handles Rekordbox data.
-        let initial_lines: Vec<Vec<String>> = text
-            .lines()
-            .map(|s| s.split('\t').map(|l| l.trim().to_string()).collect())
-            .collect();
+    /// Get the first tab-separated item of a txt line.
+    fn first_txt_item(line: &str) -> &str {
+        line.split('\t').next().unwrap_or_default().trim()
+    }
 
-        // Check if this is a Serato txt: Serato has a divider on the second line
-        if initial_lines[1][0].chars().all(|c| c == '-') {
-            // This is a Serato txt, need to do some extra parsing here...
-            serato::read_serato_txt_lines(initial_lines)
-        } else {
-            initial_lines
-        }
+    /// Split a txt line to items from tabs. This handles Rekordbox data.
+    fn split_txt_line(line: &str) -> StringRecord {
+        line.split('\t').map(str::trim).collect()
+    }
+}
+
+/// Get the track at the given index, counting from zero.
+///
+/// There is no mutable indexing, since changing a track would leave the totals out of date.
+impl Index<usize> for Playlist {
+    type Output = Track;
+
+    fn index(&self, index: usize) -> &Track {
+        &self.tracks[index]
+    }
+}
+
+impl IntoIterator for Playlist {
+    type Item = Track;
+    type IntoIter = std::vec::IntoIter<Track>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tracks.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Playlist {
+    type Item = &'a Track;
+    type IntoIter = std::slice::Iter<'a, Track>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.tracks.iter()
     }
 }
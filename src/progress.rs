@@ -0,0 +1,80 @@
+use std::io::IsTerminal;
+use std::path::Path;
+use std::time::Duration;
+
+use indicatif::{ProgressBar, ProgressStyle};
+
+use playlist_formatter::options::{ProgressCallback, ProgressEvent};
+
+/// Files larger than this show a spinner while parsing.
+const LARGE_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Progress output is only shown in an interactive terminal when not in quiet mode.
+pub fn is_enabled(quiet: bool) -> bool {
+    !quiet && std::io::stdout().is_terminal()
+}
+
+/// Create a progress bar for processing the given number of items.
+///
+/// Returns a hidden progress bar when progress output is disabled.
+pub fn bar(len: usize, quiet: bool) -> ProgressBar {
+    if !is_enabled(quiet) {
+        return ProgressBar::hidden();
+    }
+    let bar = ProgressBar::new(len as u64);
+    if let Ok(style) =
+        ProgressStyle::with_template("{spinner:.green} [{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {wide_msg}")
+    {
+        bar.set_style(style.progress_chars("=> "));
+    }
+    bar
+}
+
+/// Create a spinner for parsing the given file if it is large enough to take a noticeable time.
+///
+/// Returns a hidden spinner for small files or when progress output is disabled.
+pub fn parse_spinner(file: &Path, quiet: bool) -> ProgressBar {
+    let is_large = std::fs::metadata(file).is_ok_and(|metadata| metadata.len() > LARGE_FILE_BYTES);
+    if !is_large || !is_enabled(quiet) {
+        return ProgressBar::hidden();
+    }
+    let spinner = ProgressBar::new_spinner();
+    if let Ok(style) = ProgressStyle::with_template("{spinner:.green} {msg} [{elapsed}]") {
+        spinner.set_style(style);
+    }
+    spinner.set_message(format!(
+        "Parsing {}",
+        file.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    spinner.enable_steady_tick(Duration::from_millis(100));
+    spinner
+}
+
+/// Create a progress callback that shows the number of parsed rows in the parse spinner.
+///
+/// Returns `None` for a hidden spinner so parsing skips progress reporting.
+pub fn parse_progress(spinner: &ProgressBar, file: &Path) -> Option<ProgressCallback> {
+    if spinner.is_hidden() {
+        return None;
+    }
+    let spinner = spinner.clone();
+    let name = file.file_name().unwrap_or_default().to_string_lossy().to_string();
+    Some(ProgressCallback::new(move |event| {
+        if let ProgressEvent::RowsParsed { rows, .. } = event {
+            spinner.set_message(format!("Parsing {name}: {rows} rows"));
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quiet_hides_progress() {
+        assert!(!is_enabled(true));
+        assert!(bar(10, true).is_hidden());
+        assert!(parse_spinner(Path::new("missing.csv"), false).is_hidden());
+        assert!(parse_progress(&ProgressBar::hidden(), Path::new("missing.csv")).is_none());
+    }
+}
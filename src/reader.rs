@@ -0,0 +1,420 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::{Arc, LazyLock};
+
+use csv::StringRecord;
+
+use super::error::{PlaylistError, Result};
+use super::formatted::FormattedCsvReader;
+use super::options::{ParseOptions, ProgressCallback, ProgressEvent};
+use super::playlist::Playlist;
+use super::rekordbox::RekordboxTxtReader;
+use super::serato::{SeratoCsvReader, SeratoTxtReader};
+use super::types::{FileFormat, InputFormat};
+use super::utils;
+
+/// Readers for the playlist types supported out of the box.
+pub static DEFAULT_READERS: LazyLock<ReaderRegistry> = LazyLock::new(ReaderRegistry::default);
+
+/// Values of a data row, looked up by header name.
+///
+/// The column indices are shared between all rows of a file,
+/// so a row only owns the values of a single line.
+#[derive(Clone, Default)]
+pub struct Row {
+    columns: Arc<BTreeMap<String, usize>>,
+    values: StringRecord,
+}
+
+impl Row {
+    /// Create a row from the column index of each header name and the values of one line.
+    pub fn new(columns: Arc<BTreeMap<String, usize>>, values: StringRecord) -> Self {
+        Self { columns, values }
+    }
+
+    /// Get the value of a column.
+    ///
+    /// A known column without a value on this line, like on a short line, is empty.
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.columns
+            .get(column)
+            .map(|index| self.values.get(*index).unwrap_or_default())
+    }
+
+    /// Iterate over the column names and values in column name order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.columns
+            .iter()
+            .map(|(column, index)| (column.as_str(), self.values.get(*index).unwrap_or_default()))
+    }
+
+    /// Look up the values with different column names, for example after renaming the header columns.
+    #[must_use]
+    pub fn with_columns(self, columns: Arc<BTreeMap<String, usize>>) -> Self {
+        Self { columns, ..self }
+    }
+}
+
+/// Create a row from column name and value pairs.
+impl<K: Into<String>, V: AsRef<str>> FromIterator<(K, V)> for Row {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut columns = BTreeMap::new();
+        let mut values = StringRecord::new();
+        for (index, (column, value)) in iter.into_iter().enumerate() {
+            columns.insert(column.into(), index);
+            values.push_field(value.as_ref());
+        }
+        Self::new(Arc::new(columns), values)
+    }
+}
+
+impl fmt::Debug for Row {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+/// Data rows that are read from the input file one at a time as they are consumed.
+pub type Rows = Box<dyn Iterator<Item = Result<Row>>>;
+
+/// Number of rows between progress reports while parsing.
+const PROGRESS_ROW_INTERVAL: usize = 1000;
+
+/// Rows that report how many rows have been read to a progress callback.
+///
+/// Reports after every [`PROGRESS_ROW_INTERVAL`] rows and once more when the rows run out.
+pub(crate) struct ProgressRows {
+    rows: Rows,
+    file: PathBuf,
+    count: usize,
+    progress: ProgressCallback,
+    finished: bool,
+}
+
+impl ProgressRows {
+    pub(crate) fn new(rows: Rows, file: PathBuf, progress: ProgressCallback) -> Self {
+        Self {
+            rows,
+            file,
+            count: 0,
+            progress,
+            finished: false,
+        }
+    }
+
+    fn report(&self) {
+        self.progress.report(&ProgressEvent::RowsParsed {
+            file: self.file.clone(),
+            rows: self.count,
+        });
+    }
+}
+
+impl Iterator for ProgressRows {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Some(row) => {
+                self.count += 1;
+                if self.count.is_multiple_of(PROGRESS_ROW_INTERVAL) {
+                    self.report();
+                }
+                Some(row)
+            }
+            None => {
+                if !self.finished {
+                    self.finished = true;
+                    self.report();
+                }
+                None
+            }
+        }
+    }
+}
+
+/// Header and rows of a CSV or TXT input file before they are interpreted as a specific playlist type.
+pub struct PlaylistInput {
+    /// Path of the input file
+    pub path: PathBuf,
+    /// File name without the extension
+    pub name: String,
+    /// File type the rows were read from
+    pub file_format: FileFormat,
+    /// Column index for each header name, for example `{"#": 0, "Artist": 1, "Track Title": 2}`
+    pub headers: BTreeMap<String, usize>,
+    /// Data rows, read lazily so large files don't need to fit in memory
+    pub rows: Rows,
+}
+
+impl PlaylistInput {
+    /// Check if the input has a column with the given header name.
+    pub fn has_header(&self, name: &str) -> bool {
+        self.headers.contains_key(name)
+    }
+
+    /// Return a missing field error for the first required column that the input does not have.
+    ///
+    /// `kind` names the playlist type in the error message, for example "Serato CSV".
+    pub fn require_fields(&self, kind: &str, fields: &[&str]) -> Result<()> {
+        match fields.iter().find(|field| !self.has_header(field)) {
+            Some(field) => Err(utils::missing_field_error(kind, field, self.headers.keys())),
+            None => Ok(()),
+        }
+    }
+
+    /// Check the rows with `row_issues` while they are read.
+    ///
+    /// After the last row, an error listing every reported value with the line it is on is returned.
+    /// The first `skip` rows are not checked, for rows that contain playlist info instead of a track.
+    pub fn check_rows(&mut self, skip: usize, row_issues: fn(&Row) -> Vec<String>) {
+        let rows = std::mem::replace(&mut self.rows, Box::new(std::iter::empty()));
+        self.rows = Box::new(CheckedRows {
+            rows,
+            row_issues,
+            skip,
+            index: 0,
+            issues: Vec::new(),
+            path: self.path.clone(),
+        });
+    }
+}
+
+/// Rows that are checked for invalid values as they are read.
+struct CheckedRows {
+    rows: Rows,
+    row_issues: fn(&Row) -> Vec<String>,
+    skip: usize,
+    index: usize,
+    issues: Vec<String>,
+    path: PathBuf,
+}
+
+impl Iterator for CheckedRows {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match self.rows.next() {
+            Some(Ok(row)) => {
+                if self.index >= self.skip {
+                    // The first data row is on line 2 after the header
+                    let line = self.index + 2;
+                    self.issues.extend(
+                        (self.row_issues)(&row)
+                            .into_iter()
+                            .map(|issue| format!("Line {line}: {issue}")),
+                    );
+                }
+                self.index += 1;
+                Some(Ok(row))
+            }
+            Some(Err(error)) => Some(Err(error)),
+            None if self.issues.is_empty() => None,
+            None => Some(Err(PlaylistError::InvalidData(format!(
+                "Invalid values in '{}':\n{}",
+                self.path.display(),
+                std::mem::take(&mut self.issues).join("\n")
+            )))),
+        }
+    }
+}
+
+/// Parser for one playlist type, such as the exports of a specific DJ software.
+///
+/// Implement this to add support for a new playlist type and add it to a [`ReaderRegistry`].
+pub trait PlaylistReader: Send + Sync {
+    /// Name of the playlist type, for example "Serato CSV".
+    fn name(&self) -> &str;
+
+    /// Input format that selects this reader without detection.
+    fn input_format(&self) -> Option<InputFormat> {
+        None
+    }
+
+    /// Check if the input looks like this playlist type.
+    fn detect(&self, input: &PlaylistInput) -> bool;
+
+    /// Parse the input rows into a playlist.
+    fn read(&self, input: PlaylistInput, options: &ParseOptions) -> Result<Playlist>;
+}
+
+/// Playlist readers in the order they are tried.
+pub struct ReaderRegistry {
+    readers: Vec<Box<dyn PlaylistReader>>,
+}
+
+impl ReaderRegistry {
+    /// Create a registry without any readers.
+    pub fn empty() -> Self {
+        Self { readers: Vec::new() }
+    }
+
+    /// Add a reader that is tried before the readers already in the registry.
+    pub fn register(&mut self, reader: impl PlaylistReader + 'static) -> &mut Self {
+        self.readers.insert(0, Box::new(reader));
+        self
+    }
+
+    /// Get the names of the registered readers in the order they are tried.
+    pub fn names(&self) -> Vec<&str> {
+        self.readers.iter().map(|reader| reader.name()).collect()
+    }
+
+    /// Find the reader for the input.
+    ///
+    /// An explicit input format selects the reader directly, otherwise the first reader that detects the input is used.
+    pub fn find(&self, input: &PlaylistInput, input_format: Option<InputFormat>) -> Result<&dyn PlaylistReader> {
+        let reader = match input_format {
+            Some(format) => self
+                .readers
+                .iter()
+                .find(|reader| reader.input_format() == Some(format))
+                .ok_or_else(|| PlaylistError::UnsupportedFormat(format!("No reader registered for {format}")))?,
+            None => self.readers.iter().find(|reader| reader.detect(input)).ok_or_else(|| {
+                PlaylistError::UnsupportedFormat(format!(
+                    "Input file does not seem to be a valid playlist: '{}'. Supported types are: {}",
+                    input.path.display(),
+                    self.names().join(", ")
+                ))
+            })?,
+        };
+        tracing::debug!("Detected {}", reader.name());
+        Ok(reader.as_ref())
+    }
+
+    /// Parse the input with the matching reader.
+    pub fn read(&self, input: PlaylistInput, options: &ParseOptions) -> Result<Playlist> {
+        let reader = tracing::debug_span!("detect").in_scope(|| self.find(&input, options.input_format))?;
+        tracing::debug_span!("read", reader = reader.name()).in_scope(|| reader.read(input, options))
+    }
+}
+
+/// Built-in readers. Formatted CSV comes before Serato CSV, which accepts any CSV file.
+impl Default for ReaderRegistry {
+    fn default() -> Self {
+        Self {
+            readers: vec![
+                Box::new(FormattedCsvReader),
+                Box::new(SeratoCsvReader),
+                Box::new(SeratoTxtReader),
+                Box::new(RekordboxTxtReader),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    struct CommentReader;
+
+    impl PlaylistReader for CommentReader {
+        fn name(&self) -> &str {
+            "Comment CSV"
+        }
+
+        fn detect(&self, input: &PlaylistInput) -> bool {
+            input.has_header("comment")
+        }
+
+        fn read(&self, input: PlaylistInput, _options: &ParseOptions) -> Result<Playlist> {
+            Err(PlaylistError::InvalidData(input.name))
+        }
+    }
+
+    fn input(headers: &[&str]) -> PlaylistInput {
+        PlaylistInput {
+            path: Path::new("playlist.csv").to_path_buf(),
+            name: "playlist".to_string(),
+            file_format: FileFormat::Csv,
+            headers: headers
+                .iter()
+                .enumerate()
+                .map(|(index, header)| ((*header).to_string(), index))
+                .collect(),
+            rows: Box::new(std::iter::empty()),
+        }
+    }
+
+    #[test]
+    fn detect_builtin_readers() {
+        let registry = ReaderRegistry::default();
+        let formatted = input(&["Artist", "", "Title"]);
+        assert_eq!(registry.find(&formatted, None).unwrap().name(), "Formatted CSV");
+        let serato = input(&["name", "artist", "start time"]);
+        assert_eq!(registry.find(&serato, None).unwrap().name(), "Serato CSV");
+        assert_eq!(
+            registry.find(&serato, Some(InputFormat::FormattedCsv)).unwrap().name(),
+            "Formatted CSV"
+        );
+        let txt = PlaylistInput {
+            file_format: FileFormat::Txt,
+            ..input(&["Artist"])
+        };
+        assert!(matches!(
+            registry.find(&txt, None),
+            Err(PlaylistError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn check_rows_after_last_row() {
+        let rows: Vec<Result<Row>> = ["ok", "bad", "ok", "bad"]
+            .iter()
+            .map(|value| Ok(Row::from_iter([("value", value)])))
+            .collect();
+        let mut input = PlaylistInput {
+            rows: Box::new(rows.into_iter()),
+            ..input(&["value"])
+        };
+        input.check_rows(1, |row| match row.get("value") {
+            Some("bad") => vec!["bad value".to_string()],
+            _ => Vec::new(),
+        });
+        let results: Vec<Result<Row>> = input.rows.collect();
+        assert_eq!(results.len(), 5);
+        assert!(results[..4].iter().all(Result::is_ok));
+        let error = results[4].as_ref().unwrap_err().to_string();
+        assert!(error.ends_with("Line 3: bad value\nLine 5: bad value"), "{error}");
+    }
+
+    #[test]
+    fn registered_reader_is_tried_first() {
+        let mut registry = ReaderRegistry::default();
+        registry.register(CommentReader);
+        assert_eq!(registry.names()[0], "Comment CSV");
+        let comment = input(&["name", "artist", "comment"]);
+        assert!(matches!(
+            registry.read(comment, &ParseOptions::default()),
+            Err(PlaylistError::InvalidData(name)) if name == "playlist"
+        ));
+        assert!(ReaderRegistry::empty().find(&input(&["Artist"]), None).is_err());
+    }
+
+    #[test]
+    fn row_values_by_column() {
+        let columns = Arc::new(BTreeMap::from([
+            ("artist".to_string(), 1),
+            ("name".to_string(), 0),
+            ("notes".to_string(), 2),
+        ]));
+        let row = Row::new(columns, StringRecord::from(vec!["Title", "Artist"]));
+        assert_eq!(row.get("artist"), Some("Artist"));
+        assert_eq!(row.get("notes"), Some(""));
+        assert_eq!(row.get("bpm"), None);
+        assert_eq!(row.get("name"), Some("Title"));
+        assert_eq!(
+            row.iter().collect::<Vec<_>>(),
+            vec![("artist", "Artist"), ("name", "Title"), ("notes", "")]
+        );
+
+        let renamed = Arc::new(BTreeMap::from([("Title".to_string(), 0)]));
+        let row = row.with_columns(renamed);
+        assert_eq!(row.get("Title"), Some("Title"));
+        assert_eq!(row.get("name"), None);
+    }
+}
@@ -1,44 +1,89 @@
 use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use super::error::Result;
+use super::options::ParseOptions;
 use super::playlist::Playlist;
-use super::track::Track;
-use super::types::{FileFormat, PlaylistType};
+use super::reader::{PlaylistInput, PlaylistReader, Rows};
+use super::track::{Track, METADATA_FIELDS};
+use super::types::{FileFormat, InputFormat, PlaylistType};
 use super::utils;
 
+/// Reader for Rekordbox TXT exports, which start with a `#` column.
+pub struct RekordboxTxtReader;
+
+impl PlaylistReader for RekordboxTxtReader {
+    fn name(&self) -> &str {
+        "Rekordbox TXT"
+    }
+
+    fn input_format(&self) -> Option<InputFormat> {
+        Some(InputFormat::RekordboxTxt)
+    }
+
+    fn detect(&self, input: &PlaylistInput) -> bool {
+        input.file_format == FileFormat::Txt && input.has_header("#")
+    }
+
+    fn read(&self, input: PlaylistInput, options: &ParseOptions) -> Result<Playlist> {
+        read_rekordbox_txt(&input.path, input.name, &input.headers, input.rows, options)
+    }
+}
+
 /// Read data from a Rekordbox txt playlist.
 pub fn read_rekordbox_txt(
     path: &Path,
     name: String,
     header: &BTreeMap<String, usize>,
-    data: &[BTreeMap<String, String>],
-) -> anyhow::Result<Playlist> {
+    rows: Rows,
+    options: &ParseOptions,
+) -> Result<Playlist> {
     let required_fields = ["Artist", "Track Title"];
     for field in required_fields {
         if !header.contains_key(field) {
-            anyhow::bail!("Rekordbox TXT missing required field: '{}'", field)
+            return Err(utils::missing_field_error("Rekordbox TXT", field, header.keys()));
         }
     }
 
     let date = utils::extract_datetime_from_name(&name);
 
-    // Rekordbox does not have any start or play time info :(
-    let mut tracks: Vec<Track> = {
-        data.iter()
-            .map(|row| {
-                Track::new(
-                    row.get(required_fields[0]).unwrap().to_string(),
-                    row.get(required_fields[1]).unwrap().to_string(),
-                )
-            })
-            .collect()
-    };
-
-    // Remove consecutive duplicates
-    tracks.dedup();
+    let tracks: Vec<Track> = rows
+        .map(|row| {
+            let row = row?;
+            let mut track = Track::builder()
+                .artist(row.get(required_fields[0]).unwrap_or_default())
+                .title(row.get(required_fields[1]).unwrap_or_default())
+                .number(row.get("#").and_then(|number| number.trim().parse().ok()))
+                .build();
+            for field in METADATA_FIELDS {
+                if let Some(value) = row.get(field) {
+                    track.set_metadata(field, value);
+                }
+            }
+            // Rekordbox calls the track length "Time"
+            if let Some(value) = row.get("Time") {
+                track.set_metadata("Length", value);
+            }
+            track.add_extra_columns(&row, &["#", "Artist", "Track Title", "Time"]);
+            Ok(track)
+        })
+        .collect::<Result<_>>()?;
+
+    // Rekordbox does not have any start time info, so the track length is used as the play time.
+    // It is set after removing duplicates so the lengths of duplicate entries are not added together.
+    let tracks: Vec<Track> = utils::dedupe_tracks(tracks, options.dedupe)
+        .into_iter()
+        .map(|mut track| {
+            track.play_time = track.length;
+            track
+        })
+        .filter(|track| utils::is_long_enough(track, options.min_playtime))
+        .collect();
 
     let max_artist_length: usize = tracks.iter().map(|t| t.artist_length()).max().unwrap_or(0);
     let max_title_length: usize = tracks.iter().map(|t| t.title_length()).max().unwrap_or(0);
+    let max_playtime_length: usize = utils::get_max_playtime_length(&tracks);
+    let total_duration = utils::get_total_playtime(&tracks);
 
     Ok(Playlist {
         date,
@@ -49,7 +94,8 @@ pub fn read_rekordbox_txt(
         tracks,
         max_artist_length,
         max_title_length,
-        max_playtime_length: 0,
-        total_duration: None,
+        max_playtime_length,
+        timezone: None,
+        total_duration,
     })
 }
@@ -0,0 +1,103 @@
+use std::io::ErrorKind;
+use std::path::Path;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::cli::ErrorFormat;
+
+/// Structured description of a failure for machine-readable error output.
+#[derive(Debug, Serialize)]
+pub struct ErrorReport {
+    /// Stable error category
+    pub code: &'static str,
+    /// Top-level error message
+    pub message: String,
+    /// Underlying causes from outermost to innermost
+    pub causes: Vec<String>,
+    /// Offending file if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Offending line in the file if known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u64>,
+}
+
+impl ErrorReport {
+    /// Build report from an error and the input file that was being processed.
+    pub fn from_error(error: &anyhow::Error, file: Option<&Path>) -> Self {
+        let mut code = "error";
+        let mut line = None;
+        for cause in error.chain() {
+            if let Some(io_error) = cause.downcast_ref::<std::io::Error>() {
+                code = match io_error.kind() {
+                    ErrorKind::NotFound => "file_not_found",
+                    ErrorKind::PermissionDenied => "permission_denied",
+                    _ => "io_error",
+                };
+                break;
+            }
+            if let Some(csv_error) = cause.downcast_ref::<csv::Error>() {
+                code = "csv_error";
+                line = csv_error.position().map(csv::Position::line);
+                break;
+            }
+            if cause.is::<toml::de::Error>() {
+                code = "config_error";
+                break;
+            }
+            if let Some(json_error) = cause.downcast_ref::<serde_json::Error>() {
+                code = "json_error";
+                line = Some(json_error.line() as u64);
+                break;
+            }
+        }
+        Self {
+            code,
+            message: error.to_string(),
+            causes: error.chain().skip(1).map(ToString::to_string).collect(),
+            file: file.map(|path| path.display().to_string()),
+            line,
+        }
+    }
+}
+
+/// Print error to stderr in the requested format.
+pub fn print_error(error: &anyhow::Error, format: ErrorFormat, file: Option<&Path>) {
+    match format {
+        ErrorFormat::Text => eprintln!("{}", format!("Error: {error:?}").red()),
+        ErrorFormat::Json => {
+            let report = ErrorReport::from_error(error, file);
+            match serde_json::to_string(&report) {
+                Ok(json) => eprintln!("{json}"),
+                Err(_) => eprintln!("Error: {error:?}"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use anyhow::Context;
+
+    #[test]
+    fn report_for_missing_file() {
+        let error = std::fs::read_to_string("/nonexistent/playlist.txt")
+            .context("Failed to read playlist")
+            .unwrap_err();
+        let report = ErrorReport::from_error(&error, Some(Path::new("playlist.txt")));
+        assert_eq!(report.code, "file_not_found");
+        assert_eq!(report.message, "Failed to read playlist");
+        assert_eq!(report.causes.len(), 1);
+        assert_eq!(report.file.as_deref(), Some("playlist.txt"));
+    }
+
+    #[test]
+    fn report_serializes_without_empty_fields() {
+        let error = anyhow::anyhow!("Empty input file");
+        let json = serde_json::to_string(&ErrorReport::from_error(&error, None)).unwrap();
+        assert_eq!(json, r#"{"code":"error","message":"Empty input file","causes":[]}"#);
+    }
+}
@@ -2,16 +2,76 @@ use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Timelike};
+use chrono_tz::Tz;
 
+use super::error::Result;
+use super::options::ParseOptions;
 use super::playlist::Playlist;
-use super::track::Track;
-use super::types::{FileFormat, PlaylistType};
-use super::{serato, utils};
+use super::reader::{PlaylistInput, PlaylistReader, Row, Rows};
+use super::track::{Track, METADATA_FIELDS};
+use super::types::{FileFormat, InputFormat, PlaylistType};
+use super::{timezone, utils};
+
+/// Format of the track start and end times, for example "20.03.15 EET".
+const SERATO_TIME_FORMAT: &str = "%H.%M.%S %Z";
+/// Format of the track playtime, for example "00:05:17".
+const SERATO_PLAYTIME_FORMAT: &str = "%H:%M:%S";
+
+/// Reader for Serato CSV exports, used for any CSV file that is not already formatted.
+pub struct SeratoCsvReader;
+
+impl PlaylistReader for SeratoCsvReader {
+    fn name(&self) -> &str {
+        "Serato CSV"
+    }
+
+    fn input_format(&self) -> Option<InputFormat> {
+        Some(InputFormat::SeratoCsv)
+    }
+
+    fn detect(&self, input: &PlaylistInput) -> bool {
+        input.file_format == FileFormat::Csv
+    }
+
+    fn read(&self, mut input: PlaylistInput, options: &ParseOptions) -> Result<Playlist> {
+        input.require_fields("Serato CSV", &["name", "artist"])?;
+        if options.strict {
+            // The first row has the playlist info
+            input.check_rows(1, serato_row_issues);
+        }
+        read_serato_csv(&input.path, input.rows, options)
+    }
+}
+
+/// Reader for Serato TXT exports.
+pub struct SeratoTxtReader;
+
+impl PlaylistReader for SeratoTxtReader {
+    fn name(&self) -> &str {
+        "Serato TXT"
+    }
+
+    fn input_format(&self) -> Option<InputFormat> {
+        Some(InputFormat::SeratoTxt)
+    }
+
+    fn detect(&self, input: &PlaylistInput) -> bool {
+        input.file_format == FileFormat::Txt && input.has_header("name")
+    }
+
+    fn read(&self, input: PlaylistInput, options: &ParseOptions) -> Result<Playlist> {
+        read_serato_txt(&input.path, input.name, &input.headers, input.rows, options)
+    }
+}
 
 /// Read a Serato CSV playlist file.
-pub fn read_serato_csv(path: &Path, data: Vec<BTreeMap<String, String>>) -> anyhow::Result<Playlist> {
-    let (playlist_name, playlist_date) = serato::parse_serato_playlist_info(&data[0]);
-    let tracks = serato::parse_serato_tracks_from_data(&data, playlist_date);
+pub fn read_serato_csv(path: &Path, rows: Rows, options: &ParseOptions) -> Result<Playlist> {
+    let SeratoRows {
+        name: playlist_name,
+        date: playlist_date,
+        timezone,
+        tracks,
+    } = parse_serato_rows(rows, options)?;
     let total_duration = utils::get_total_playtime(&tracks);
     let max_artist_length: usize = tracks.iter().map(|t| t.artist_length()).max().unwrap_or(0);
     let max_title_length: usize = tracks.iter().map(|t| t.title_length()).max().unwrap_or(0);
@@ -27,6 +87,7 @@ pub fn read_serato_csv(path: &Path, data: Vec<BTreeMap<String, String>>) -> anyh
         max_artist_length,
         max_title_length,
         max_playtime_length,
+        timezone,
         total_duration,
     })
 }
@@ -36,23 +97,28 @@ pub fn read_serato_txt(
     path: &Path,
     name: String,
     header: &BTreeMap<String, usize>,
-    data: &[BTreeMap<String, String>],
-) -> anyhow::Result<Playlist> {
+    rows: Rows,
+    options: &ParseOptions,
+) -> Result<Playlist> {
     let required_fields = ["artist", "name"];
     for field in required_fields {
         if !header.contains_key(field) {
-            anyhow::bail!("Serato TXT missing required field: '{}'", field)
+            return Err(utils::missing_field_error("Serato TXT", field, header.keys()));
         }
     }
 
-    let (playlist_name, playlist_date) = serato::parse_serato_playlist_info(&data[0]);
+    let SeratoRows {
+        name: playlist_name,
+        date: playlist_date,
+        timezone,
+        tracks,
+    } = parse_serato_rows(rows, options)?;
     let name = if playlist_name.is_empty() { name } else { playlist_name };
     let date = if playlist_date.is_none() {
         utils::extract_datetime_from_name(&name)
     } else {
         playlist_date
     };
-    let tracks = serato::parse_serato_tracks_from_data(data, playlist_date);
     let total_duration = utils::get_total_playtime(&tracks);
     let max_artist_length: usize = tracks.iter().map(|t| t.artist_length()).max().unwrap_or(0);
     let max_title_length: usize = tracks.iter().map(|t| t.title_length()).max().unwrap_or(0);
@@ -68,14 +134,46 @@ pub fn read_serato_txt(
         max_artist_length,
         max_title_length,
         max_playtime_length,
+        timezone,
         total_duration,
     })
 }
 
+/// Playlist info and tracks parsed from the rows of a Serato playlist.
+struct SeratoRows {
+    name: String,
+    date: Option<NaiveDateTime>,
+    timezone: Option<Tz>,
+    tracks: Vec<Track>,
+}
+
+/// Parse the playlist info from the first row and the tracks from the remaining rows.
+fn parse_serato_rows(mut rows: Rows, options: &ParseOptions) -> Result<SeratoRows> {
+    let info = rows.next().transpose()?.unwrap_or_default();
+    let (name, date) = parse_serato_playlist_info(&info);
+    // Use the playlist start time if available, otherwise the first track with a start time
+    let mut timezone = parse_serato_timezone(&info);
+    let tracks = parse_serato_tracks(
+        rows.inspect(|row| {
+            if timezone.is_none() {
+                timezone = row.as_ref().ok().and_then(parse_serato_timezone);
+            }
+        }),
+        date,
+        options,
+    )?;
+    Ok(SeratoRows {
+        name,
+        date,
+        timezone,
+        tracks,
+    })
+}
+
 /// Parse first row data from a Serato playlist.
 ///
 /// This row should contain the playlist name and start datetime.
-pub fn parse_serato_playlist_info(data: &BTreeMap<String, String>) -> (String, Option<NaiveDateTime>) {
+pub fn parse_serato_playlist_info(data: &Row) -> (String, Option<NaiveDateTime>) {
     let playlist_name = match data.get("name") {
         None => String::new(),
         Some(n) => n.to_string(),
@@ -91,97 +189,152 @@ pub fn parse_serato_playlist_info(data: &BTreeMap<String, String>) -> (String, O
     (playlist_name, playlist_date)
 }
 
-/// Parse Serato track data from dictionary
-pub fn parse_serato_tracks_from_data(
-    data: &[BTreeMap<String, String>],
+/// Get the time zone from the timestamp abbreviation in the start time of a Serato row.
+pub fn parse_serato_timezone(row: &Row) -> Option<Tz> {
+    row.get("start time").and_then(timezone::zone_from_timestamp)
+}
+
+/// Parse Serato tracks from the rows after the playlist info row.
+pub fn parse_serato_tracks(
+    rows: impl Iterator<Item = Result<Row>>,
     playlist_date: Option<NaiveDateTime>,
-) -> Vec<Track> {
-    let start_date = playlist_date.unwrap_or_default().date();
-    let initial_tracks: Vec<Track> = data
-        .iter()
-        .skip(1)
-        .map(|row| parse_track_with_time_from_row(start_date, row))
-        .collect();
-
-    // Remove consecutive duplicates
-    let mut deduped_tracks: Vec<Track> = Vec::new();
-    for track in initial_tracks {
-        if let Some(last_track) = deduped_tracks.last_mut() {
-            if *last_track == track {
-                // Add playtime of duplicate track to previous
-                *last_track += track.play_time;
-                last_track.end_time = track.end_time;
-                continue;
+    options: &ParseOptions,
+) -> Result<Vec<Track>> {
+    let mut date = playlist_date.unwrap_or_default().date();
+    let mut previous_start = playlist_date;
+    let mut tracks: Vec<Track> = Vec::new();
+    for row in rows {
+        let row = row?;
+        let mut track = parse_track_with_time_from_row(date, &row);
+        // Serato only has the time of day for tracks,
+        // so a start time far before the previous one means the set continued past midnight.
+        // A slightly earlier start time is an out-of-order row that is left for validation to report.
+        // This repeats for each midnight in sessions spanning multiple days.
+        if let (Some(start), Some(previous)) = (track.start_time, previous_start) {
+            if start + TimeDelta::hours(12) < previous {
+                date = date.succ_opt().unwrap_or(date);
+                track = parse_track_with_time_from_row(date, &row);
             }
         }
-        deduped_tracks.push(track);
+        previous_start = track.start_time.or(previous_start);
+        if utils::is_long_enough(&track, options.min_playtime) {
+            tracks.push(track);
+        }
     }
+    Ok(utils::dedupe_tracks(tracks, options.dedupe))
+}
 
-    deduped_tracks
+/// Check if a Serato TXT line is a divider between the header and the tracks.
+pub fn is_divider_line(line: &str) -> bool {
+    line.chars().all(|c| c == '-')
 }
 
-pub fn read_serato_txt_lines(initial_lines: Vec<Vec<String>>) -> Vec<Vec<String>> {
-    let header_line: String = initial_lines[0][0].clone();
-    let column_names: Vec<String> = header_line
-        .replace("     ", "\t")
-        .split('\t')
-        .filter_map(|s| {
-            let v = s.trim();
-            if s.is_empty() {
-                None
-            } else {
-                Some(v.to_string())
-            }
-        })
-        .collect();
-    // Get starting location of each column item on a line
-    let mut column_start_indices: Vec<usize> = column_names
-        .iter()
-        .map(|field| header_line.find(field).unwrap())
-        .collect();
-    // Extract each column item from the line, starting from the end of the line
-    column_start_indices.reverse();
-    let mut serato_lines: Vec<Vec<String>> = Vec::new();
-    for line in initial_lines {
-        // skip the divider lines
-        if line[0].chars().all(|c| c == '-') {
-            continue;
-        }
-        let mut split_line: Vec<String> = Vec::new();
-        let mut remaining_line: String = line[0].to_string();
-        for index in &column_start_indices {
+/// Column layout of a Serato TXT playlist, where the columns are aligned with spaces.
+pub struct SeratoTxtColumns {
+    names: Vec<String>,
+    /// Starting location of each column item on a line, in reverse order
+    start_indices: Vec<usize>,
+}
+
+impl SeratoTxtColumns {
+    /// Get the column names and locations from the header line.
+    pub fn from_header(header_line: &str) -> Self {
+        let names: Vec<String> = header_line
+            .replace("     ", "\t")
+            .split('\t')
+            .filter_map(|s| {
+                let v = s.trim();
+                if s.is_empty() {
+                    None
+                } else {
+                    Some(v.to_string())
+                }
+            })
+            .collect();
+        // Columns are aligned by character, so the locations are character counts instead of byte indices.
+        // Each name is searched after the previous one so the locations stay in order.
+        let mut position = 0;
+        let mut start_indices: Vec<usize> = names
+            .iter()
+            .map(|name| {
+                let index = header_line[position..]
+                    .find(name.as_str())
+                    .map_or(position, |i| position + i);
+                position = index + name.len();
+                header_line[..index].chars().count()
+            })
+            .collect();
+        // Extract each column item from the line, starting from the end of the line
+        start_indices.reverse();
+        Self { names, start_indices }
+    }
+
+    /// Get the column names.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Split a line into the column values.
+    pub fn split<'a>(&self, line: &'a str) -> Vec<&'a str> {
+        let mut split_line: Vec<&str> = Vec::new();
+        let mut remaining_line: &str = line;
+        for index in &self.start_indices {
             // some lines do not contain data in all fields
-            if *index >= remaining_line.len() {
+            let Some((byte_index, _)) = remaining_line.char_indices().nth(*index) else {
                 continue;
-            }
-            let (string_left, value) = remaining_line.split_at(*index);
-            split_line.push(value.trim().to_string());
-            remaining_line = string_left.to_string();
+            };
+            let (string_left, value) = remaining_line.split_at(byte_index);
+            split_line.push(value.trim());
+            remaining_line = string_left;
         }
         // Convert line item order since we extracted items in reverse order
         split_line.reverse();
         // pad line in case some columns did not have any data
-        while split_line.len() < column_names.len() {
-            split_line.push(String::new());
+        while split_line.len() < self.names.len() {
+            split_line.push("");
         }
-        serato_lines.push(split_line);
+        split_line
     }
-    serato_lines
 }
 
-fn parse_track_with_time_from_row(start_date: NaiveDate, row: &BTreeMap<String, String>) -> Track {
+/// Describe the values in a Serato track row that can't be parsed.
+pub fn serato_row_issues(row: &Row) -> Vec<String> {
+    [
+        ("start time", SERATO_TIME_FORMAT),
+        ("end time", SERATO_TIME_FORMAT),
+        ("playtime", SERATO_PLAYTIME_FORMAT),
+    ]
+    .into_iter()
+    .filter_map(|(column, format)| {
+        let value = row
+            .get(column)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())?;
+        NaiveTime::parse_from_str(value, format)
+            .is_err()
+            .then(|| format!("invalid {column} '{value}'"))
+    })
+    .collect()
+}
+
+fn parse_track_with_time_from_row(start_date: NaiveDate, row: &Row) -> Track {
     let start_time: Option<NaiveDateTime> = row
         .get("start time")
-        .and_then(|t| NaiveTime::parse_from_str(t, "%H.%M.%S %Z").ok())
+        .and_then(|t| NaiveTime::parse_from_str(t, SERATO_TIME_FORMAT).ok())
         .map(|time| NaiveDateTime::new(start_date, time));
 
     let end_time: Option<NaiveDateTime> = row
         .get("end time")
-        .and_then(|t| NaiveTime::parse_from_str(t, "%H.%M.%S %Z").ok())
-        .map(|time| NaiveDateTime::new(start_date, time));
+        .and_then(|t| NaiveTime::parse_from_str(t, SERATO_TIME_FORMAT).ok())
+        .map(|time| NaiveDateTime::new(start_date, time))
+        // Track played over midnight ends on the next day
+        .map(|end| match start_time {
+            Some(start) if end < start => end.checked_add_signed(TimeDelta::days(1)).unwrap_or(end),
+            _ => end,
+        });
 
-    let play_time = match row.get("playtime") {
-        Some(t) => NaiveTime::parse_from_str(t, "%H:%M:%S").ok().and_then(|n| {
+    let play_time = match row.get("playtime").filter(|t| !t.trim().is_empty()) {
+        Some(t) => NaiveTime::parse_from_str(t, SERATO_PLAYTIME_FORMAT).ok().and_then(|n| {
             let hours = TimeDelta::try_hours(i64::from(n.hour()))?;
             let minutes = TimeDelta::try_minutes(i64::from(n.minute()))?;
             let seconds = TimeDelta::try_seconds(i64::from(n.second()))?;
@@ -189,11 +342,28 @@ fn parse_track_with_time_from_row(start_date: NaiveDate, row: &BTreeMap<String,
         }),
         None => start_time.and_then(|start| end_time.map(|end| end - start)),
     };
-    Track::new_with_time(
-        row.get("artist").unwrap_or(&"".to_string()).to_string(),
-        row.get("name").unwrap_or(&"".to_string()).to_string(),
-        start_time,
-        end_time,
-        play_time,
-    )
+    let mut track = Track::builder()
+        .artist(row.get("artist").unwrap_or_default())
+        .title(row.get("name").unwrap_or_default())
+        .start_time(start_time)
+        .end_time(end_time)
+        .play_time(play_time)
+        .number(row.get("#").and_then(|number| number.trim().parse().ok()))
+        .build();
+    // Optional columns are only present when enabled in the Serato export settings
+    for field in METADATA_FIELDS {
+        if let Some(value) = row.get(&field.to_lowercase()) {
+            track.set_metadata(field, value);
+        }
+    }
+    track.notes = row
+        .get("notes")
+        .map(|notes| notes.trim())
+        .filter(|notes| !notes.is_empty())
+        .map(ToString::to_string);
+    track.add_extra_columns(
+        row,
+        &["#", "name", "artist", "start time", "end time", "playtime", "notes"],
+    );
+    track
 }
@@ -0,0 +1,843 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use super::error::Result;
+use chrono::TimeDelta;
+#[cfg(feature = "cli")]
+use colored::Colorize;
+use serde::Serialize;
+
+use super::key::{self, MusicalKey};
+use super::track::Track;
+use super::utils;
+
+/// Playtime buckets as (label, minimum seconds) pairs.
+const PLAYTIME_BUCKETS: [(&str, i64); 8] = [
+    ("< 1:00", 0),
+    ("1:00 - 2:00", 60),
+    ("2:00 - 3:00", 120),
+    ("3:00 - 4:00", 180),
+    ("4:00 - 5:00", 240),
+    ("5:00 - 6:00", 300),
+    ("6:00 - 8:00", 360),
+    ("8:00+", 480),
+];
+
+/// Bar heights used for sparklines, from lowest to highest.
+const SPARKLINE_LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+/// Maximum number of bars in the printed BPM sparkline.
+pub const SPARKLINE_WIDTH: usize = 60;
+
+/// Default BPM change between consecutive tracks that is flagged as a tempo jump.
+pub const DEFAULT_BPM_JUMP: f64 = 8.0;
+/// Default time between the end of a track and the start of the next track that is reported as a gap.
+pub const DEFAULT_MAX_GAP_SECONDS: i64 = 60;
+/// Default time two consecutive tracks play at the same time that is reported as a long overlap.
+pub const DEFAULT_MAX_OVERLAP_SECONDS: i64 = 300;
+
+/// Thresholds for flagging transitions between tracks.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StatsOptions {
+    /// BPM change flagged as a tempo jump
+    pub bpm_jump: f64,
+    /// Time without a track playing that is reported as a gap
+    pub max_gap: TimeDelta,
+    /// Time two consecutive tracks play at the same time that is reported as a long overlap
+    pub max_overlap: TimeDelta,
+}
+
+impl Default for StatsOptions {
+    fn default() -> Self {
+        Self {
+            bpm_jump: DEFAULT_BPM_JUMP,
+            max_gap: TimeDelta::seconds(DEFAULT_MAX_GAP_SECONDS),
+            max_overlap: TimeDelta::seconds(DEFAULT_MAX_OVERLAP_SECONDS),
+        }
+    }
+}
+
+/// Track with its playtime in seconds.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackPlaytime {
+    pub artist: String,
+    pub title: String,
+    pub seconds: i64,
+}
+
+/// Number of tracks played from an artist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ArtistCount {
+    pub artist: String,
+    pub count: usize,
+}
+
+/// Number of tracks with the given metadata value, such as a genre or key.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValueCount {
+    pub value: String,
+    pub count: usize,
+}
+
+/// Transition between two consecutive tracks with incompatible keys.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct KeyClash {
+    /// Number of the track mixed into, counting from one
+    pub track_number: usize,
+    pub from_key: String,
+    pub to_key: String,
+    pub from_track: String,
+    pub to_track: String,
+}
+
+/// Tempo change between two consecutive tracks over the BPM jump threshold.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TempoJump {
+    /// Number of the track mixed into, counting from one
+    pub track_number: usize,
+    pub from_bpm: f64,
+    pub to_bpm: f64,
+    pub from_track: String,
+    pub to_track: String,
+}
+
+/// Time between the end of a track and the start of the next track.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackGap {
+    /// Number of the track after the gap, counting from one
+    pub track_number: usize,
+    /// End time of the previous track
+    pub start: String,
+    pub seconds: i64,
+    pub from_track: String,
+    pub to_track: String,
+}
+
+/// Track that started long before the previous track ended.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TrackOverlap {
+    /// Number of the later track, counting from one
+    pub track_number: usize,
+    pub seconds: i64,
+    pub from_track: String,
+    pub to_track: String,
+}
+
+/// Number of tracks with a playtime in the given range.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PlaytimeBucket {
+    pub label: String,
+    pub min_seconds: i64,
+    pub count: usize,
+}
+
+/// Per-set analytics for a playlist.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PlaylistStats {
+    pub track_count: usize,
+    pub unique_artists: usize,
+    /// Track count per artist, sorted by track count
+    pub artists: Vec<ArtistCount>,
+    /// Artists with more than one track, sorted by track count
+    pub repeated_artists: Vec<ArtistCount>,
+    pub shortest_track: Option<TrackPlaytime>,
+    pub longest_track: Option<TrackPlaytime>,
+    pub total_playtime_seconds: Option<i64>,
+    pub average_playtime_seconds: Option<i64>,
+    pub playtime_distribution: Vec<PlaytimeBucket>,
+    /// Track count per genre, most common first
+    pub genres: Vec<ValueCount>,
+    /// Track count per musical key, most common first
+    pub keys: Vec<ValueCount>,
+    /// Number of transitions where both tracks have a recognized key
+    pub key_transitions: usize,
+    /// Transitions between incompatible keys
+    pub key_clashes: Vec<KeyClash>,
+    pub min_bpm: Option<f64>,
+    pub max_bpm: Option<f64>,
+    pub average_bpm: Option<f64>,
+    /// Tempo of each track with a known BPM, in playing order
+    pub bpm_curve: Vec<f64>,
+    /// BPM change flagged as a tempo jump
+    pub bpm_jump_threshold: f64,
+    /// Transitions with a tempo change over the threshold
+    pub tempo_jumps: Vec<TempoJump>,
+    /// Time between tracks reported as a gap
+    pub max_gap_seconds: i64,
+    /// Gaps between tracks longer than the threshold
+    pub gaps: Vec<TrackGap>,
+    /// Overlap between tracks reported as a long overlap
+    pub max_overlap_seconds: i64,
+    /// Overlaps between tracks longer than the threshold
+    pub overlaps: Vec<TrackOverlap>,
+}
+
+impl PlaylistStats {
+    /// Calculate statistics for the given tracks.
+    pub fn from_tracks(tracks: &[Track]) -> PlaylistStats {
+        Self::from_tracks_with_options(tracks, &StatsOptions::default())
+    }
+
+    /// Calculate statistics for the given tracks using the given thresholds for flagging transitions.
+    pub fn from_tracks_with_options(tracks: &[Track], options: &StatsOptions) -> PlaylistStats {
+        let mut artist_counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for track in tracks {
+            *artist_counts.entry(track.artist.as_str()).or_default() += 1;
+        }
+        let mut artists: Vec<ArtistCount> = artist_counts
+            .iter()
+            .map(|(artist, count)| ArtistCount {
+                artist: (*artist).to_string(),
+                count: *count,
+            })
+            .collect();
+        artists.sort_by_key(|a| std::cmp::Reverse(a.count));
+        let repeated_artists: Vec<ArtistCount> = artists.iter().filter(|a| a.count > 1).cloned().collect();
+
+        let timed_tracks: Vec<TrackPlaytime> = tracks
+            .iter()
+            .filter_map(|track| {
+                track.play_time.map(|duration| TrackPlaytime {
+                    artist: track.artist.clone(),
+                    title: track.title.clone(),
+                    seconds: duration.num_seconds(),
+                })
+            })
+            .collect();
+
+        let total_playtime_seconds = utils::get_total_playtime(tracks).map(|d| d.num_seconds());
+        let average_playtime_seconds = total_playtime_seconds
+            .filter(|_| !timed_tracks.is_empty())
+            .map(|total| total / timed_tracks.len() as i64);
+
+        let playtime_distribution = if timed_tracks.is_empty() {
+            Vec::new()
+        } else {
+            PLAYTIME_BUCKETS
+                .iter()
+                .enumerate()
+                .map(|(index, (label, min_seconds))| {
+                    let max_seconds = PLAYTIME_BUCKETS.get(index + 1).map_or(i64::MAX, |(_, max)| *max);
+                    PlaytimeBucket {
+                        label: (*label).to_string(),
+                        min_seconds: *min_seconds,
+                        count: timed_tracks
+                            .iter()
+                            .filter(|t| t.seconds >= *min_seconds && t.seconds < max_seconds)
+                            .count(),
+                    }
+                })
+                .collect()
+        };
+
+        let key_clashes = key::key_clashes(tracks)
+            .into_iter()
+            .map(|index| KeyClash {
+                track_number: index + 1,
+                from_key: tracks[index - 1].key.clone().unwrap_or_default(),
+                to_key: tracks[index].key.clone().unwrap_or_default(),
+                from_track: tracks[index - 1].to_string(),
+                to_track: tracks[index].to_string(),
+            })
+            .collect();
+
+        let tempo_jumps = tempo_jumps(tracks, options.bpm_jump)
+            .into_iter()
+            .filter_map(|index| {
+                Some(TempoJump {
+                    track_number: index + 1,
+                    from_bpm: tracks[index - 1].bpm?,
+                    to_bpm: tracks[index].bpm?,
+                    from_track: tracks[index - 1].to_string(),
+                    to_track: tracks[index].to_string(),
+                })
+            })
+            .collect();
+        let bpm_range = bpm_range(tracks);
+        let gaps = track_gaps(tracks, options.max_gap)
+            .into_iter()
+            .map(|(index, gap)| TrackGap {
+                track_number: index + 1,
+                start: tracks[index - 1]
+                    .end_time
+                    .map(|end| end.format("%H:%M:%S").to_string())
+                    .unwrap_or_default(),
+                seconds: gap.num_seconds(),
+                from_track: tracks[index - 1].to_string(),
+                to_track: tracks[index].to_string(),
+            })
+            .collect();
+        let overlaps = long_overlaps(tracks, options.max_overlap)
+            .into_iter()
+            .map(|(index, overlap)| TrackOverlap {
+                track_number: index + 1,
+                seconds: overlap.num_seconds(),
+                from_track: tracks[index - 1].to_string(),
+                to_track: tracks[index].to_string(),
+            })
+            .collect();
+
+        PlaylistStats {
+            track_count: tracks.len(),
+            unique_artists: artist_counts.len(),
+            artists,
+            repeated_artists,
+            shortest_track: timed_tracks.iter().min_by_key(|t| t.seconds).cloned(),
+            longest_track: timed_tracks.iter().max_by_key(|t| t.seconds).cloned(),
+            total_playtime_seconds,
+            average_playtime_seconds,
+            playtime_distribution,
+            genres: genre_counts(tracks),
+            keys: key_counts(tracks),
+            key_transitions: key::key_transition_count(tracks),
+            key_clashes,
+            min_bpm: bpm_range.map(|(min, _, _)| min),
+            max_bpm: bpm_range.map(|(_, max, _)| max),
+            average_bpm: bpm_range.map(|(_, _, average)| average),
+            bpm_curve: tracks.iter().filter_map(|track| track.bpm).collect(),
+            bpm_jump_threshold: options.bpm_jump,
+            tempo_jumps,
+            max_gap_seconds: options.max_gap.num_seconds(),
+            gaps,
+            max_overlap_seconds: options.max_overlap.num_seconds(),
+            overlaps,
+        }
+    }
+
+    /// Get a sparkline of the tempo over the set, if at least two tracks have a BPM.
+    pub fn bpm_sparkline(&self) -> Option<String> {
+        sparkline(&self.bpm_curve, SPARKLINE_WIDTH)
+    }
+
+    /// Get statistics as summary rows of (label, value) pairs.
+    pub fn summary(&self) -> Vec<(String, String)> {
+        let mut rows = vec![
+            ("Tracks".to_string(), self.track_count.to_string()),
+            ("Unique artists".to_string(), self.unique_artists.to_string()),
+        ];
+        if let Some(total) = self.total_playtime_seconds {
+            rows.push(("Total playtime".to_string(), format_seconds(total)));
+        }
+        if let Some(average) = self.average_playtime_seconds {
+            rows.push(("Average playtime".to_string(), format_seconds(average)));
+        }
+        if let Some(track) = &self.shortest_track {
+            rows.push((
+                "Shortest track".to_string(),
+                format!("{} - {} ({})", track.artist, track.title, format_seconds(track.seconds)),
+            ));
+        }
+        if let Some(track) = &self.longest_track {
+            rows.push((
+                "Longest track".to_string(),
+                format!("{} - {} ({})", track.artist, track.title, format_seconds(track.seconds)),
+            ));
+        }
+        if let (Some(min), Some(max), Some(average)) = (self.min_bpm, self.max_bpm, self.average_bpm) {
+            rows.push(("BPM".to_string(), format_bpm_range(min, max, average)));
+            rows.push((
+                "Tempo jumps".to_string(),
+                format!(
+                    "{} over {} BPM",
+                    self.tempo_jumps.len(),
+                    utils::formatted_bpm(self.bpm_jump_threshold)
+                ),
+            ));
+        }
+        if !self.gaps.is_empty() {
+            let total: i64 = self.gaps.iter().map(|gap| gap.seconds).sum();
+            rows.push((
+                "Gaps".to_string(),
+                format!(
+                    "{} over {}, total {}",
+                    self.gaps.len(),
+                    format_seconds(self.max_gap_seconds),
+                    format_seconds(total)
+                ),
+            ));
+        }
+        if !self.overlaps.is_empty() {
+            rows.push((
+                "Long overlaps".to_string(),
+                format!(
+                    "{} over {}",
+                    self.overlaps.len(),
+                    format_seconds(self.max_overlap_seconds)
+                ),
+            ));
+        }
+        if self.key_transitions > 0 {
+            rows.push((
+                "Key clashes".to_string(),
+                format!("{} of {} transitions", self.key_clashes.len(), self.key_transitions),
+            ));
+        }
+        rows
+    }
+
+    /// Print statistics in a human-readable format.
+    #[cfg(feature = "cli")]
+    pub fn print(&self) {
+        let label_width = self.summary().iter().map(|(label, _)| label.len()).max().unwrap_or(0);
+        for (label, value) in self.summary() {
+            println!("{:<label_width$}   {}", label.bold(), value);
+        }
+        if !self.repeated_artists.is_empty() {
+            println!("\n{}", "Artists played more than once".bold());
+            for artist in &self.repeated_artists {
+                println!("  {}x {}", artist.count, artist.artist);
+            }
+        }
+        if !self.playtime_distribution.is_empty() {
+            println!("\n{}", "Playtime distribution".bold());
+            let bucket_width = self
+                .playtime_distribution
+                .iter()
+                .map(|b| b.label.len())
+                .max()
+                .unwrap_or(0);
+            for bucket in &self.playtime_distribution {
+                println!(
+                    "  {:<bucket_width$}   {:>3} {}",
+                    bucket.label,
+                    bucket.count,
+                    "#".repeat(bucket.count).green()
+                );
+            }
+        }
+        for (title, counts) in [("Genres", &self.genres), ("Keys", &self.keys)] {
+            if counts.is_empty() {
+                continue;
+            }
+            println!("\n{}", title.bold());
+            let value_width = counts.iter().map(|c| utils::display_width(&c.value)).max().unwrap_or(0);
+            for count in counts {
+                println!(
+                    "  {}   {:>3} {}",
+                    utils::pad_to_width(&count.value, value_width),
+                    count.count,
+                    "#".repeat(count.count).green()
+                );
+            }
+        }
+        if !self.gaps.is_empty() {
+            println!("\n{}", "Gaps between tracks".bold());
+            for gap in &self.gaps {
+                println!(
+                    "  {:>3}  {}  {:>5}   {} -> {}",
+                    gap.track_number,
+                    gap.start,
+                    format_seconds(gap.seconds).red(),
+                    gap.from_track,
+                    gap.to_track
+                );
+            }
+        }
+        if !self.overlaps.is_empty() {
+            println!("\n{}", "Long overlaps".bold());
+            for overlap in &self.overlaps {
+                println!(
+                    "  {:>3}  {:>5}   {} -> {}",
+                    overlap.track_number,
+                    format_seconds(overlap.seconds).red(),
+                    overlap.from_track,
+                    overlap.to_track
+                );
+            }
+        }
+        if let (Some(sparkline), Some(min), Some(max)) = (self.bpm_sparkline(), self.min_bpm, self.max_bpm) {
+            println!("\n{}", "Tempo".bold());
+            println!(
+                "  {}  {} - {} BPM",
+                sparkline.cyan(),
+                utils::formatted_bpm(min),
+                utils::formatted_bpm(max)
+            );
+        }
+        if !self.tempo_jumps.is_empty() {
+            println!("\n{}", "Tempo jumps".bold());
+            for jump in &self.tempo_jumps {
+                println!(
+                    "  {:>3}  {} -> {}   {} -> {}",
+                    jump.track_number,
+                    utils::formatted_bpm(jump.from_bpm).red(),
+                    utils::formatted_bpm(jump.to_bpm).red(),
+                    jump.from_track,
+                    jump.to_track
+                );
+            }
+        }
+        if !self.key_clashes.is_empty() {
+            println!("\n{}", "Incompatible key transitions".bold());
+            for clash in &self.key_clashes {
+                println!(
+                    "  {:>3}  {} -> {}   {} -> {}",
+                    clash.track_number,
+                    clash.from_key.red(),
+                    clash.to_key.red(),
+                    clash.from_track,
+                    clash.to_track
+                );
+            }
+        }
+    }
+
+    /// Serialize statistics to a JSON string.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+/// Get the indices of tracks whose BPM differs from the previous track by more than the threshold.
+///
+/// Half and double time are treated as the same tempo,
+/// and transitions where either track has no BPM are skipped.
+pub fn tempo_jumps(tracks: &[Track], threshold: f64) -> Vec<usize> {
+    (1..tracks.len())
+        .filter(|&index| match (tracks[index - 1].bpm, tracks[index].bpm) {
+            (Some(from), Some(to)) => bpm_difference(from, to) > threshold,
+            _ => false,
+        })
+        .collect()
+}
+
+/// Count tracks per genre, most common first.
+pub fn genre_counts(tracks: &[Track]) -> Vec<ValueCount> {
+    value_counts(tracks.iter().filter_map(|track| track.genre.clone()))
+}
+
+/// Count tracks per musical key, most common first.
+///
+/// The same key written in different notations is counted together
+/// and shown the way it was first written.
+pub fn key_counts(tracks: &[Track]) -> Vec<ValueCount> {
+    let mut labels: HashMap<MusicalKey, String> = HashMap::new();
+    value_counts(
+        tracks
+            .iter()
+            .filter_map(|track| track.key.as_deref())
+            .map(|text| match MusicalKey::from_str(text) {
+                Ok(key) => labels.entry(key).or_insert_with(|| text.to_string()).clone(),
+                Err(_) => text.to_string(),
+            }),
+    )
+}
+
+/// Format the most common values like `House (12), Techno (5)`, with at most `limit` values.
+pub fn format_value_counts(counts: &[ValueCount], limit: usize) -> String {
+    let mut text = counts
+        .iter()
+        .take(limit)
+        .map(|c| format!("{} ({})", c.value, c.count))
+        .collect::<Vec<_>>()
+        .join(", ");
+    if counts.len() > limit {
+        text.push_str(&format!(" and {} more", counts.len() - limit));
+    }
+    text
+}
+
+/// Count occurrences of each value, sorted by count with ties in order of first appearance.
+fn value_counts(values: impl Iterator<Item = String>) -> Vec<ValueCount> {
+    let mut counts: Vec<ValueCount> = Vec::new();
+    for value in values {
+        match counts.iter_mut().find(|c| c.value == value) {
+            Some(count) => count.count += 1,
+            None => counts.push(ValueCount { value, count: 1 }),
+        }
+    }
+    counts.sort_by_key(|c| std::cmp::Reverse(c.count));
+    counts
+}
+
+/// Get the indices and lengths of gaps longer than the threshold before a track.
+///
+/// A gap is the time from the end of the previous track to the start of the track.
+pub fn track_gaps(tracks: &[Track], threshold: TimeDelta) -> Vec<(usize, TimeDelta)> {
+    (1..tracks.len())
+        .filter_map(|index| {
+            let gap = tracks[index].start_time? - tracks[index - 1].end_time?;
+            (gap > threshold).then_some((index, gap))
+        })
+        .collect()
+}
+
+/// Get the time a track played at the same time as the previous track.
+///
+/// Returns `None` for the first track, when either time is missing, or when the tracks did not overlap.
+pub fn track_overlap(tracks: &[Track], index: usize) -> Option<TimeDelta> {
+    let previous = tracks.get(index.checked_sub(1)?)?;
+    let overlap = previous.end_time? - tracks.get(index)?.start_time?;
+    (overlap > TimeDelta::zero()).then_some(overlap)
+}
+
+/// Get the indices and lengths of overlaps with the previous track longer than the threshold.
+pub fn long_overlaps(tracks: &[Track], threshold: TimeDelta) -> Vec<(usize, TimeDelta)> {
+    (1..tracks.len())
+        .filter_map(|index| track_overlap(tracks, index).map(|overlap| (index, overlap)))
+        .filter(|(_, overlap)| *overlap > threshold)
+        .collect()
+}
+
+/// Get the minimum, maximum, and average BPM of the tracks that have a BPM.
+pub fn bpm_range(tracks: &[Track]) -> Option<(f64, f64, f64)> {
+    let bpms: Vec<f64> = tracks.iter().filter_map(|track| track.bpm).collect();
+    if bpms.is_empty() {
+        return None;
+    }
+    let min = bpms.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = bpms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let average = bpms.iter().sum::<f64>() / bpms.len() as f64;
+    Some((min, max, average))
+}
+
+/// Draw the values as a line of bars scaled between the smallest and largest value.
+///
+/// Consecutive values are averaged when there are more values than the width.
+/// Returns `None` for fewer than two values.
+pub fn sparkline(values: &[f64], width: usize) -> Option<String> {
+    if values.len() < 2 || width == 0 {
+        return None;
+    }
+    let points: Vec<f64> = if values.len() > width {
+        (0..width)
+            .map(|index| {
+                let chunk = &values[index * values.len() / width..(index + 1) * values.len() / width];
+                chunk.iter().sum::<f64>() / chunk.len() as f64
+            })
+            .collect()
+    } else {
+        values.to_vec()
+    };
+    let min = points.iter().copied().fold(f64::INFINITY, f64::min);
+    let max = points.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let top = SPARKLINE_LEVELS.len() - 1;
+    Some(
+        points
+            .iter()
+            .map(|&value| {
+                let level = if max > min {
+                    ((value - min) / (max - min) * top as f64).round() as usize
+                } else {
+                    top / 2
+                };
+                SPARKLINE_LEVELS[level.min(top)]
+            })
+            .collect(),
+    )
+}
+
+/// Format a BPM range like `118 - 128 (avg. 124.5)`.
+pub fn format_bpm_range(min: f64, max: f64, average: f64) -> String {
+    format!(
+        "{} - {} (avg. {:.1})",
+        utils::formatted_bpm(min),
+        utils::formatted_bpm(max),
+        average
+    )
+}
+
+/// Smallest BPM difference between two tempos, also comparing against half and double time.
+fn bpm_difference(from: f64, to: f64) -> f64 {
+    [to, to * 2.0, to / 2.0]
+        .iter()
+        .map(|to| (to - from).abs())
+        .fold(f64::INFINITY, f64::min)
+}
+
+fn format_seconds(seconds: i64) -> String {
+    TimeDelta::try_seconds(seconds).map_or(String::new(), utils::formatted_duration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(artist: &str, title: &str, seconds: i64) -> Track {
+        Track::new(artist.to_string(), title.to_string()) + TimeDelta::try_seconds(seconds).unwrap()
+    }
+
+    #[test]
+    fn stats_from_tracks() {
+        let tracks = vec![
+            track("Artist", "One", 30),
+            track("Other", "Two", 200),
+            track("Artist", "Three", 500),
+        ];
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(stats.track_count, 3);
+        assert_eq!(stats.unique_artists, 2);
+        assert_eq!(
+            stats.repeated_artists,
+            vec![ArtistCount {
+                artist: "Artist".to_string(),
+                count: 2
+            }]
+        );
+        assert_eq!(
+            stats
+                .artists
+                .iter()
+                .map(|a| (a.artist.as_str(), a.count))
+                .collect::<Vec<_>>(),
+            vec![("Artist", 2), ("Other", 1)]
+        );
+        assert_eq!(stats.shortest_track.unwrap().seconds, 30);
+        assert_eq!(stats.longest_track.unwrap().title, "Three");
+        assert_eq!(stats.average_playtime_seconds, Some(243));
+        assert_eq!(stats.playtime_distribution[0].count, 1);
+        assert_eq!(stats.playtime_distribution[3].count, 1);
+        assert_eq!(stats.playtime_distribution[7].count, 1);
+    }
+
+    #[test]
+    fn stats_key_clashes() {
+        let mut tracks = vec![
+            track("Artist", "One", 200),
+            track("Other", "Two", 200),
+            track("Third", "Three", 200),
+        ];
+        for (track, key) in tracks.iter_mut().zip(["8A", "9A", "3B"]) {
+            track.key = Some(key.to_string());
+        }
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(stats.key_transitions, 2);
+        assert_eq!(stats.key_clashes.len(), 1);
+        assert_eq!(stats.key_clashes[0].track_number, 3);
+        assert_eq!(stats.key_clashes[0].from_key, "9A");
+        assert!(stats
+            .summary()
+            .contains(&("Key clashes".to_string(), "1 of 2 transitions".to_string())));
+    }
+
+    #[test]
+    fn stats_tempo_jumps() {
+        let mut tracks = vec![
+            track("Artist", "One", 200),
+            track("Other", "Two", 200),
+            track("Third", "Three", 200),
+            track("Fourth", "Four", 200),
+            track("Fifth", "Five", 200),
+        ];
+        for (track, bpm) in tracks
+            .iter_mut()
+            .zip([Some(120.0), Some(124.0), Some(140.0), Some(70.0), None])
+        {
+            track.bpm = bpm;
+        }
+        assert_eq!(tempo_jumps(&tracks, 8.0), vec![2]);
+        assert_eq!(tempo_jumps(&tracks, 2.0), vec![1, 2]);
+
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(stats.min_bpm, Some(70.0));
+        assert_eq!(stats.max_bpm, Some(140.0));
+        assert_eq!(stats.average_bpm, Some(113.5));
+        assert_eq!(stats.tempo_jumps.len(), 1);
+        assert_eq!(stats.tempo_jumps[0].track_number, 3);
+        assert_eq!(stats.tempo_jumps[0].to_bpm, 140.0);
+        assert!(stats
+            .summary()
+            .contains(&("BPM".to_string(), "70 - 140 (avg. 113.5)".to_string())));
+        assert_eq!(stats.bpm_curve, vec![120.0, 124.0, 140.0, 70.0]);
+        assert_eq!(stats.bpm_sparkline().as_deref(), Some("▆▆█▁"));
+    }
+
+    #[test]
+    fn sparkline_values() {
+        assert_eq!(sparkline(&[120.0], 10), None);
+        assert_eq!(sparkline(&[120.0, 120.0], 10).as_deref(), Some("▄▄"));
+        assert_eq!(
+            sparkline(&[1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0], 8).as_deref(),
+            Some("▁▂▃▄▅▆▇█")
+        );
+        // Pairs of values are averaged to fit the width
+        assert_eq!(sparkline(&[1.0, 1.0, 8.0, 8.0, 1.0, 1.0], 3).as_deref(), Some("▁█▁"));
+    }
+
+    #[test]
+    fn stats_genres_and_keys() {
+        let mut tracks = vec![
+            track("Artist", "One", 200),
+            track("Other", "Two", 200),
+            track("Third", "Three", 200),
+            track("Fourth", "Four", 200),
+        ];
+        for (track, (genre, key)) in
+            tracks
+                .iter_mut()
+                .zip([("Techno", "8A"), ("House", "Am"), ("House", "9A"), ("Disco", "Am")])
+        {
+            track.genre = Some(genre.to_string());
+            track.key = Some(key.to_string());
+        }
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(
+            stats
+                .genres
+                .iter()
+                .map(|c| (c.value.as_str(), c.count))
+                .collect::<Vec<_>>(),
+            vec![("House", 2), ("Techno", 1), ("Disco", 1)]
+        );
+        assert_eq!(
+            stats
+                .keys
+                .iter()
+                .map(|c| (c.value.as_str(), c.count))
+                .collect::<Vec<_>>(),
+            vec![("8A", 3), ("9A", 1)]
+        );
+        assert_eq!(
+            format_value_counts(&stats.genres, 2),
+            "House (2), Techno (1) and 1 more"
+        );
+    }
+
+    #[test]
+    fn stats_gaps_and_overlaps() {
+        let start = chrono::NaiveDate::from_ymd_opt(2024, 3, 8)
+            .unwrap()
+            .and_hms_opt(22, 0, 0)
+            .unwrap();
+        let timed = |title: &str, start_minutes: i64, end_minutes: i64| {
+            Track::new_with_time(
+                "Artist".to_string(),
+                title.to_string(),
+                Some(start + TimeDelta::minutes(start_minutes)),
+                Some(start + TimeDelta::minutes(end_minutes)),
+                Some(TimeDelta::minutes(end_minutes - start_minutes)),
+            )
+        };
+        let tracks = vec![
+            timed("One", 0, 5),
+            timed("Two", 4, 10),
+            timed("Three", 13, 18),
+            timed("Four", 18, 20),
+        ];
+        assert_eq!(
+            track_gaps(&tracks, TimeDelta::seconds(60)),
+            vec![(2, TimeDelta::minutes(3))]
+        );
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(stats.gaps.len(), 1);
+        assert_eq!(stats.gaps[0].track_number, 3);
+        assert_eq!(stats.gaps[0].start, "22:10:00");
+        assert_eq!(stats.gaps[0].seconds, 180);
+        let options = StatsOptions {
+            max_gap: TimeDelta::minutes(5),
+            ..StatsOptions::default()
+        };
+        assert!(PlaylistStats::from_tracks_with_options(&tracks, &options)
+            .gaps
+            .is_empty());
+    }
+
+    #[test]
+    fn stats_without_playtimes() {
+        let tracks = vec![Track::new("Artist".to_string(), "Title".to_string())];
+        let stats = PlaylistStats::from_tracks(&tracks);
+        assert_eq!(stats.track_count, 1);
+        assert!(stats.shortest_track.is_none());
+        assert!(stats.average_playtime_seconds.is_none());
+        assert!(stats.playtime_distribution.is_empty());
+    }
+}
@@ -0,0 +1,84 @@
+use chrono::{NaiveDateTime, TimeZone};
+use chrono_tz::Tz;
+
+/// Get the time zone for a time zone abbreviation used in Serato timestamps.
+///
+/// Abbreviations are ambiguous, so each one maps to a representative zone
+/// that uses it, for example `EET` and `EEST` map to `Europe/Helsinki`.
+pub fn zone_from_abbreviation(abbreviation: &str) -> Option<Tz> {
+    let zone = match abbreviation.trim().to_uppercase().as_str() {
+        "UTC" | "GMT" | "Z" => Tz::UTC,
+        "BST" => Tz::Europe__London,
+        "WET" | "WEST" => Tz::Europe__Lisbon,
+        "CET" | "CEST" => Tz::Europe__Berlin,
+        "EET" | "EEST" => Tz::Europe__Helsinki,
+        "MSK" => Tz::Europe__Moscow,
+        "EST" | "EDT" => Tz::America__New_York,
+        "CST" | "CDT" => Tz::America__Chicago,
+        "MST" | "MDT" => Tz::America__Denver,
+        "PST" | "PDT" => Tz::America__Los_Angeles,
+        "JST" => Tz::Asia__Tokyo,
+        "AEST" | "AEDT" => Tz::Australia__Sydney,
+        _ => return None,
+    };
+    Some(zone)
+}
+
+/// Get the time zone from the abbreviation at the end of a timestamp,
+/// for example `10.01.2019, 20.00.00 EET`.
+pub fn zone_from_timestamp(timestamp: &str) -> Option<Tz> {
+    timestamp
+        .split_whitespace()
+        .last()
+        .filter(|word| word.chars().all(|c| c.is_ascii_alphabetic()))
+        .and_then(zone_from_abbreviation)
+}
+
+/// Convert a local time in one time zone to the local time in another zone.
+///
+/// Times that do not exist in the source zone, such as during a daylight saving gap, are returned unchanged.
+pub fn convert(datetime: NaiveDateTime, from: Tz, to: Tz) -> NaiveDateTime {
+    match from.from_local_datetime(&datetime).earliest() {
+        Some(local) => local.with_timezone(&to).naive_local(),
+        None => datetime,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn zone_from_serato_timestamp() {
+        assert_eq!(
+            zone_from_timestamp("10.01.2019, 20.00.00 EET"),
+            Some(Tz::Europe__Helsinki)
+        );
+        assert_eq!(zone_from_timestamp("16.04.53 EEST"), Some(Tz::Europe__Helsinki));
+        assert_eq!(zone_from_timestamp("16.04.53 cest"), Some(Tz::Europe__Berlin));
+        assert_eq!(zone_from_timestamp("16.04.53"), None);
+        assert_eq!(zone_from_timestamp("16.04.53 XYZ"), None);
+        assert_eq!(zone_from_timestamp(""), None);
+    }
+
+    #[test]
+    fn convert_between_zones() {
+        let summer = NaiveDate::from_ymd_opt(2023, 7, 1)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        let converted = convert(summer, Tz::Europe__Helsinki, Tz::Europe__London);
+        assert_eq!(converted, summer - chrono::TimeDelta::hours(2));
+
+        let winter = NaiveDate::from_ymd_opt(2019, 1, 10)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        assert_eq!(
+            convert(winter, Tz::Europe__Helsinki, Tz::UTC),
+            winter - chrono::TimeDelta::hours(2)
+        );
+    }
+}
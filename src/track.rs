@@ -1,8 +1,13 @@
+use std::collections::BTreeMap;
 use std::fmt;
 use std::ops::{Add, AddAssign};
 use std::string::String;
 
-use chrono::{NaiveDateTime, TimeDelta};
+use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+
+use super::builder::TrackBuilder;
+use super::reader::Row;
+use super::{normalize, utils};
 
 /// Represents one played track.
 #[derive(Debug, Clone)]
@@ -12,17 +17,82 @@ pub struct Track {
     pub start_time: Option<NaiveDateTime>,
     pub end_time: Option<NaiveDateTime>,
     pub play_time: Option<TimeDelta>,
+    /// Hand-written comment for the track
+    pub notes: Option<String>,
+    pub bpm: Option<f64>,
+    pub key: Option<String>,
+    pub genre: Option<String>,
+    pub album: Option<String>,
+    pub label: Option<String>,
+    pub year: Option<u16>,
+    /// Star rating from 1 to 5
+    pub rating: Option<u8>,
+    /// Full length of the audio file
+    pub length: Option<TimeDelta>,
+    pub date_added: Option<NaiveDate>,
+    /// Serato deck the track was played from
+    pub deck: Option<u8>,
+    /// Values from input columns that are not otherwise used, keyed by column name.
+    ///
+    /// This is the per-track metadata map for columns the parsers do not model.
+    /// The writers emit it when [`FormatOptions::include_extras`](crate::options::FormatOptions::include_extras) is set.
+    /// It keeps the name `extras` rather than `extra`, since it is already public API.
+    pub extras: BTreeMap<String, String>,
+    /// Secondary tracks played on top of this track
+    pub mashups: Vec<Track>,
+    /// Number of separate plays merged into this track
+    pub plays: usize,
+    /// Name of the DJ who played the track in a back-to-back set
+    pub dj: Option<String>,
+    /// Track number in the source playlist
+    pub number: Option<usize>,
 }
 
+/// Separator between the main track and a track played on top of it, as in `Title w/ Artist - Title`.
+pub const MASHUP_SEPARATOR: &str = " w/ ";
+
+/// Column names for the optional track metadata fields, in output order.
+pub const METADATA_FIELDS: [&str; 10] = [
+    "BPM",
+    "Key",
+    "Genre",
+    "Album",
+    "Label",
+    "Year",
+    "Rating",
+    "Length",
+    "Date Added",
+    "Deck",
+];
+
+/// Column names for the audio file location in exported playlists.
+pub const LOCATION_COLUMNS: [&str; 4] = ["Location", "Filename", "File Name", "Path"];
+
 impl Track {
     /// Create a simple track with only artist name and song title.
     pub fn new(artist: String, title: String) -> Track {
         Track {
-            artist,
-            title,
+            artist: normalize::nfc(artist),
+            title: normalize::nfc(title),
             start_time: None,
             end_time: None,
             play_time: None,
+            notes: None,
+            bpm: None,
+            key: None,
+            genre: None,
+            album: None,
+            label: None,
+            year: None,
+            rating: None,
+            length: None,
+            date_added: None,
+            deck: None,
+            extras: BTreeMap::new(),
+            mashups: Vec::new(),
+            plays: 1,
+            dj: None,
+            number: None,
         }
     }
 
@@ -35,24 +105,220 @@ impl Track {
         play_time: Option<TimeDelta>,
     ) -> Track {
         Track {
-            artist,
-            title,
+            artist: normalize::nfc(artist),
+            title: normalize::nfc(title),
             start_time,
             end_time,
             play_time,
+            notes: None,
+            bpm: None,
+            key: None,
+            genre: None,
+            album: None,
+            label: None,
+            year: None,
+            rating: None,
+            length: None,
+            date_added: None,
+            deck: None,
+            extras: BTreeMap::new(),
+            mashups: Vec::new(),
+            plays: 1,
+            dj: None,
+            number: None,
         }
     }
 
-    /// Get the number of characters the artist name has.
+    /// Start building a track with optional fields, see [`TrackBuilder`].
+    pub fn builder() -> TrackBuilder {
+        TrackBuilder::new()
+    }
+
+    /// Create a track from an `Artist - Title` name.
+    ///
+    /// A name without a separator is used as the title with an empty artist.
+    pub fn from_name(name: &str) -> Track {
+        match name.split_once(" - ") {
+            Some((artist, title)) => Track::new(artist.trim().to_string(), title.trim().to_string()),
+            None => Track::new(String::new(), name.trim().to_string()),
+        }
+    }
+
+    /// Get the `Artist - Title` name, or only the title if there is no artist.
+    pub fn name(&self) -> String {
+        if self.artist.is_empty() {
+            self.title.clone()
+        } else {
+            self.to_string()
+        }
+    }
+
+    /// Move tracks given with `w/` in the title to the mashups of this track.
+    ///
+    /// Returns true if the title contained mashups.
+    pub fn split_mashups(&mut self) -> bool {
+        let Some(index) = self.title.to_lowercase().find(MASHUP_SEPARATOR) else {
+            return false;
+        };
+        let rest = self.title[index + MASHUP_SEPARATOR.len()..].to_string();
+        self.title = self.title[..index].trim().to_string();
+        let mut rest = rest.as_str();
+        loop {
+            let (name, next) = match rest.to_lowercase().find(MASHUP_SEPARATOR) {
+                Some(index) => (&rest[..index], Some(&rest[index + MASHUP_SEPARATOR.len()..])),
+                None => (rest, None),
+            };
+            if !name.trim().is_empty() {
+                self.mashups.push(Track::from_name(name));
+            }
+            match next {
+                Some(next) => rest = next,
+                None => break,
+            }
+        }
+        true
+    }
+
+    /// Get the names of the mashup tracks joined with a semicolon, as written to the mashup column.
+    pub fn mashups_text(&self) -> String {
+        self.mashups.iter().map(Track::name).collect::<Vec<_>>().join("; ")
+    }
+
+    /// Get a normalized artist and title used for fuzzy duplicate matching.
+    ///
+    /// Ignores case, punctuation, whitespace, and an "original mix" suffix.
+    pub fn fuzzy_key(&self) -> String {
+        let normalize = |text: &str| -> String {
+            text.to_lowercase()
+                .replace("original mix", "")
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect()
+        };
+        format!("{}|{}", normalize(&self.artist), normalize(&self.title))
+    }
+
+    /// Compute a missing start time, end time, or play time from the other two.
+    pub fn fill_missing_times(&mut self) {
+        match (self.start_time, self.end_time, self.play_time) {
+            (Some(start), None, Some(play_time)) => self.end_time = Some(start + play_time),
+            (None, Some(end), Some(play_time)) => self.start_time = Some(end - play_time),
+            (Some(start), Some(end), None) if end >= start => self.play_time = Some(end - start),
+            _ => {}
+        }
+    }
+
+    /// Set a metadata field from its column name, ignoring empty and invalid values.
+    ///
+    /// Returns false if the name is not a known metadata field.
+    pub fn set_metadata(&mut self, field: &str, value: &str) -> bool {
+        let value = value.trim();
+        let text = (!value.is_empty()).then(|| value.to_string());
+        match field.to_lowercase().as_str() {
+            "bpm" => self.bpm = value.replace(',', ".").parse::<f64>().ok().filter(|bpm| *bpm > 0.0),
+            "key" => self.key = text,
+            "genre" => self.genre = text,
+            "album" => self.album = text,
+            "label" => self.label = text,
+            "year" => self.year = value.parse::<u16>().ok().filter(|year| *year > 0),
+            "rating" => self.rating = parse_rating(value),
+            "length" => self.length = parse_length(value),
+            "date added" => self.date_added = NaiveDate::parse_from_str(value, "%Y-%m-%d").ok(),
+            "deck" => self.deck = value.parse::<u8>().ok().filter(|deck| *deck > 0),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Keep the non-empty values from columns that are neither known columns nor metadata fields.
+    ///
+    /// Column names are compared case-insensitively.
+    pub fn add_extra_columns(&mut self, row: &Row, known_columns: &[&str]) {
+        let is_known = |column: &str| {
+            known_columns
+                .iter()
+                .chain(METADATA_FIELDS.iter())
+                .any(|known| known.eq_ignore_ascii_case(column))
+        };
+        for (column, value) in row.iter() {
+            let value = value.trim();
+            if !column.trim().is_empty() && !value.is_empty() && !is_known(column) {
+                self.extras.insert(column.to_string(), value.to_string());
+            }
+        }
+    }
+
+    /// Get the audio file location, if the input had one of the [`LOCATION_COLUMNS`].
+    pub fn location(&self) -> Option<&str> {
+        self.extras
+            .iter()
+            .find(|(column, _)| LOCATION_COLUMNS.iter().any(|name| name.eq_ignore_ascii_case(column)))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Get the metadata values formatted for output, in the same order as [`METADATA_FIELDS`].
+    pub fn metadata(&self) -> [Option<String>; METADATA_FIELDS.len()] {
+        [
+            self.bpm.map(utils::formatted_bpm),
+            self.key.clone(),
+            self.genre.clone(),
+            self.album.clone(),
+            self.label.clone(),
+            self.year.map(|year| year.to_string()),
+            self.rating.map(|rating| rating.to_string()),
+            self.length.map(utils::formatted_duration),
+            self.date_added.map(|date| date.format("%Y-%m-%d").to_string()),
+            self.deck.map(|deck| deck.to_string()),
+        ]
+    }
+
+    /// Get the display width of the artist name in terminal columns.
     pub fn artist_length(&self) -> usize {
-        // .len() counts bytes, not chars
-        self.artist.chars().count()
+        utils::display_width(&self.artist)
     }
 
-    /// Get the number of characters the song title has.
+    /// Get the display width of the song title in terminal columns, including the play count marker.
     pub fn title_length(&self) -> usize {
-        self.title.chars().count()
+        utils::display_width(&self.display_title())
     }
+
+    /// Get the title with a play count marker like `×2` if the track was played more than once.
+    pub fn display_title(&self) -> String {
+        if self.plays > 1 {
+            format!("{} ×{}", self.title, self.plays)
+        } else {
+            self.title.clone()
+        }
+    }
+
+    /// Get the `Artist - Title` line for text output, with the play count marker.
+    pub fn display_name(&self) -> String {
+        format!("{} - {}", self.artist, self.display_title())
+    }
+}
+
+/// Parse a star rating given either as a number or as stars like `***`.
+fn parse_rating(value: &str) -> Option<u8> {
+    let rating = if !value.is_empty() && value.chars().all(|c| c == '*') {
+        value.len() as u8
+    } else {
+        value.parse::<u8>().ok()?
+    };
+    (1..=5).contains(&rating).then_some(rating)
+}
+
+/// Parse a track length given as `M:SS` or `H:MM:SS`.
+fn parse_length(value: &str) -> Option<TimeDelta> {
+    let parts = value
+        .split(':')
+        .map(|part| part.parse::<i64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    let seconds = match parts.as_slice() {
+        [minutes, seconds] if *seconds < 60 => minutes * 60 + seconds,
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => hours * 3600 + minutes * 60 + seconds,
+        _ => return None,
+    };
+    TimeDelta::try_seconds(seconds).filter(|length| *length > TimeDelta::zero())
 }
 
 impl Add<Option<TimeDelta>> for Track {
@@ -65,13 +331,7 @@ impl Add<Option<TimeDelta>> for Track {
             },
             None => duration,
         };
-        Track {
-            artist: self.artist,
-            title: self.title,
-            start_time: self.start_time,
-            end_time: self.end_time,
-            play_time,
-        }
+        Track { play_time, ..self }
     }
 }
 
@@ -101,15 +361,12 @@ impl Add<TimeDelta> for Track {
     type Output = Track;
     fn add(self, duration: TimeDelta) -> Track {
         Track {
-            artist: self.artist,
-            title: self.title,
-            start_time: self.start_time,
-            end_time: self.end_time,
             play_time: if let Some(time) = self.play_time {
                 Some(time + duration)
             } else {
                 Some(duration)
             },
+            ..self
         }
     }
 }
@@ -131,6 +388,33 @@ mod tests {
     use super::*;
     use chrono::{NaiveDate, TimeDelta};
 
+    #[test]
+    fn play_count_marker() {
+        let mut track = Track::new("Artist".to_string(), "Title".to_string());
+        assert_eq!(track.display_name(), "Artist - Title");
+        track.plays = 2;
+        assert_eq!(track.display_name(), "Artist - Title ×2");
+        assert_eq!(track.title_length(), "Title ×2".chars().count());
+    }
+
+    #[test]
+    fn split_mashups() {
+        let mut track = Track::new(
+            "Artist".to_string(),
+            "Main Title w/ Other - Acapella W/ Third Title".to_string(),
+        );
+        assert!(track.split_mashups());
+        assert_eq!(track.title, "Main Title");
+        assert_eq!(track.mashups.len(), 2);
+        assert_eq!(track.mashups[0].name(), "Other - Acapella");
+        assert_eq!(track.mashups[1].name(), "Third Title");
+        assert_eq!(track.mashups_text(), "Other - Acapella; Third Title");
+
+        let mut track = Track::new("Artist".to_string(), "Title (Remix)".to_string());
+        assert!(!track.split_mashups());
+        assert!(track.mashups.is_empty());
+    }
+
     #[test]
     fn new_track() {
         let track = Track::new("Artist".to_string(), "Title".to_string());
@@ -186,6 +470,93 @@ mod tests {
         assert_eq!(track1, track2);
     }
 
+    #[test]
+    fn fuzzy_key() {
+        let track1 = Track::new("Some Artist".to_string(), "Song Title (Original Mix)".to_string());
+        let track2 = Track::new("some artist".to_string(), "Song Title".to_string());
+        let track3 = Track::new("Some Artist".to_string(), "Song Title (Remix)".to_string());
+        assert_eq!(track1.fuzzy_key(), track2.fuzzy_key());
+        assert_ne!(track1.fuzzy_key(), track3.fuzzy_key());
+    }
+
+    #[test]
+    fn metadata_fields() {
+        let mut track = Track::new("Artist".to_string(), "Title".to_string());
+        assert!(track.set_metadata("BPM", "122.32"));
+        assert!(track.set_metadata("Key", "6A"));
+        assert!(track.set_metadata("Genre", " Dance "));
+        assert!(track.set_metadata("Album", ""));
+        assert!(track.set_metadata("Year", "2019"));
+        assert!(!track.set_metadata("Comment", "Great"));
+        assert_eq!(track.bpm, Some(122.32));
+        assert_eq!(track.genre.as_deref(), Some("Dance"));
+        assert_eq!(track.album, None);
+        assert!(track.set_metadata("Rating", "***"));
+        assert!(track.set_metadata("Length", "07:07"));
+        assert!(track.set_metadata("Date Added", "2023-03-13"));
+        assert_eq!(
+            track.metadata(),
+            [
+                Some("122.32".to_string()),
+                Some("6A".to_string()),
+                Some("Dance".to_string()),
+                None,
+                None,
+                Some("2019".to_string()),
+                Some("3".to_string()),
+                Some("7:07".to_string()),
+                Some("2023-03-13".to_string()),
+                None,
+            ]
+        );
+        track.set_metadata("Rating", "0");
+        assert_eq!(track.rating, None);
+        track.set_metadata("deck", "2");
+        assert_eq!(track.deck, Some(2));
+        track.set_metadata("bpm", "128");
+        assert_eq!(track.metadata()[0].as_deref(), Some("128"));
+    }
+
+    #[test]
+    fn fill_missing_times() {
+        let start = NaiveDate::from_ymd_opt(2023, 1, 1).unwrap().and_hms_opt(23, 58, 0);
+        let end = start.map(|s| s + TimeDelta::minutes(5));
+        let play_time = TimeDelta::try_minutes(5);
+
+        let mut track = Track::new_with_time("A".to_string(), "T".to_string(), start, None, play_time);
+        track.fill_missing_times();
+        assert_eq!(track.end_time, end);
+
+        let mut track = Track::new_with_time("A".to_string(), "T".to_string(), None, end, play_time);
+        track.fill_missing_times();
+        assert_eq!(track.start_time, start);
+
+        let mut track = Track::new_with_time("A".to_string(), "T".to_string(), start, end, None);
+        track.fill_missing_times();
+        assert_eq!(track.play_time, play_time);
+
+        let mut track = Track::new_with_time("A".to_string(), "T".to_string(), start, None, None);
+        track.fill_missing_times();
+        assert_eq!(track.end_time, None);
+        assert_eq!(track.play_time, None);
+    }
+
+    #[test]
+    fn extra_columns() {
+        let mut track = Track::new("Artist".to_string(), "Title".to_string());
+        let row = Row::from_iter([
+            ("Artist", "Artist"),
+            ("Title", "Title"),
+            ("BPM", "120"),
+            ("Comment", " crowd favourite "),
+            ("Empty", ""),
+            ("", "no name"),
+        ]);
+        track.add_extra_columns(&row, &["artist", "title"]);
+        assert_eq!(track.extras.len(), 1);
+        assert_eq!(track.extras.get("Comment").map(String::as_str), Some("crowd favourite"));
+    }
+
     #[test]
     fn lengths() {
         let track = Track::new("Artist".to_string(), "Title".to_string());
@@ -0,0 +1,297 @@
+use std::io::IsTerminal;
+
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use strum::IntoEnumIterator;
+
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::track::Track;
+use playlist_formatter::types::OutputFormat;
+use playlist_formatter::utils;
+use playlist_formatter::writer::DEFAULT_WRITERS;
+
+const HELP_TEXT: &str =
+    "↑/↓ move  d delete  a edit artist  t edit title  m merge with next  f output format  s save  q quit";
+
+/// Which track field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Artist,
+    Title,
+}
+
+/// What to do after handling a key press.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Action {
+    Continue,
+    Save,
+    Quit,
+}
+
+/// Interactive playlist editor state.
+struct App {
+    tracks: Vec<Track>,
+    state: TableState,
+    output_format: OutputFormat,
+    editing: Option<(Field, String)>,
+    message: String,
+}
+
+/// Show playlist in an interactive terminal view where tracks can be reviewed and edited.
+///
+/// Returns the selected output format if the user chose to save,
+/// or `None` if the editor was closed without saving.
+pub fn run(playlist: &mut Playlist, output_format: OutputFormat) -> Result<Option<OutputFormat>> {
+    if !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        anyhow::bail!("Interactive mode requires a terminal");
+    }
+    let mut app = App::new(playlist, output_format);
+
+    let mut terminal = ratatui::try_init()?;
+    let result = app.run(&mut terminal);
+    ratatui::restore();
+
+    let save = result?;
+    if save {
+        playlist.tracks = app.tracks;
+        playlist.update_totals();
+        Ok(Some(app.output_format))
+    } else {
+        Ok(None)
+    }
+}
+
+impl App {
+    fn new(playlist: &Playlist, output_format: OutputFormat) -> Self {
+        Self {
+            tracks: playlist.tracks.clone(),
+            state: TableState::default().with_selected(Some(0)),
+            output_format,
+            editing: None,
+            message: String::new(),
+        }
+    }
+
+    /// Handle events until the user saves or quits. Returns true if the playlist should be saved.
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<bool> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+            let Event::Key(key) = event::read()? else {
+                continue;
+            };
+            if key.kind != KeyEventKind::Press {
+                continue;
+            }
+            match self.handle_key(key.code) {
+                Action::Continue => {}
+                Action::Save => return Ok(true),
+                Action::Quit => return Ok(false),
+            }
+        }
+    }
+
+    /// Update state for a key press.
+    fn handle_key(&mut self, code: KeyCode) -> Action {
+        if let Some((field, mut buffer)) = self.editing.take() {
+            match code {
+                KeyCode::Enter => self.apply_edit(field, buffer),
+                KeyCode::Esc => self.message = "Edit cancelled".to_string(),
+                KeyCode::Backspace => {
+                    buffer.pop();
+                    self.editing = Some((field, buffer));
+                }
+                KeyCode::Char(c) => {
+                    buffer.push(c);
+                    self.editing = Some((field, buffer));
+                }
+                _ => self.editing = Some((field, buffer)),
+            }
+            return Action::Continue;
+        }
+        match code {
+            KeyCode::Char('q') | KeyCode::Esc => return Action::Quit,
+            KeyCode::Char('s') => return Action::Save,
+            KeyCode::Down | KeyCode::Char('j') => self.select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.state.select_previous(),
+            KeyCode::Char('d') | KeyCode::Delete => self.delete_selected(),
+            KeyCode::Char('a') => self.start_edit(Field::Artist),
+            KeyCode::Char('t') => self.start_edit(Field::Title),
+            KeyCode::Char('m') => self.merge_selected_with_next(),
+            KeyCode::Char('f') => self.cycle_output_format(),
+            _ => {}
+        }
+        Action::Continue
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [table_area, status_area] =
+            Layout::vertical([Constraint::Min(3), Constraint::Length(3)]).areas(frame.area());
+
+        let rows = self.tracks.iter().enumerate().map(|(index, track)| {
+            Row::new(vec![
+                (index + 1).to_string(),
+                track.artist.clone(),
+                track.title.clone(),
+                track.play_time.map_or(String::new(), utils::formatted_duration),
+            ])
+        });
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(self.tracks.len().to_string().len() as u16),
+                Constraint::Percentage(35),
+                Constraint::Percentage(55),
+                Constraint::Length(8),
+            ],
+        )
+        .header(Row::new(vec!["#", "ARTIST", "TITLE", "PLAYTIME"]).style(Style::new().add_modifier(Modifier::BOLD)))
+        .row_highlight_style(Style::new().bg(Color::DarkGray))
+        .block(Block::default().borders(Borders::ALL).title(format!(
+            " {} tracks, output: {} ",
+            self.tracks.len(),
+            self.output_format
+        )));
+        frame.render_stateful_widget(table, table_area, &mut self.state);
+
+        let status = match &self.editing {
+            Some((field, buffer)) => Line::from(format!("{field:?}: {buffer}_  (Enter to apply, Esc to cancel)")),
+            None if !self.message.is_empty() => Line::from(format!("{}  |  {HELP_TEXT}", self.message)),
+            None => Line::from(HELP_TEXT),
+        };
+        frame.render_widget(
+            Paragraph::new(status).block(Block::default().borders(Borders::ALL)),
+            status_area,
+        );
+    }
+
+    fn select_next(&mut self) {
+        if let Some(index) = self.state.selected() {
+            if index + 1 < self.tracks.len() {
+                self.state.select(Some(index + 1));
+            }
+        }
+    }
+
+    fn delete_selected(&mut self) {
+        if let Some(index) = self.state.selected().filter(|i| *i < self.tracks.len()) {
+            let track = self.tracks.remove(index);
+            self.message = format!("Deleted: {track}");
+            if index >= self.tracks.len() {
+                self.state.select(self.tracks.len().checked_sub(1));
+            }
+        }
+    }
+
+    fn start_edit(&mut self, field: Field) {
+        if let Some(track) = self.state.selected().and_then(|i| self.tracks.get(i)) {
+            let value = match field {
+                Field::Artist => track.artist.clone(),
+                Field::Title => track.title.clone(),
+            };
+            self.editing = Some((field, value));
+        }
+    }
+
+    fn apply_edit(&mut self, field: Field, value: String) {
+        if let Some(track) = self.state.selected().and_then(|i| self.tracks.get_mut(i)) {
+            match field {
+                Field::Artist => track.artist = value.trim().to_string(),
+                Field::Title => track.title = value.trim().to_string(),
+            }
+            self.message = format!("Updated: {track}");
+        }
+    }
+
+    /// Merge the next track into the selected track, combining their playtimes.
+    fn merge_selected_with_next(&mut self) {
+        let Some(index) = self.state.selected() else {
+            return;
+        };
+        if index + 1 >= self.tracks.len() {
+            return;
+        }
+        let next = self.tracks.remove(index + 1);
+        let track = &mut self.tracks[index];
+        *track += next.play_time;
+        if next.end_time.is_some() {
+            track.end_time = next.end_time;
+        }
+        self.message = format!("Merged: {next}");
+    }
+
+    fn cycle_output_format(&mut self) {
+        // Only formats that can be saved in this build
+        let formats: Vec<OutputFormat> = OutputFormat::iter()
+            .filter(|format| DEFAULT_WRITERS.for_format(format).is_ok())
+            .collect();
+        let current = formats.iter().position(|f| *f == self.output_format).unwrap_or(0);
+        self.output_format = formats[(current + 1) % formats.len()].clone();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::path::Path;
+
+    fn press(app: &mut App, keys: &str) {
+        for c in keys.chars() {
+            assert_eq!(app.handle_key(KeyCode::Char(c)), Action::Continue);
+        }
+    }
+
+    #[test]
+    fn handle_keys() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("tests")
+            .join("files")
+            .join("serato.csv");
+        let playlist = Playlist::new(&path).unwrap();
+        let mut app = App::new(&playlist, OutputFormat::Txt);
+        let count = playlist.tracks.len();
+        assert!(count > 3);
+
+        press(&mut app, "jd");
+        assert_eq!(app.tracks.len(), count - 1);
+        assert_eq!(app.tracks[1], playlist.tracks[2]);
+        assert_eq!(app.state.selected(), Some(1));
+
+        // Artist is edited starting from the current value
+        press(&mut app, "a");
+        for _ in 0..app.tracks[1].artist.chars().count() {
+            app.handle_key(KeyCode::Backspace);
+        }
+        press(&mut app, " New Artist ");
+        app.handle_key(KeyCode::Enter);
+        assert_eq!(app.tracks[1].artist, "New Artist");
+        assert_eq!(app.tracks[1].title, playlist.tracks[2].title);
+
+        // Cancelled edit keeps the title
+        press(&mut app, "tx");
+        app.handle_key(KeyCode::Esc);
+        assert!(app.editing.is_none());
+        assert_eq!(app.tracks[1].title, playlist.tracks[2].title);
+
+        press(&mut app, "m");
+        assert_eq!(app.tracks.len(), count - 2);
+
+        app.handle_key(KeyCode::Up);
+        assert_eq!(app.state.selected(), Some(0));
+
+        press(&mut app, "f");
+        assert_ne!(app.output_format, OutputFormat::Txt);
+        for _ in 0..OutputFormat::iter().count() {
+            assert!(DEFAULT_WRITERS.for_format(&app.output_format).is_ok());
+            press(&mut app, "f");
+        }
+
+        assert_eq!(app.handle_key(KeyCode::Char('s')), Action::Save);
+        assert_eq!(app.handle_key(KeyCode::Char('q')), Action::Quit);
+    }
+}
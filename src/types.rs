@@ -1,9 +1,15 @@
+use std::ffi::OsStr;
+use std::path::Path;
 use std::str::FromStr;
 
-use anyhow::anyhow;
+use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
+#[cfg(feature = "cli")]
 use clap::ValueEnum;
+use serde::Deserialize;
 use strum_macros::{Display, EnumIter};
 
+use super::error::{PlaylistError, Result};
+
 /// Playlist file type
 #[derive(Debug, Clone, PartialEq, EnumIter, Display)]
 pub enum FileFormat {
@@ -12,12 +18,245 @@ pub enum FileFormat {
 }
 
 /// Export file type
-#[derive(Debug, Clone, PartialEq, Default, EnumIter, Display, ValueEnum)]
+#[derive(Debug, Clone, PartialEq, Default, EnumIter, Display, Deserialize)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Txt,
     Csv,
     #[default]
     Xlsx,
+    M3u,
+    Json,
+}
+
+/// Input playlist file type with the DJ software it originates from
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum InputFormat {
+    SeratoCsv,
+    SeratoTxt,
+    RekordboxTxt,
+    FormattedCsv,
+}
+
+/// How duplicate tracks are handled when parsing
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum DedupePolicy {
+    /// Keep all tracks as is
+    Off,
+    /// Merge consecutive plays of the same track
+    #[default]
+    Consecutive,
+    /// Merge all plays of the same track into the first one, summing playtimes and counting plays
+    All,
+    /// Merge all plays of the same track, ignoring case, punctuation, and "original mix" suffixes
+    Fuzzy,
+}
+
+/// How to combine tracks from multiple playlists
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum MergeStrategy {
+    /// Append playlists one after another in the given order
+    #[default]
+    Concatenate,
+    /// Sort all tracks by their start time
+    Interleave,
+    /// Sort all tracks by their start time and merge the same track logged by more than one machine
+    Timestamp,
+}
+
+/// Track field to sort a playlist by
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum SortKey {
+    Artist,
+    Title,
+    Playtime,
+    StartTime,
+}
+
+/// How durations such as track playtimes are formatted
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum DurationFormat {
+    /// M:SS, or H:MM:SS for an hour or longer, and empty for zero
+    #[default]
+    Auto,
+    /// Always H:MM:SS
+    Hms,
+    /// Zero-padded HH:MM:SS
+    #[cfg_attr(feature = "cli", value(name = "hh:mm:ss"))]
+    #[strum(serialize = "hh:mm:ss")]
+    Padded,
+    /// Total number of seconds
+    Seconds,
+}
+
+/// Format for track start and end times in saved files
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum TimeFormat {
+    /// `2024.03.30 23:14:05`
+    #[default]
+    Default,
+    /// ISO 8601, for example `2024-03-30T23:14:05`
+    Iso,
+    /// Custom strftime pattern, for example `%d/%m/%Y %H:%M`
+    Custom(String),
+}
+
+impl TimeFormat {
+    /// Format a timestamp.
+    pub fn format(&self, time: NaiveDateTime) -> String {
+        match self {
+            TimeFormat::Default => time.format("%Y.%m.%d %H:%M:%S").to_string(),
+            TimeFormat::Iso => time.format("%Y-%m-%dT%H:%M:%S").to_string(),
+            TimeFormat::Custom(pattern) => time.format(pattern).to_string(),
+        }
+    }
+}
+
+/// Convert string to `TimeFormat`: either a named format or a strftime pattern.
+impl FromStr for TimeFormat {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<TimeFormat> {
+        match input.to_lowercase().trim() {
+            "default" => Ok(TimeFormat::Default),
+            "iso" | "iso8601" => Ok(TimeFormat::Iso),
+            _ => super::utils::validate_strftime(input).map(|()| TimeFormat::Custom(input.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for TimeFormat {
+    type Error = PlaylistError;
+    fn try_from(input: String) -> Result<TimeFormat> {
+        TimeFormat::from_str(&input)
+    }
+}
+
+/// Format for playlist dates
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct DateFormat(String);
+
+impl DateFormat {
+    /// Format the date part of a timestamp.
+    pub fn format(&self, date: NaiveDateTime) -> String {
+        date.format(&self.0).to_string()
+    }
+}
+
+impl Default for DateFormat {
+    fn default() -> Self {
+        Self("%Y.%m.%d".to_string())
+    }
+}
+
+/// Convert string to `DateFormat`: either a named format or a strftime pattern.
+impl FromStr for DateFormat {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<DateFormat> {
+        match input.to_lowercase().trim() {
+            "default" => Ok(DateFormat::default()),
+            "iso" | "iso8601" => Ok(DateFormat("%Y-%m-%d".to_string())),
+            _ => super::utils::validate_strftime(input).map(|()| DateFormat(input.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for DateFormat {
+    type Error = PlaylistError;
+    fn try_from(input: String) -> Result<DateFormat> {
+        DateFormat::from_str(&input)
+    }
+}
+
+/// Value shown in the totals footer after the tracks
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum FooterField {
+    /// Number of tracks
+    Tracks,
+    /// Total duration of the playlist
+    Duration,
+    /// Average track playtime
+    Average,
+}
+
+/// Web search that track names link to in terminal output
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SearchLink {
+    #[default]
+    Beatport,
+    Youtube,
+    Discogs,
+    /// URL template where `{query}` is replaced with the search terms
+    Custom(String),
+}
+
+impl SearchLink {
+    /// Get the search URL for the given search terms.
+    pub fn url(&self, query: &str) -> String {
+        let template = match self {
+            SearchLink::Beatport => "https://www.beatport.com/search?q={query}",
+            SearchLink::Youtube => "https://www.youtube.com/results?search_query={query}",
+            SearchLink::Discogs => "https://www.discogs.com/search/?q={query}&type=all",
+            SearchLink::Custom(template) => template,
+        };
+        template.replace("{query}", &super::utils::url_encode(query))
+    }
+}
+
+/// Convert string to `SearchLink`: either a named site or a URL template containing `{query}`.
+impl FromStr for SearchLink {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<SearchLink> {
+        let input = input.trim();
+        match input.to_lowercase().as_str() {
+            "beatport" => Ok(SearchLink::Beatport),
+            "youtube" => Ok(SearchLink::Youtube),
+            "discogs" => Ok(SearchLink::Discogs),
+            lower if (lower.starts_with("https://") || lower.starts_with("http://")) && input.contains("{query}") => {
+                Ok(SearchLink::Custom(input.to_string()))
+            }
+            _ => Err(PlaylistError::InvalidArgument(format!(
+                "Invalid search link '{input}': use beatport, youtube, discogs, or a URL containing {{query}}"
+            ))),
+        }
+    }
+}
+
+impl TryFrom<String> for SearchLink {
+    type Error = PlaylistError;
+    fn try_from(input: String) -> Result<SearchLink> {
+        SearchLink::from_str(&input)
+    }
+}
+
+/// Notation for musical keys
+#[derive(Debug, Clone, Copy, PartialEq, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum KeyNotation {
+    /// Standard notation like F#m
+    Standard,
+    /// Camelot wheel notation like 11A
+    Camelot,
+}
+
+/// How transliterated artist names are shown
+#[derive(Debug, Clone, Copy, PartialEq, Default, EnumIter, Display)]
+#[cfg_attr(feature = "cli", derive(ValueEnum))]
+pub enum TransliterateMode {
+    /// Add the romanized name in parentheses after the original
+    #[default]
+    Append,
+    /// Replace the original name with the romanized name
+    Replace,
 }
 
 /// Which DJ software is the playlist from.
@@ -33,27 +272,215 @@ pub enum PlaylistType {
 
 /// Convert string to `FileFormat` enum
 impl FromStr for FileFormat {
-    type Err = anyhow::Error;
-    fn from_str(input: &str) -> anyhow::Result<FileFormat> {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<FileFormat> {
         match input.to_lowercase().trim() {
             "csv" => Ok(FileFormat::Csv),
             "txt" => Ok(FileFormat::Txt),
-            "" => Err(anyhow!("Can't convert empty string to file format")),
-            _ => Err(anyhow!("Unsupported file format: '{input}'")),
+            "" => Err(PlaylistError::UnsupportedFormat(
+                "Can't convert empty string to file format".to_string(),
+            )),
+            _ => Err(PlaylistError::UnsupportedFormat(format!(
+                "Unsupported file format: '{input}'"
+            ))),
         }
     }
 }
 
 /// Convert string to `OutputFormat` enum
 impl FromStr for OutputFormat {
-    type Err = anyhow::Error;
-    fn from_str(input: &str) -> anyhow::Result<OutputFormat> {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<OutputFormat> {
         match input.to_lowercase().trim() {
             "csv" => Ok(OutputFormat::Csv),
             "txt" => Ok(OutputFormat::Txt),
             "xlsx" => Ok(OutputFormat::Xlsx),
-            "" => Err(anyhow!("Can't convert empty string to file format")),
-            _ => Err(anyhow!("Unsupported file format: '{input}'")),
+            "m3u" => Ok(OutputFormat::M3u),
+            "json" => Ok(OutputFormat::Json),
+            "" => Err(PlaylistError::UnsupportedFormat(
+                "Can't convert empty string to file format".to_string(),
+            )),
+            _ => Err(PlaylistError::UnsupportedFormat(format!(
+                "Unsupported file format: '{input}'"
+            ))),
+        }
+    }
+}
+
+impl InputFormat {
+    /// Get the file format for this input format.
+    pub const fn file_format(&self) -> FileFormat {
+        match self {
+            InputFormat::SeratoCsv | InputFormat::FormattedCsv => FileFormat::Csv,
+            InputFormat::SeratoTxt | InputFormat::RekordboxTxt => FileFormat::Txt,
+        }
+    }
+
+    /// Get the playlist type for this input format.
+    pub const fn playlist_type(&self) -> PlaylistType {
+        match self {
+            InputFormat::SeratoCsv | InputFormat::SeratoTxt => PlaylistType::Serato,
+            InputFormat::RekordboxTxt => PlaylistType::Rekordbox,
+            InputFormat::FormattedCsv => PlaylistType::Formatted,
+        }
+    }
+}
+
+/// Position to split a playlist at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitPoint {
+    /// Track number starting a new part, counting from one
+    Track(usize),
+    /// Elapsed time from the start of the playlist
+    Elapsed(TimeDelta),
+}
+
+/// Convert string to `SplitPoint`: a plain number is a track number, anything else an elapsed time.
+impl FromStr for SplitPoint {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<SplitPoint> {
+        let input = input.trim();
+        if let Ok(number) = input.parse::<usize>() {
+            return match number {
+                0 => Err(PlaylistError::InvalidArgument("Track numbers start from 1".to_string())),
+                _ => Ok(SplitPoint::Track(number)),
+            };
+        }
+        super::utils::parse_duration(input).map(SplitPoint::Elapsed)
+    }
+}
+
+/// New artist and title for a track, parsed from `N:Artist - Title`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackRename {
+    /// Track number counting from one
+    pub number: usize,
+    pub artist: String,
+    pub title: String,
+}
+
+impl FromStr for TrackRename {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<TrackRename> {
+        let (number, track) = input
+            .split_once(':')
+            .ok_or_else(|| PlaylistError::InvalidArgument(format!("Expected 'N:Artist - Title', got: '{input}'")))?;
+        let number: usize = number
+            .trim()
+            .parse()
+            .map_err(|_| PlaylistError::InvalidArgument(format!("Invalid track number: '{}'", number.trim())))?;
+        if number == 0 {
+            return Err(PlaylistError::InvalidArgument("Track numbers start from 1".to_string()));
+        }
+        let (artist, title) = track.split_once(" - ").ok_or_else(|| {
+            PlaylistError::InvalidArgument(format!("Expected 'Artist - Title', got: '{}'", track.trim()))
+        })?;
+        let (artist, title) = (artist.trim(), title.trim());
+        if artist.is_empty() || title.is_empty() {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "Artist and title can't be empty: '{input}'"
+            )));
+        }
+        Ok(TrackRename {
+            number,
+            artist: artist.to_string(),
+            title: title.to_string(),
+        })
+    }
+}
+
+/// Position in a playlist given as a clock time or as elapsed time from the start
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimePosition {
+    /// Time of day, for example `22:30`
+    Clock(NaiveTime),
+    /// Elapsed time from the start of the playlist, for example `+1:30` or `90m`
+    Elapsed(TimeDelta),
+}
+
+/// Convert string to `TimePosition`.
+///
+/// A leading `+` or a unit suffix means elapsed time, otherwise the input is parsed as a clock time.
+impl FromStr for TimePosition {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<TimePosition> {
+        let input = input.trim();
+        if let Some(elapsed) = input.strip_prefix('+') {
+            return super::utils::parse_duration(elapsed).map(TimePosition::Elapsed);
+        }
+        if input.ends_with(['h', 'm', 's']) {
+            return super::utils::parse_duration(input).map(TimePosition::Elapsed);
+        }
+        super::utils::parse_clock_time(input).map(TimePosition::Clock)
+    }
+}
+
+/// Part of a back-to-back set played by one DJ, parsed from `Name=HH:MM-HH:MM` or `Name=DECK`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DjAssignment {
+    pub name: String,
+    pub slot: DjSlot,
+}
+
+/// Time range or deck played by a DJ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DjSlot {
+    /// Tracks starting from the start time until before the end time, which may be past midnight
+    Time { start: NaiveTime, end: NaiveTime },
+    /// Tracks played from the given Serato deck
+    Deck(u8),
+}
+
+impl DjSlot {
+    /// Check if a track with the given start time and deck belongs to this slot.
+    pub fn contains(&self, start_time: Option<NaiveDateTime>, deck: Option<u8>) -> bool {
+        match *self {
+            DjSlot::Time { start, end } => start_time.is_some_and(|time| {
+                let time = time.time();
+                if start <= end {
+                    time >= start && time < end
+                } else {
+                    time >= start || time < end
+                }
+            }),
+            DjSlot::Deck(number) => deck == Some(number),
+        }
+    }
+}
+
+impl FromStr for DjAssignment {
+    type Err = PlaylistError;
+    fn from_str(input: &str) -> Result<DjAssignment> {
+        let (name, slot) = input.split_once('=').ok_or_else(|| {
+            PlaylistError::InvalidArgument(format!("Expected 'Name=HH:MM-HH:MM' or 'Name=DECK', got: '{input}'"))
+        })?;
+        let (name, slot) = (name.trim(), slot.trim());
+        if name.is_empty() {
+            return Err(PlaylistError::InvalidArgument(format!(
+                "DJ name can't be empty: '{input}'"
+            )));
+        }
+        let slot = match slot.split_once('-') {
+            Some((start, end)) => DjSlot::Time {
+                start: super::utils::parse_clock_time(start)?,
+                end: super::utils::parse_clock_time(end)?,
+            },
+            None => DjSlot::Deck(slot.parse().map_err(|_| {
+                PlaylistError::InvalidArgument(format!("Expected a time range or deck number, got: '{slot}'"))
+            })?),
+        };
+        Ok(DjAssignment {
+            name: name.to_string(),
+            slot,
+        })
+    }
+}
+
+impl std::fmt::Display for SplitPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SplitPoint::Track(number) => write!(f, "track {number}"),
+            SplitPoint::Elapsed(time) => write!(f, "{}", super::utils::formatted_duration(*time)),
         }
     }
 }
@@ -62,13 +489,36 @@ impl OutputFormat {
     pub fn to_extension(&self) -> String {
         self.to_string().to_lowercase()
     }
+
+    /// Get the output format from the file extension of the path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        path.extension()
+            .and_then(OsStr::to_str)
+            .and_then(|extension| extension.parse().ok())
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{FileFormat, OutputFormat};
+    use super::{
+        DateFormat, DjAssignment, DjSlot, FileFormat, InputFormat, OutputFormat, PlaylistType, SearchLink, SplitPoint,
+        TimeFormat, TimePosition, TrackRename,
+    };
+    use chrono::{NaiveDateTime, NaiveTime, TimeDelta};
     use std::str::FromStr;
 
+    #[test]
+    fn search_link_urls() {
+        assert_eq!(
+            SearchLink::from_str("YouTube").unwrap().url("Artist - Title & More"),
+            "https://www.youtube.com/results?search_query=Artist%20-%20Title%20%26%20More"
+        );
+        let custom = SearchLink::from_str("https://example.com/find?term={query}").unwrap();
+        assert_eq!(custom.url("Röyksopp"), "https://example.com/find?term=R%C3%B6yksopp");
+        assert!(SearchLink::from_str("example.com").is_err());
+        assert!(SearchLink::from_str("https://example.com/").is_err());
+    }
+
     #[test]
     fn file_format_valid_format() {
         assert_eq!(FileFormat::from_str("csv").unwrap(), FileFormat::Csv);
@@ -111,5 +561,131 @@ mod tests {
         assert_eq!(OutputFormat::from_str("TXT").unwrap(), OutputFormat::Txt);
         assert_eq!(OutputFormat::from_str("xlsx").unwrap(), OutputFormat::Xlsx);
         assert_eq!(OutputFormat::from_str("XLSX").unwrap(), OutputFormat::Xlsx);
+        assert_eq!(OutputFormat::from_str("m3u").unwrap(), OutputFormat::M3u);
+        assert_eq!(OutputFormat::from_str("JSON").unwrap(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn input_format() {
+        assert_eq!(InputFormat::SeratoCsv.file_format(), FileFormat::Csv);
+        assert_eq!(InputFormat::SeratoTxt.playlist_type(), PlaylistType::Serato);
+        assert_eq!(InputFormat::RekordboxTxt.file_format(), FileFormat::Txt);
+        assert_eq!(InputFormat::FormattedCsv.playlist_type(), PlaylistType::Formatted);
+    }
+
+    #[test]
+    fn split_point_from_str() {
+        assert_eq!(SplitPoint::from_str("12").unwrap(), SplitPoint::Track(12));
+        assert_eq!(
+            SplitPoint::from_str("2:00:00").unwrap(),
+            SplitPoint::Elapsed(TimeDelta::hours(2))
+        );
+        assert_eq!(
+            SplitPoint::from_str("90m").unwrap(),
+            SplitPoint::Elapsed(TimeDelta::minutes(90))
+        );
+        assert!(SplitPoint::from_str("0").is_err());
+        assert!(SplitPoint::from_str("soon").is_err());
+    }
+
+    #[test]
+    fn time_position_from_str() {
+        assert_eq!(
+            TimePosition::from_str("22:30").unwrap(),
+            TimePosition::Clock(NaiveTime::from_hms_opt(22, 30, 0).unwrap())
+        );
+        assert_eq!(
+            TimePosition::from_str("01:15:30").unwrap(),
+            TimePosition::Clock(NaiveTime::from_hms_opt(1, 15, 30).unwrap())
+        );
+        assert_eq!(
+            TimePosition::from_str("+1:30").unwrap(),
+            TimePosition::Elapsed(TimeDelta::minutes(90))
+        );
+        assert_eq!(
+            TimePosition::from_str("45m").unwrap(),
+            TimePosition::Elapsed(TimeDelta::minutes(45))
+        );
+        assert!(TimePosition::from_str("25:00").is_err());
+        assert!(TimePosition::from_str("+later").is_err());
+    }
+
+    #[test]
+    fn time_format_from_str() {
+        let time = NaiveDateTime::new(
+            chrono::NaiveDate::from_ymd_opt(2024, 3, 30).unwrap(),
+            NaiveTime::from_hms_opt(23, 14, 5).unwrap(),
+        );
+        assert_eq!(TimeFormat::from_str("iso").unwrap().format(time), "2024-03-30T23:14:05");
+        assert_eq!(TimeFormat::from_str("ISO").unwrap(), TimeFormat::Iso);
+        assert_eq!(TimeFormat::default().format(time), "2024.03.30 23:14:05");
+        assert_eq!(TimeFormat::from_str("%d/%m %H:%M").unwrap().format(time), "30/03 23:14");
+        assert!(TimeFormat::from_str("unix").is_err());
+        assert!(TimeFormat::from_str("%Q").is_err());
+    }
+
+    #[test]
+    fn date_format_from_str() {
+        let date = chrono::NaiveDate::from_ymd_opt(2024, 3, 30)
+            .unwrap()
+            .and_hms_opt(23, 14, 5)
+            .unwrap();
+        assert_eq!(DateFormat::default().format(date), "2024.03.30");
+        assert_eq!(DateFormat::from_str("iso").unwrap().format(date), "2024-03-30");
+        assert_eq!(DateFormat::from_str("%d.%m.%Y").unwrap().format(date), "30.03.2024");
+        assert!(DateFormat::from_str("today").is_err());
+    }
+
+    #[test]
+    fn track_rename_from_str() {
+        assert_eq!(
+            TrackRename::from_str("3:Some Artist - New Title (Remix)").unwrap(),
+            TrackRename {
+                number: 3,
+                artist: "Some Artist".to_string(),
+                title: "New Title (Remix)".to_string(),
+            }
+        );
+        assert_eq!(TrackRename::from_str(" 12 : A - B - C").unwrap().title, "B - C");
+        assert!(TrackRename::from_str("Artist - Title").is_err());
+        assert!(TrackRename::from_str("0:Artist - Title").is_err());
+        assert!(TrackRename::from_str("1:Artist").is_err());
+        assert!(TrackRename::from_str("1: - Title").is_err());
+    }
+
+    #[test]
+    fn dj_assignment_from_str() {
+        let time = |text: &str| NaiveTime::parse_from_str(text, "%H:%M").unwrap();
+        assert_eq!(
+            DjAssignment::from_str("A=22:00-23:00").unwrap(),
+            DjAssignment {
+                name: "A".to_string(),
+                slot: DjSlot::Time {
+                    start: time("22:00"),
+                    end: time("23:00"),
+                },
+            }
+        );
+        assert_eq!(DjAssignment::from_str("DJ B = 2").unwrap().slot, DjSlot::Deck(2));
+        assert!(DjAssignment::from_str("A").is_err());
+        assert!(DjAssignment::from_str("=1").is_err());
+        assert!(DjAssignment::from_str("A=late").is_err());
+        assert!(DjAssignment::from_str("A=22:00-25:00").is_err());
+    }
+
+    #[test]
+    fn dj_slot_contains() {
+        let time = |text: &str| NaiveTime::parse_from_str(text, "%H:%M").unwrap();
+        let at = |text: &str| Some(NaiveDateTime::new(chrono::NaiveDate::default(), time(text)));
+        let late = DjSlot::Time {
+            start: time("23:00"),
+            end: time("0:00"),
+        };
+        assert!(late.contains(at("23:30"), None));
+        assert!(!late.contains(at("22:59"), None));
+        assert!(!late.contains(at("0:00"), None));
+        assert!(!late.contains(None, Some(1)));
+        assert!(DjSlot::Deck(1).contains(None, Some(1)));
+        assert!(!DjSlot::Deck(1).contains(at("23:30"), Some(2)));
     }
 }
@@ -1,19 +1,26 @@
+use std::collections::HashMap;
 use std::ffi::OsStr;
 use std::ffi::OsString;
+#[cfg(feature = "cli")]
+use std::io::IsTerminal;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::string::String;
 use std::sync::LazyLock;
 
-use anyhow::Context;
-use anyhow::Result;
-use chrono::{NaiveDate, NaiveDateTime, TimeDelta};
+use chrono::format::{Item, StrftimeItems};
+#[cfg(feature = "fs")]
+use chrono::{DateTime, Local};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
+#[cfg(feature = "fs")]
 use home::home_dir;
 use regex::Regex;
 use strum::IntoEnumIterator;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+use super::error::{PlaylistError, Result};
 use super::track::Track;
-use super::types::FileFormat;
+use super::types::{DedupePolicy, DurationFormat, FileFormat};
 
 static RE_DD_MM_YYYY: LazyLock<Regex> = LazyLock::new(|| {
     Regex::new(r"(\d{1,2})\.(\d{1,2})\.(\d{4})").expect("Failed to create regex pattern for dd.mm.yyyy")
@@ -32,6 +39,235 @@ pub fn append_extension_to_path(path: PathBuf, extension: impl AsRef<OsStr>) ->
     os_string.into()
 }
 
+/// Remove duplicate tracks according to the given policy.
+///
+/// Playtime of a removed duplicate is added to the track that is kept.
+/// For consecutive duplicates the end time is also taken from the removed track.
+pub fn dedupe_tracks(tracks: Vec<Track>, policy: DedupePolicy) -> Vec<Track> {
+    match policy {
+        DedupePolicy::Off => tracks,
+        DedupePolicy::Consecutive => {
+            let mut deduped_tracks: Vec<Track> = Vec::new();
+            for track in tracks {
+                if let Some(last_track) = deduped_tracks.last_mut() {
+                    if *last_track == track {
+                        merge_duplicate_track(last_track, &track, true);
+                        continue;
+                    }
+                }
+                deduped_tracks.push(track);
+            }
+            deduped_tracks
+        }
+        DedupePolicy::All => dedupe_tracks_by_key(tracks, |t| format!("{}|{}", t.artist, t.title)),
+        DedupePolicy::Fuzzy => dedupe_tracks_by_key(tracks, Track::fuzzy_key),
+    }
+}
+
+/// Check track playtime against the minimum playtime.
+/// Tracks without playtime info are kept.
+pub fn is_long_enough(track: &Track, min_playtime: Option<TimeDelta>) -> bool {
+    match (track.play_time, min_playtime) {
+        (Some(play_time), Some(min_playtime)) => {
+            if play_time < min_playtime {
+                tracing::debug!("Skipping short track ({}s): {track}", play_time.num_seconds());
+                return false;
+            }
+            true
+        }
+        _ => true,
+    }
+}
+
+/// Merge all tracks with the same key into the first occurrence.
+fn dedupe_tracks_by_key(tracks: Vec<Track>, key: impl Fn(&Track) -> String) -> Vec<Track> {
+    let mut deduped_tracks: Vec<Track> = Vec::new();
+    let mut indices: HashMap<String, usize> = HashMap::new();
+    for track in tracks {
+        let track_key = key(&track);
+        if let Some(&index) = indices.get(&track_key) {
+            let consecutive = index + 1 == deduped_tracks.len();
+            merge_duplicate_track(&mut deduped_tracks[index], &track, consecutive);
+            continue;
+        }
+        indices.insert(track_key, deduped_tracks.len());
+        deduped_tracks.push(track);
+    }
+    deduped_tracks
+}
+
+/// Merge tracks that are played again within the given time after the earlier play of the same track ended,
+/// for example when a track was pulled off and brought back.
+///
+/// Other tracks may sit between the plays. Tracks without start or end times are kept as is.
+pub fn merge_reloads(tracks: Vec<Track>, window: TimeDelta) -> Vec<Track> {
+    let mut merged_tracks: Vec<Track> = Vec::new();
+    for track in tracks {
+        let earlier = track.start_time.and_then(|start| {
+            merged_tracks.iter().rposition(|kept| {
+                *kept == track
+                    && kept
+                        .end_time
+                        .is_some_and(|end| start >= end - window && start - end <= window)
+            })
+        });
+        match earlier {
+            Some(index) => {
+                let kept = &mut merged_tracks[index];
+                tracing::debug!("Merging reloaded track: {kept}");
+                *kept += track.play_time;
+                kept.end_time = kept.end_time.max(track.end_time);
+            }
+            None => merged_tracks.push(track),
+        }
+    }
+    merged_tracks
+}
+
+/// Merge the same track logged by more than one machine, for example in a back-to-back set
+/// where both laptops record the history.
+///
+/// Tracks must be sorted by start time. A track is merged into an earlier entry of the same track
+/// that started at most the given time before it. The merged entry covers both plays,
+/// and metadata missing from the kept entry is taken from the duplicate.
+pub fn merge_logged_duplicates(tracks: Vec<Track>, window: TimeDelta) -> Vec<Track> {
+    let mut merged_tracks: Vec<Track> = Vec::new();
+    for track in tracks {
+        let earlier = track.start_time.and_then(|start| {
+            merged_tracks.iter().rposition(|kept| {
+                *kept == track && kept.start_time.is_some_and(|kept_start| start - kept_start <= window)
+            })
+        });
+        let Some(index) = earlier else {
+            merged_tracks.push(track);
+            continue;
+        };
+        let kept = &mut merged_tracks[index];
+        tracing::debug!("Merging track logged twice: {kept}");
+        kept.end_time = kept.end_time.max(track.end_time);
+        kept.play_time = kept.play_time.max(track.play_time);
+        kept.bpm = kept.bpm.or(track.bpm);
+        kept.key = kept.key.take().or(track.key);
+        kept.genre = kept.genre.take().or(track.genre);
+        kept.deck = kept.deck.or(track.deck);
+        kept.notes = kept.notes.take().or(track.notes);
+    }
+    merged_tracks
+}
+
+/// Collect secondary tracks played on top of another track as mashups of that track.
+///
+/// Mashups given with `w/` in the title are split from the title,
+/// and a track played entirely within the previous track's play time is moved to its mashups.
+pub fn merge_mashups(tracks: Vec<Track>) -> Vec<Track> {
+    let mut merged_tracks: Vec<Track> = Vec::new();
+    for mut track in tracks {
+        track.split_mashups();
+        let inside_previous = merged_tracks.last().is_some_and(|previous| {
+            match (previous.start_time, previous.end_time, track.start_time, track.end_time) {
+                (Some(previous_start), Some(previous_end), Some(start), Some(end)) => {
+                    start >= previous_start && end <= previous_end
+                }
+                _ => false,
+            }
+        });
+        match merged_tracks.last_mut() {
+            Some(previous) if inside_previous => {
+                tracing::debug!("Mashup: {track} on top of {previous}");
+                let mashups = std::mem::take(&mut track.mashups);
+                previous.mashups.push(track);
+                previous.mashups.extend(mashups);
+            }
+            _ => merged_tracks.push(track),
+        }
+    }
+    merged_tracks
+}
+
+fn merge_duplicate_track(kept: &mut Track, duplicate: &Track, consecutive: bool) {
+    // Add playtime of duplicate track to the kept one
+    *kept += duplicate.play_time;
+    if consecutive && duplicate.end_time.is_some() {
+        kept.end_time = duplicate.end_time;
+    } else if !consecutive {
+        // A consecutive duplicate continues the same play, other tracks in between mean it was played again
+        kept.plays += duplicate.plays;
+    }
+}
+
+/// Get a path that does not exist yet by adding a number to the file name if needed,
+/// for example `playlist (2).csv`.
+#[cfg(feature = "fs")]
+pub fn next_available_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_string());
+    let mut number = 2;
+    loop {
+        let file_name = match &extension {
+            Some(ext) => format!("{stem} ({number}).{ext}"),
+            None => format!("{stem} ({number})"),
+        };
+        let candidate = path.with_file_name(file_name);
+        if !candidate.exists() {
+            return candidate;
+        }
+        number += 1;
+    }
+}
+
+/// Move an existing file to a backup file before it is overwritten.
+///
+/// Keeping a single backup uses `<file>.bak`, otherwise timestamped backups
+/// `<file>.<YYYYmmdd-HHMMSS>.bak` are created and only the newest `keep` backups are retained.
+#[cfg(feature = "fs")]
+pub fn backup_file(path: &Path, keep: usize) -> Result<PathBuf> {
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| PlaylistError::InvalidArgument(format!("Backup path has no file name: '{}'", path.display())))?
+        .to_string_lossy()
+        .to_string();
+    let backup_path = if keep <= 1 {
+        path.with_file_name(format!("{file_name}.bak"))
+    } else {
+        let timestamp = Local::now().format("%Y%m%d-%H%M%S");
+        next_available_path(&path.with_file_name(format!("{file_name}.{timestamp}.bak")))
+    };
+    std::fs::rename(path, &backup_path)
+        .map_err(|e| PlaylistError::io(format!("Failed to create backup: '{}'", backup_path.display()), e))?;
+
+    if keep > 1 {
+        if let Some(dir) = path.parent().filter(|p| p.is_dir()) {
+            let prefix = format!("{file_name}.");
+            let single_backup = format!("{file_name}.bak");
+            let mut backups: Vec<PathBuf> = std::fs::read_dir(dir)
+                .map_err(|e| PlaylistError::io(format!("Failed to read directory: '{}'", dir.display()), e))?
+                .filter_map(|entry| entry.ok().map(|e| e.path()))
+                .filter(|p| {
+                    p.file_name().map(|name| name.to_string_lossy()).is_some_and(|name| {
+                        name.starts_with(&prefix) && name.ends_with(".bak") && name != single_backup
+                    })
+                })
+                .collect();
+            // Timestamps sort chronologically, so the oldest backups come first.
+            // Numbered backups from the same second are longer than the first one.
+            backups.sort_by_key(|p| (p.as_os_str().len(), p.clone()));
+            for old_backup in backups.iter().take(backups.len().saturating_sub(keep)) {
+                tracing::debug!("Removing old backup: {}", old_backup.display());
+                std::fs::remove_file(old_backup).map_err(|e| {
+                    PlaylistError::io(format!("Failed to remove old backup: '{}'", old_backup.display()), e)
+                })?;
+            }
+        }
+    }
+    Ok(backup_path)
+}
+
 /// Get total playtime for a list of tracks
 pub fn get_total_playtime(tracks: &[Track]) -> Option<TimeDelta> {
     let mut sum = TimeDelta::try_seconds(0)?;
@@ -63,6 +299,238 @@ pub fn formatted_duration(duration: TimeDelta) -> String {
     }
 }
 
+/// Format duration using the given format.
+///
+/// Unlike the automatic format, the explicit formats also show zero durations.
+pub fn format_duration(duration: TimeDelta, format: DurationFormat) -> String {
+    let seconds = duration.num_seconds().max(0);
+    match format {
+        DurationFormat::Auto => formatted_duration(duration),
+        DurationFormat::Hms => format!("{}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60),
+        DurationFormat::Padded => format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60),
+        DurationFormat::Seconds => seconds.to_string(),
+    }
+}
+
+/// Round duration to the nearest multiple of the given step.
+///
+/// Non-zero durations are rounded to at least one step.
+pub fn round_duration(duration: TimeDelta, step: TimeDelta) -> TimeDelta {
+    let step_seconds = step.num_seconds();
+    if step_seconds <= 1 {
+        return duration;
+    }
+    let seconds = duration.num_seconds();
+    let rounded = ((seconds + step_seconds / 2) / step_seconds * step_seconds).max(step_seconds);
+    if seconds > 0 {
+        TimeDelta::try_seconds(rounded).unwrap_or(duration)
+    } else {
+        duration
+    }
+}
+
+/// Parse a signed time offset given as `[-]MM:SS` or `[-]H:MM:SS`.
+pub fn parse_offset(input: &str) -> Result<TimeDelta> {
+    let input = input.trim();
+    let invalid = || PlaylistError::ParseDate(format!("Invalid offset, expected [-]MM:SS: '{input}'"));
+    let (sign, value) = match input.strip_prefix('-') {
+        Some(value) => (-1, value),
+        None => (1, input.strip_prefix('+').unwrap_or(input)),
+    };
+    let parts = value
+        .split(':')
+        .map(|part| part.trim().parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>>>()?;
+    let seconds = match parts.as_slice() {
+        [minutes, seconds] if *seconds < 60 => i64::from(*minutes) * 60 + i64::from(*seconds),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            i64::from(*hours) * 3600 + i64::from(*minutes) * 60 + i64::from(*seconds)
+        }
+        _ => return Err(invalid()),
+    };
+    TimeDelta::try_seconds(sign * seconds).ok_or_else(invalid)
+}
+
+/// Check that a strftime pattern is valid and contains at least one format specifier.
+pub fn validate_strftime(pattern: &str) -> Result<()> {
+    let items: Vec<Item> = StrftimeItems::new(pattern).collect();
+    if items.iter().any(|item| matches!(item, Item::Error)) {
+        return Err(PlaylistError::ParseDate(format!(
+            "Invalid strftime pattern: '{pattern}'"
+        )));
+    }
+    if !items
+        .iter()
+        .any(|item| matches!(item, Item::Numeric(..) | Item::Fixed(_)))
+    {
+        return Err(PlaylistError::ParseDate(format!(
+            "Expected a named format or a strftime pattern like '%Y-%m-%d', got: '{pattern}'"
+        )));
+    }
+    Ok(())
+}
+
+/// Format a BPM value without decimals when it is a whole number.
+pub fn formatted_bpm(bpm: f64) -> String {
+    if bpm.fract() == 0.0 {
+        format!("{bpm:.0}")
+    } else {
+        format!("{bpm:.2}")
+    }
+}
+
+/// Format elapsed time from the start of a set as `MM:SS`, or `H:MM:SS` from one hour on.
+pub fn formatted_elapsed(elapsed: TimeDelta) -> String {
+    let seconds = elapsed.num_seconds().max(0);
+    if seconds >= 3600 {
+        format!("{}:{:02}:{:02}", seconds / 3600, seconds % 3600 / 60, seconds % 60)
+    } else {
+        format!("{:02}:{:02}", seconds / 60, seconds % 60)
+    }
+}
+
+/// Parse a duration given as `H:MM:SS`, `H:MM`, or a number with an `h`, `m`, or `s` suffix.
+pub fn parse_duration(input: &str) -> Result<TimeDelta> {
+    let input = input.trim();
+    let invalid = || PlaylistError::ParseDate(format!("Invalid duration: '{input}'"));
+    let seconds: i64 = if input.contains(':') {
+        let parts = input
+            .split(':')
+            .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>>>()?;
+        match parts.as_slice() {
+            [hours, minutes] if *minutes < 60 => i64::from(*hours) * 3600 + i64::from(*minutes) * 60,
+            [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+                i64::from(*hours) * 3600 + i64::from(*minutes) * 60 + i64::from(*seconds)
+            }
+            _ => return Err(invalid()),
+        }
+    } else {
+        let (number, multiplier) = match input.char_indices().last() {
+            Some((index, 'h')) => (&input[..index], 3600),
+            Some((index, 'm')) => (&input[..index], 60),
+            Some((index, 's')) => (&input[..index], 1),
+            _ => return Err(invalid()),
+        };
+        number.trim().parse::<u32>().map_err(|_| invalid())? as i64 * multiplier
+    };
+    TimeDelta::try_seconds(seconds).ok_or_else(invalid)
+}
+
+/// Header names that mean the same thing in different playlist exports.
+const HEADER_SYNONYMS: [&[&str]; 2] = [
+    &["name", "title", "tracktitle", "song"],
+    &["artist", "artists", "performer"],
+];
+
+/// Create an error for a missing required column, listing the headers that were found
+/// and suggesting the closest one if there is a plausible match.
+pub fn missing_field_error<'a>(
+    source: &str,
+    field: &str,
+    headers: impl IntoIterator<Item = &'a String>,
+) -> PlaylistError {
+    let found: Vec<String> = headers.into_iter().filter(|h| !h.is_empty()).cloned().collect();
+    let candidates: Vec<&str> = found.iter().map(String::as_str).collect();
+    PlaylistError::MissingField {
+        kind: source.to_string(),
+        field: field.to_string(),
+        suggestion: suggest_header(field, &candidates).map(str::to_string),
+        found,
+    }
+}
+
+/// Find the header most likely meant as the given field.
+///
+/// Headers are compared ignoring case, whitespace, and punctuation,
+/// and match if they are known synonyms, contain one another, or differ by a small edit distance.
+pub fn suggest_header<'a>(field: &str, headers: &[&'a str]) -> Option<&'a str> {
+    let simplify = |text: &str| -> String {
+        text.chars()
+            .filter(|c| c.is_alphanumeric())
+            .flat_map(char::to_lowercase)
+            .collect()
+    };
+    let field = simplify(field);
+    if field.is_empty() {
+        return None;
+    }
+    let synonyms: &[&str] = HEADER_SYNONYMS
+        .iter()
+        .find(|group| group.contains(&field.as_str()))
+        .copied()
+        .unwrap_or_default();
+    let max_distance = (field.chars().count() / 3).max(1);
+    headers
+        .iter()
+        .filter_map(|&header| {
+            let simple = simplify(header);
+            if simple.is_empty() {
+                return None;
+            }
+            let score = if simple == field {
+                0
+            } else if synonyms.contains(&simple.as_str()) {
+                1
+            } else if simple.contains(&field) || field.contains(&simple) {
+                2
+            } else {
+                let distance = edit_distance(&simple, &field);
+                if distance > max_distance {
+                    return None;
+                }
+                2 + distance
+            };
+            Some((score, header))
+        })
+        .min_by_key(|(score, _)| *score)
+        .map(|(_, header)| header)
+}
+
+/// Levenshtein distance between two strings counted in characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Parse a track playtime written as `M:SS`, `H:MM:SS`, or a number of seconds.
+///
+/// This is the inverse of [`format_duration`] for all duration formats.
+pub fn parse_playtime(input: &str) -> Result<TimeDelta> {
+    let input = input.trim();
+    let invalid = || PlaylistError::ParseDate(format!("Invalid playtime: '{input}'"));
+    let parts = input
+        .split(':')
+        .map(|part| part.parse::<u32>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>>>()?;
+    let seconds = match parts.as_slice() {
+        [seconds] => i64::from(*seconds),
+        [minutes, seconds] if *seconds < 60 => i64::from(*minutes) * 60 + i64::from(*seconds),
+        [hours, minutes, seconds] if *minutes < 60 && *seconds < 60 => {
+            i64::from(*hours) * 3600 + i64::from(*minutes) * 60 + i64::from(*seconds)
+        }
+        _ => return Err(invalid()),
+    };
+    TimeDelta::try_seconds(seconds).ok_or_else(invalid)
+}
+
+/// Parse a time of day given as `HH:MM:SS` or `HH:MM`.
+pub fn parse_clock_time(input: &str) -> Result<NaiveTime> {
+    let input = input.trim();
+    NaiveTime::parse_from_str(input, "%H:%M:%S")
+        .or_else(|_| NaiveTime::parse_from_str(input, "%H:%M"))
+        .map_err(|_| PlaylistError::ParseDate(format!("Invalid time: '{input}'")))
+}
+
 pub fn extract_datetime_from_name(input: &str) -> Option<NaiveDateTime> {
     if let Some(caps) = RE_DD_MM_YYYY.captures(input) {
         let day = caps.get(1)?.as_str().parse::<u32>().ok()?;
@@ -81,7 +549,15 @@ pub fn extract_datetime_from_name(input: &str) -> Option<NaiveDateTime> {
     None
 }
 
+/// Get file modification time as a local datetime.
+#[cfg(feature = "fs")]
+pub fn file_modified_datetime(path: &Path) -> Option<NaiveDateTime> {
+    let modified = path.metadata().and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Local>::from(modified).naive_local())
+}
+
 /// Get DJ playlist directory path in Dropbox if it exists
+#[cfg(feature = "fs")]
 pub fn dropbox_save_dir() -> Option<PathBuf> {
     let path = if cfg!(target_os = "windows") {
         Some(dunce::simplified(Path::new("D:\\Dropbox\\DJ\\PLAYLIST")).to_path_buf())
@@ -94,6 +570,104 @@ pub fn dropbox_save_dir() -> Option<PathBuf> {
     path.filter(|p| p.is_dir())
 }
 
+/// Get Serato library directory path if it exists
+#[cfg(feature = "fs")]
+pub fn serato_dir() -> Option<PathBuf> {
+    home_dir()
+        .map(|home| home.join("Music").join("_Serato_"))
+        .filter(|p| p.is_dir())
+}
+
+/// Get Rekordbox settings directory path if it exists
+#[cfg(feature = "fs")]
+pub fn rekordbox_dir() -> Option<PathBuf> {
+    let path = if cfg!(target_os = "windows") {
+        std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join("Pioneer").join("rekordbox"))
+    } else if cfg!(target_os = "macos") {
+        home_dir().map(|home| home.join("Library/Pioneer/rekordbox"))
+    } else {
+        None
+    };
+    path.filter(|p| p.is_dir())
+}
+
+/// Check if files can be created in the given directory.
+#[cfg(feature = "fs")]
+pub fn is_writable_dir(dir: &Path) -> bool {
+    let test_file = dir.join(format!(".playfmt-write-test-{}", std::process::id()));
+    match std::fs::File::create(&test_file) {
+        Ok(_) => std::fs::remove_file(&test_file).is_ok(),
+        Err(_) => false,
+    }
+}
+
+/// Get the number of terminal columns the text takes up.
+///
+/// Wide characters like CJK take two columns and combining marks take none,
+/// so this is used instead of the number of chars for aligning columns.
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Pad the text with spaces on the right to the given display width.
+///
+/// Format string widths like `{:<20}` count chars, which misaligns text with wide characters.
+pub fn pad_to_width(text: &str, width: usize) -> String {
+    format!("{text}{}", " ".repeat(width.saturating_sub(display_width(text))))
+}
+
+/// Shorten the text to the given display width, ending with an ellipsis if it was cut.
+pub fn truncate_to_width(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return text.to_string();
+    }
+    let mut truncated = String::new();
+    let mut truncated_width = 0;
+    for c in text.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if truncated_width + char_width + 1 > width {
+            break;
+        }
+        truncated.push(c);
+        truncated_width += char_width;
+    }
+    if width > 0 {
+        truncated.push('…');
+    }
+    truncated
+}
+
+/// Percent-encode text for use in a URL query value.
+///
+/// Everything except ASCII letters, digits, and `-._~` is encoded.
+pub fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+            _ => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+/// Wrap text in an OSC 8 terminal hyperlink to the URL.
+///
+/// Terminals without hyperlink support show the text as is.
+pub fn hyperlink(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// Get the width of the terminal in columns, if standard output is a terminal.
+#[cfg(feature = "cli")]
+pub fn terminal_width() -> Option<usize> {
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    ratatui::crossterm::terminal::size()
+        .ok()
+        .map(|(columns, _)| usize::from(columns))
+        .filter(|&columns| columns > 0)
+}
+
 /// Get the longest formatted track playtime length in number of chars.
 pub fn get_max_playtime_length(tracks: &[Track]) -> usize {
     tracks
@@ -111,16 +685,18 @@ pub fn get_max_playtime_length(tracks: &[Track]) -> usize {
 pub fn playlist_format(file: &Path) -> Result<FileFormat> {
     let extension: &str = match file.extension() {
         None => {
-            anyhow::bail!(
+            return Err(PlaylistError::UnsupportedFormat(format!(
                 "Input file has no file extension: '{}'. Supported file types are: {}",
                 file.display(),
                 FileFormat::iter()
                     .map(|f| f.to_string())
                     .collect::<Vec<String>>()
                     .join(", ")
-            )
+            )))
         }
-        Some(ext) => ext.to_str().context("Failed to parse file extension")?,
+        Some(ext) => ext.to_str().ok_or_else(|| {
+            PlaylistError::UnsupportedFormat(format!("Failed to parse file extension: '{}'", file.display()))
+        })?,
     };
     FileFormat::from_str(extension)
 }
@@ -174,6 +750,233 @@ mod tests {
         assert_eq!(result.to_str().unwrap(), "14.5.2001.txt");
     }
 
+    #[test]
+    fn test_dedupe_tracks() {
+        let tracks = vec![
+            Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::try_seconds(30).unwrap(),
+            Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::try_seconds(90).unwrap(),
+            Track::new("Other".to_string(), "Title".to_string()),
+            Track::new("Artist".to_string(), "Title".to_string()),
+        ];
+        assert_eq!(dedupe_tracks(tracks.clone(), DedupePolicy::Off).len(), 4);
+        let deduped = dedupe_tracks(tracks, DedupePolicy::Consecutive);
+        assert_eq!(deduped.len(), 3);
+        assert_eq!(deduped[0].play_time, TimeDelta::try_seconds(120));
+        assert_eq!(deduped[0].plays, 1);
+    }
+
+    #[test]
+    fn test_dedupe_tracks_all_and_fuzzy() {
+        let tracks = vec![
+            Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::try_seconds(30).unwrap(),
+            Track::new("Other".to_string(), "Title".to_string()),
+            Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::try_seconds(90).unwrap(),
+            Track::new("artist".to_string(), "Title (Original Mix)".to_string()),
+        ];
+        let deduped = dedupe_tracks(tracks.clone(), DedupePolicy::All);
+        assert_eq!(deduped.len(), 3);
+        assert_eq!(deduped[0].play_time, TimeDelta::try_seconds(120));
+        assert_eq!(deduped[0].plays, 2);
+        assert_eq!(deduped[1].artist, "Other");
+        assert_eq!(deduped[1].plays, 1);
+        assert_eq!(dedupe_tracks(tracks, DedupePolicy::Fuzzy).len(), 2);
+    }
+
+    #[test]
+    fn test_is_long_enough() {
+        let track = Track::new("Artist".to_string(), "Title".to_string());
+        assert!(is_long_enough(&track, Some(TimeDelta::minutes(1))));
+        let track = track + TimeDelta::try_seconds(30).unwrap();
+        assert!(is_long_enough(&track, None));
+        assert!(is_long_enough(&track, Some(TimeDelta::seconds(30))));
+        assert!(!is_long_enough(&track, Some(TimeDelta::minutes(1))));
+    }
+
+    #[test]
+    fn test_merge_logged_duplicates() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(22, 0, 0)
+            .unwrap();
+        let track = |title: &str, start_second: i64, end_second: i64| {
+            Track::new_with_time(
+                "Artist".to_string(),
+                title.to_string(),
+                Some(start + TimeDelta::try_seconds(start_second).unwrap()),
+                Some(start + TimeDelta::try_seconds(end_second).unwrap()),
+                TimeDelta::try_seconds(end_second - start_second),
+            )
+        };
+        let mut first = track("One", 0, 300);
+        first.bpm = Some(124.0);
+        let mut logged_twice = track("One", 5, 310);
+        logged_twice.key = Some("8A".to_string());
+        let tracks = vec![first, logged_twice, track("Two", 300, 600), track("One", 900, 1000)];
+        let merged = merge_logged_duplicates(tracks, TimeDelta::minutes(2));
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].end_time, Some(start + TimeDelta::seconds(310)));
+        assert_eq!(merged[0].play_time, TimeDelta::try_seconds(305));
+        assert_eq!(merged[0].bpm, Some(124.0));
+        assert_eq!(merged[0].key.as_deref(), Some("8A"));
+        assert_eq!(merged[2].title, "One");
+    }
+
+    #[test]
+    fn test_merge_mashups() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(22, 0, 0)
+            .unwrap();
+        let track = |title: &str, start_minute: i64, end_minute: i64| {
+            Track::new_with_time(
+                "Artist".to_string(),
+                title.to_string(),
+                Some(start + TimeDelta::try_minutes(start_minute).unwrap()),
+                Some(start + TimeDelta::try_minutes(end_minute).unwrap()),
+                TimeDelta::try_minutes(end_minute - start_minute),
+            )
+        };
+        let tracks = vec![
+            track("One w/ Singer - Acapella", 0, 6),
+            track("Loop", 2, 4),
+            track("Two", 5, 10),
+            track("Three", 9, 15),
+        ];
+        let merged = merge_mashups(tracks);
+        assert_eq!(merged.len(), 3);
+        assert_eq!(merged[0].title, "One");
+        assert_eq!(merged[0].mashups_text(), "Singer - Acapella; Artist - Loop");
+        assert!(merged[1].mashups.is_empty());
+    }
+
+    #[test]
+    fn test_merge_reloads() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1)
+            .unwrap()
+            .and_hms_opt(22, 0, 0)
+            .unwrap();
+        let track = |title: &str, start_minute: i64, end_minute: i64| {
+            let start_time = start + TimeDelta::try_minutes(start_minute).unwrap();
+            let end_time = start + TimeDelta::try_minutes(end_minute).unwrap();
+            Track::new_with_time(
+                "Artist".to_string(),
+                title.to_string(),
+                Some(start_time),
+                Some(end_time),
+                Some(end_time - start_time),
+            )
+        };
+        let tracks = vec![
+            track("A", 0, 2),
+            track("B", 1, 3),
+            track("A", 3, 8),
+            track("C", 8, 12),
+            track("B", 12, 15),
+        ];
+        let merged = merge_reloads(tracks, TimeDelta::try_seconds(60).unwrap());
+        assert_eq!(merged.len(), 4);
+        assert_eq!(merged[0].title, "A");
+        assert_eq!(merged[0].play_time, TimeDelta::try_minutes(7));
+        assert_eq!(merged[0].end_time, Some(start + TimeDelta::try_minutes(8).unwrap()));
+        assert_eq!(merged[1].title, "B");
+        assert_eq!(merged[3].title, "B");
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_is_writable_dir() {
+        assert!(is_writable_dir(&std::env::temp_dir()));
+        assert!(!is_writable_dir(Path::new("/path/that/does/not/exist")));
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_next_available_path() {
+        let dir = std::env::temp_dir().join(format!("playfmt-next-path-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("playlist.csv");
+        assert_eq!(next_available_path(&path), path);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(next_available_path(&path), dir.join("playlist (2).csv"));
+        std::fs::write(dir.join("playlist (2).csv"), "").unwrap();
+        assert_eq!(next_available_path(&path), dir.join("playlist (3).csv"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("2:00:00").unwrap(), TimeDelta::hours(2));
+        assert_eq!(parse_duration("1:30").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_duration("0:45:30").unwrap(), TimeDelta::seconds(45 * 60 + 30));
+        assert_eq!(parse_duration("2h").unwrap(), TimeDelta::hours(2));
+        assert_eq!(parse_duration("90m").unwrap(), TimeDelta::minutes(90));
+        assert_eq!(parse_duration(" 30s ").unwrap(), TimeDelta::seconds(30));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("90").is_err());
+        assert!(parse_duration("1:75").is_err());
+        assert!(parse_duration("a:b").is_err());
+    }
+
+    #[test]
+    fn test_suggest_header() {
+        let headers = ["Track Title", "Artist", "BPM"];
+        assert_eq!(suggest_header("name", &headers), Some("Track Title"));
+        assert_eq!(suggest_header("artist", &["artists", "title"]), Some("artists"));
+        assert_eq!(suggest_header("Artist", &["Artsit", "Title"]), Some("Artsit"));
+        assert_eq!(suggest_header("Track Title", &["Title", "Artist"]), Some("Title"));
+        assert_eq!(suggest_header("name", &["BPM", "Key"]), None);
+    }
+
+    #[test]
+    fn test_missing_field_error() {
+        let headers = ["artist".to_string(), "Track Title".to_string(), String::new()];
+        let error = missing_field_error("Serato CSV", "name", &headers);
+        assert!(matches!(
+            &error,
+            PlaylistError::MissingField { field, suggestion: Some(suggestion), .. }
+                if field == "name" && suggestion == "Track Title"
+        ));
+        let error = error.to_string();
+        assert!(error.starts_with("Serato CSV missing required field: 'name'. Did you mean 'Track Title'?"));
+        assert!(error.ends_with("Found headers: 'artist', 'Track Title'"));
+    }
+
+    #[test]
+    fn test_parse_playtime() {
+        assert_eq!(parse_playtime("5:17").unwrap(), TimeDelta::seconds(5 * 60 + 17));
+        assert_eq!(parse_playtime("00:05:17").unwrap(), TimeDelta::seconds(5 * 60 + 17));
+        assert_eq!(parse_playtime("1:02:03").unwrap(), TimeDelta::seconds(3723));
+        assert_eq!(parse_playtime("317").unwrap(), TimeDelta::seconds(317));
+        assert!(parse_playtime("").is_err());
+        assert!(parse_playtime("5:75").is_err());
+        assert!(parse_playtime("5m").is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "fs")]
+    fn test_backup_file() {
+        let dir = std::env::temp_dir().join(format!("playfmt-backup-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("playlist.csv");
+        std::fs::write(&path, "old").unwrap();
+        let backup = backup_file(&path, 1).unwrap();
+        assert_eq!(backup, dir.join("playlist.csv.bak"));
+        assert!(!path.exists());
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), "old");
+
+        for _ in 0..3 {
+            std::fs::write(&path, "old").unwrap();
+            backup_file(&path, 2).unwrap();
+        }
+        let timestamped = std::fs::read_dir(&dir)
+            .unwrap()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_name().to_string_lossy() != "playlist.csv.bak")
+            .count();
+        assert_eq!(timestamped, 2);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_get_total_playtime() {
         let tracks: Vec<Track> = Vec::new();
@@ -197,4 +1000,77 @@ mod tests {
         let duration = TimeDelta::try_minutes(31).unwrap() + TimeDelta::try_seconds(33).unwrap();
         assert_eq!(formatted_duration(duration), "31:33");
     }
+
+    #[test]
+    fn test_format_duration() {
+        let duration = TimeDelta::try_seconds(311).unwrap();
+        assert_eq!(format_duration(duration, DurationFormat::Auto), "5:11");
+        assert_eq!(format_duration(duration, DurationFormat::Hms), "0:05:11");
+        assert_eq!(format_duration(duration, DurationFormat::Padded), "00:05:11");
+        assert_eq!(format_duration(duration, DurationFormat::Seconds), "311");
+
+        let duration = TimeDelta::try_seconds(3 * 3600 + 62).unwrap();
+        assert_eq!(format_duration(duration, DurationFormat::Hms), "3:01:02");
+        assert_eq!(format_duration(duration, DurationFormat::Padded), "03:01:02");
+
+        assert_eq!(format_duration(TimeDelta::zero(), DurationFormat::Auto), "");
+        assert_eq!(format_duration(TimeDelta::zero(), DurationFormat::Padded), "00:00:00");
+        assert_eq!(format_duration(TimeDelta::zero(), DurationFormat::Seconds), "0");
+    }
+
+    #[test]
+    fn test_round_duration() {
+        let step = TimeDelta::try_seconds(15).unwrap();
+        assert_eq!(
+            round_duration(TimeDelta::try_seconds(317).unwrap(), step),
+            TimeDelta::try_seconds(315).unwrap()
+        );
+        assert_eq!(
+            round_duration(TimeDelta::try_seconds(323).unwrap(), step),
+            TimeDelta::try_seconds(330).unwrap()
+        );
+        assert_eq!(round_duration(TimeDelta::try_seconds(5).unwrap(), step), step);
+        assert_eq!(round_duration(TimeDelta::zero(), step), TimeDelta::zero());
+        let minute = TimeDelta::try_minutes(1).unwrap();
+        assert_eq!(
+            round_duration(TimeDelta::try_seconds(450).unwrap(), minute),
+            TimeDelta::try_minutes(8).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parse_offset() {
+        assert_eq!(parse_offset("01:30").unwrap(), TimeDelta::try_seconds(90).unwrap());
+        assert_eq!(parse_offset("-0:45").unwrap(), TimeDelta::try_seconds(-45).unwrap());
+        assert_eq!(parse_offset("+1:00:05").unwrap(), TimeDelta::try_seconds(3605).unwrap());
+        assert!(parse_offset("90").is_err());
+        assert!(parse_offset("1:75").is_err());
+        assert!(parse_offset("-").is_err());
+    }
+
+    #[test]
+    fn test_formatted_elapsed() {
+        assert_eq!(formatted_elapsed(TimeDelta::zero()), "00:00");
+        assert_eq!(formatted_elapsed(TimeDelta::try_seconds(272).unwrap()), "04:32");
+        assert_eq!(formatted_elapsed(TimeDelta::try_seconds(3723).unwrap()), "1:02:03");
+        assert_eq!(formatted_elapsed(TimeDelta::try_seconds(-5).unwrap()), "00:00");
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("Artist"), 6);
+        assert_eq!(display_width("坂本龍一"), 8);
+        assert_eq!(display_width("Beyonce\u{301}"), 7);
+        assert_eq!(pad_to_width("坂本", 6), "坂本  ");
+        assert_eq!(pad_to_width("Longer", 3), "Longer");
+    }
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("Title", 5), "Title");
+        assert_eq!(truncate_to_width("Long Title", 6), "Long …");
+        assert_eq!(truncate_to_width("坂本龍一", 6), "坂本…");
+        assert_eq!(truncate_to_width("坂本龍一", 5), "坂本…");
+        assert_eq!(truncate_to_width("Title", 0), "");
+    }
 }
@@ -0,0 +1,205 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use chrono::{NaiveDateTime, TimeDelta};
+use serde::Serialize;
+use strum_macros::Display;
+
+use super::stats::{self, StatsOptions};
+use super::track::Track;
+use super::utils;
+
+/// How serious a validation issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Display)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// Something worth knowing that is usually intentional, like a gap between tracks
+    Info,
+    /// Track data that looks suspicious but can be correct
+    Warning,
+    /// Track data that is missing or contradicts itself
+    Error,
+}
+
+/// Type of problem found in a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Display)]
+#[serde(rename_all = "snake_case")]
+pub enum IssueKind {
+    MissingArtist,
+    MissingTitle,
+    /// Start time is missing while other tracks have one
+    MissingStartTime,
+    ZeroPlaytime,
+    /// Same artist and title as an earlier track
+    Duplicate,
+    /// Start time is earlier than the start time of the previous track
+    StartTimeOrder,
+    /// End time is earlier than the start time of the same track
+    EndTimeOrder,
+    /// Time without a track playing before this track is longer than the gap threshold
+    Gap,
+}
+
+/// Problem found in one track of a playlist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Index of the track in the playlist, counting from zero
+    pub track_index: usize,
+    pub kind: IssueKind,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.track_index + 1, self.message)
+    }
+}
+
+/// Check the tracks for common problems.
+///
+/// Gaps between tracks are reported using the gap threshold from the options.
+pub fn validate_tracks(tracks: &[Track], options: &StatsOptions) -> Vec<ValidationIssue> {
+    let mut issues: Vec<ValidationIssue> = Vec::new();
+    let has_times = tracks.iter().any(|t| t.start_time.is_some());
+    let gaps: HashMap<usize, TimeDelta> = stats::track_gaps(tracks, options.max_gap).into_iter().collect();
+    // Index of the first occurrence of each artist and title
+    let mut first_indices: HashMap<(&str, &str), usize> = HashMap::new();
+    let mut previous_start: Option<NaiveDateTime> = None;
+    for (index, track) in tracks.iter().enumerate() {
+        let mut add = |severity: Severity, kind: IssueKind, message: String| {
+            issues.push(ValidationIssue {
+                severity,
+                track_index: index,
+                kind,
+                message,
+            });
+        };
+        if track.artist.trim().is_empty() {
+            add(
+                Severity::Warning,
+                IssueKind::MissingArtist,
+                format!("Missing artist for '{}'", track.title),
+            );
+        }
+        if track.title.trim().is_empty() {
+            add(
+                Severity::Error,
+                IssueKind::MissingTitle,
+                format!("Empty title for '{}'", track.artist),
+            );
+        }
+        if has_times && track.start_time.is_none() {
+            add(
+                Severity::Error,
+                IssueKind::MissingStartTime,
+                format!("Missing or unparseable start time: {track}"),
+            );
+        }
+        if track.play_time.is_some_and(|d| d.num_seconds() <= 0) {
+            add(
+                Severity::Error,
+                IssueKind::ZeroPlaytime,
+                format!("Zero second playtime: {track}"),
+            );
+        }
+        let first = *first_indices
+            .entry((track.artist.as_str(), track.title.as_str()))
+            .or_insert(index);
+        if first != index {
+            // A consecutive duplicate is most likely a row that should have been merged,
+            // while a track played again later is usually intentional
+            if tracks[index - 1] == *track {
+                add(
+                    Severity::Warning,
+                    IssueKind::Duplicate,
+                    format!("Duplicate of the previous track: {track}"),
+                );
+            } else {
+                add(
+                    Severity::Info,
+                    IssueKind::Duplicate,
+                    format!("Duplicate of track {}: {track}", first + 1),
+                );
+            }
+        }
+        if let (Some(start), Some(end)) = (track.start_time, track.end_time) {
+            if end < start {
+                add(
+                    Severity::Error,
+                    IssueKind::EndTimeOrder,
+                    format!(
+                        "End time {} is before start time {}",
+                        end.format("%H:%M:%S"),
+                        start.format("%H:%M:%S")
+                    ),
+                );
+            }
+        }
+        if let (Some(previous), Some(current)) = (previous_start, track.start_time) {
+            if current < previous {
+                add(
+                    Severity::Error,
+                    IssueKind::StartTimeOrder,
+                    format!(
+                        "Start time {} is before previous track start time {}",
+                        current.format("%H:%M:%S"),
+                        previous.format("%H:%M:%S")
+                    ),
+                );
+            }
+        }
+        previous_start = track.start_time.or(previous_start);
+        if let Some(gap) = gaps.get(&index) {
+            add(
+                Severity::Info,
+                IssueKind::Gap,
+                format!("Gap of {} before track: {track}", utils::formatted_duration(*gap)),
+            );
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::NaiveDate;
+
+    #[test]
+    fn track_issues() {
+        let start = NaiveDate::from_ymd_opt(2024, 3, 8)
+            .unwrap()
+            .and_hms_opt(20, 0, 0)
+            .unwrap();
+        let tracks = vec![
+            Track::builder()
+                .artist("A")
+                .title("One")
+                .start_time(start)
+                .end_time(start + TimeDelta::minutes(4))
+                .build(),
+            Track::builder()
+                .title("Two")
+                .start_time(start + TimeDelta::minutes(10))
+                .build(),
+            Track::builder().artist("A").title("One").build(),
+        ];
+        let issues = validate_tracks(&tracks, &StatsOptions::default());
+        let kinds: Vec<(usize, IssueKind, Severity)> = issues
+            .iter()
+            .map(|issue| (issue.track_index, issue.kind, issue.severity))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (1, IssueKind::MissingArtist, Severity::Warning),
+                (1, IssueKind::Gap, Severity::Info),
+                (2, IssueKind::MissingStartTime, Severity::Error),
+                (2, IssueKind::Duplicate, Severity::Info),
+            ]
+        );
+        assert_eq!(issues[3].to_string(), "3: Duplicate of track 1: A - One");
+    }
+}
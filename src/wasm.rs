@@ -0,0 +1,58 @@
+use std::io::Cursor;
+use std::str::FromStr;
+
+use wasm_bindgen::prelude::*;
+
+use super::options::ParseOptions;
+use super::playlist::Playlist;
+use super::types::OutputFormat;
+
+/// Playlist for JavaScript, for example parsed from a file dropped on a web page.
+///
+/// ```js
+/// const playlist = WasmPlaylist.fromBytes(new Uint8Array(await file.arrayBuffer()), file.name);
+/// const csv = playlist.renderString("csv");
+/// ```
+#[wasm_bindgen]
+pub struct WasmPlaylist {
+    playlist: Playlist,
+}
+
+#[wasm_bindgen]
+impl WasmPlaylist {
+    /// Parse playlist file contents. The file type is detected from the file name extension.
+    #[wasm_bindgen(js_name = fromStr)]
+    pub fn from_str(contents: &str, file_name: &str) -> Result<WasmPlaylist, JsError> {
+        let playlist = Playlist::from_str(contents, file_name, &ParseOptions::default())?;
+        Ok(Self { playlist })
+    }
+
+    /// Parse playlist file bytes, including the UTF-16 text files exported by Rekordbox.
+    #[wasm_bindgen(js_name = fromBytes)]
+    pub fn from_bytes(contents: Vec<u8>, file_name: &str) -> Result<WasmPlaylist, JsError> {
+        let playlist = Playlist::from_reader(Cursor::new(contents), file_name, &ParseOptions::default())?;
+        Ok(Self { playlist })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.playlist.name.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = trackCount)]
+    pub fn track_count(&self) -> usize {
+        self.playlist.len()
+    }
+
+    /// Render the playlist in a text output format, for example "csv", "txt", or "m3u".
+    #[wasm_bindgen(js_name = renderString)]
+    pub fn render_string(&self, output_format: &str) -> Result<String, JsError> {
+        Ok(self.playlist.render_string(&OutputFormat::from_str(output_format)?)?)
+    }
+
+    /// Get the output file contents for any output format, including binary ones like "xlsx".
+    #[wasm_bindgen(js_name = toBytes)]
+    pub fn to_bytes(&self, output_format: &str) -> Result<Vec<u8>, JsError> {
+        Ok(self.playlist.to_bytes(&OutputFormat::from_str(output_format)?)?)
+    }
+}
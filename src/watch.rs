@@ -0,0 +1,138 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use notify_debouncer_mini::notify::RecursiveMode;
+use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
+
+use playlist_formatter::options::ParseOptions;
+use playlist_formatter::playlist::Playlist;
+use playlist_formatter::types::PlaylistType;
+use playlist_formatter::utils;
+
+use crate::config::{SaveSettings, UserConfig};
+
+/// How long to wait for file events to settle before processing a file.
+const DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Watch directory for new playlist exports and format them automatically.
+///
+/// Runs until the process is interrupted.
+pub fn watch_directory(
+    dir: &Path,
+    settings: &SaveSettings,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+) -> Result<()> {
+    if !dir.is_dir() {
+        anyhow::bail!("Directory does not exist or is not accessible: '{}'", dir.display());
+    }
+    let dir = dunce::canonicalize(dir)?;
+
+    let (sender, receiver) = mpsc::channel();
+    let mut debouncer = new_debouncer(DEBOUNCE_TIMEOUT, sender).context("Failed to create file watcher")?;
+    debouncer
+        .watcher()
+        .watch(&dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch directory: '{}'", dir.display()))?;
+
+    tracing::info!("Watching for new playlists in: {}", dir.display().to_string().cyan());
+
+    // Output files written by this process, so they are not processed again
+    let mut written_files: HashSet<PathBuf> = HashSet::new();
+    // Modification times of processed input files, so repeated events for the same file are skipped
+    let mut processed_files: HashMap<PathBuf, SystemTime> = HashMap::new();
+    for result in receiver {
+        match result {
+            Ok(events) => {
+                for event in events {
+                    if event.kind != DebouncedEventKind::Any || written_files.contains(&event.path) {
+                        continue;
+                    }
+                    if !event.path.is_file() || utils::playlist_format(&event.path).is_err() {
+                        continue;
+                    }
+                    let modified = event.path.metadata().and_then(|m| m.modified()).ok();
+                    if let Some(modified) = modified {
+                        if processed_files.get(&event.path) == Some(&modified) {
+                            continue;
+                        }
+                        processed_files.insert(event.path.clone(), modified);
+                    }
+                    match format_playlist(&event.path, settings, user_config, parse_options) {
+                        Ok(Some(output_path)) => {
+                            written_files.insert(output_path);
+                        }
+                        Ok(None) => {}
+                        Err(error) => tracing::error!("Failed to format {}: {error}", event.path.display()),
+                    }
+                }
+            }
+            Err(error) => tracing::error!("Watch error: {error}"),
+        }
+    }
+    Ok(())
+}
+
+/// Format a single playlist file and return the output file path if it was saved.
+fn format_playlist(
+    path: &Path,
+    settings: &SaveSettings,
+    user_config: &UserConfig,
+    parse_options: &ParseOptions,
+) -> Result<Option<PathBuf>> {
+    let _span = tracing::debug_span!("convert", file = %path.display()).entered();
+    tracing::info!("New playlist: {}", path.display());
+    let playlist = Playlist::new_with_options(path, parse_options)?;
+    if playlist.playlist_type == PlaylistType::Formatted {
+        tracing::debug!("Skipping already formatted playlist: {}", path.display());
+        return Ok(None);
+    }
+    if let Some(output_path) = user_config.up_to_date_output(&playlist, settings) {
+        tracing::info!("Up to date: {}", output_path.display());
+        return Ok(Some(output_path));
+    }
+    let output_path = user_config.resolve_output_path(&playlist, None, settings);
+    settings.save(&playlist, &output_path)?;
+    Ok(Some(output_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_new_playlist() {
+        let dir = std::env::temp_dir().join(format!("playfmt-watch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let files_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("files");
+        let input = dir.join("serato.csv");
+        std::fs::copy(files_dir.join("serato.csv"), &input).unwrap();
+        let formatted = dir.join("formatted.csv");
+        std::fs::copy(files_dir.join("formatted.csv"), &formatted).unwrap();
+
+        let user_config = UserConfig {
+            save_dir: Some(dir.join("output")),
+            ..Default::default()
+        };
+        let settings = SaveSettings::default();
+        let parse_options = ParseOptions::default();
+
+        let output_path = format_playlist(&input, &settings, &user_config, &parse_options)
+            .unwrap()
+            .unwrap();
+        assert!(output_path.is_file());
+        assert!(output_path.starts_with(dir.join("output")));
+
+        // A repeated event for the same file finds the existing output
+        let repeated = format_playlist(&input, &settings, &user_config, &parse_options).unwrap();
+        assert_eq!(repeated, Some(output_path));
+
+        let skipped = format_playlist(&formatted, &settings, &user_config, &parse_options).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(skipped, None);
+    }
+}
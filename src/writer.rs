@@ -0,0 +1,226 @@
+use std::sync::LazyLock;
+
+use super::error::{PlaylistError, Result};
+use super::options::FormatOptions;
+use super::playlist::Playlist;
+use super::types::OutputFormat;
+
+/// Writers for the output formats supported out of the box.
+pub static DEFAULT_WRITERS: LazyLock<WriterRegistry> = LazyLock::new(WriterRegistry::default);
+
+/// Serializer for one output format.
+///
+/// Implement this to add an output format and add it to a [`WriterRegistry`].
+pub trait PlaylistWriter: Send + Sync {
+    /// File extension without the leading dot, for example "csv".
+    fn extension(&self) -> &str;
+
+    /// Built-in output format this writer produces, if any.
+    fn output_format(&self) -> Option<OutputFormat> {
+        None
+    }
+
+    /// Check if the output is binary instead of UTF-8 text.
+    fn is_binary(&self) -> bool {
+        false
+    }
+
+    /// Get the file contents for the playlist using the given options.
+    fn write(&self, playlist: &Playlist, options: &FormatOptions) -> Result<Vec<u8>>;
+}
+
+/// Writer for formatted CSV files.
+pub struct CsvWriter;
+
+impl PlaylistWriter for CsvWriter {
+    fn extension(&self) -> &str {
+        "csv"
+    }
+
+    fn output_format(&self) -> Option<OutputFormat> {
+        Some(OutputFormat::Csv)
+    }
+
+    fn write(&self, playlist: &Playlist, options: &FormatOptions) -> Result<Vec<u8>> {
+        playlist.csv_bytes(options)
+    }
+}
+
+/// Writer for plain text track lists.
+pub struct TxtWriter;
+
+impl PlaylistWriter for TxtWriter {
+    fn extension(&self) -> &str {
+        "txt"
+    }
+
+    fn output_format(&self) -> Option<OutputFormat> {
+        Some(OutputFormat::Txt)
+    }
+
+    fn write(&self, playlist: &Playlist, options: &FormatOptions) -> Result<Vec<u8>> {
+        Ok(playlist.txt_bytes(options))
+    }
+}
+
+/// Writer for Excel workbooks.
+#[cfg(feature = "xlsx")]
+pub struct XlsxWriter;
+
+#[cfg(feature = "xlsx")]
+impl PlaylistWriter for XlsxWriter {
+    fn extension(&self) -> &str {
+        "xlsx"
+    }
+
+    fn output_format(&self) -> Option<OutputFormat> {
+        Some(OutputFormat::Xlsx)
+    }
+
+    fn is_binary(&self) -> bool {
+        true
+    }
+
+    fn write(&self, playlist: &Playlist, options: &FormatOptions) -> Result<Vec<u8>> {
+        playlist.excel_bytes(options)
+    }
+}
+
+/// Writer for extended M3U playlists.
+pub struct M3uWriter;
+
+impl PlaylistWriter for M3uWriter {
+    fn extension(&self) -> &str {
+        "m3u"
+    }
+
+    fn output_format(&self) -> Option<OutputFormat> {
+        Some(OutputFormat::M3u)
+    }
+
+    fn write(&self, playlist: &Playlist, _options: &FormatOptions) -> Result<Vec<u8>> {
+        playlist.m3u_bytes()
+    }
+}
+
+/// Writer for the versioned JSON export.
+pub struct JsonWriter;
+
+impl PlaylistWriter for JsonWriter {
+    fn extension(&self) -> &str {
+        "json"
+    }
+
+    fn output_format(&self) -> Option<OutputFormat> {
+        Some(OutputFormat::Json)
+    }
+
+    fn write(&self, playlist: &Playlist, options: &FormatOptions) -> Result<Vec<u8>> {
+        playlist.json_bytes(options)
+    }
+}
+
+/// Playlist writers looked up by file extension or output format.
+pub struct WriterRegistry {
+    writers: Vec<Box<dyn PlaylistWriter>>,
+}
+
+impl WriterRegistry {
+    /// Create a registry without any writers.
+    pub fn empty() -> Self {
+        Self { writers: Vec::new() }
+    }
+
+    /// Add a writer that takes precedence over the writers already registered for the same extension.
+    pub fn register(&mut self, writer: impl PlaylistWriter + 'static) -> &mut Self {
+        self.writers.insert(0, Box::new(writer));
+        self
+    }
+
+    /// Get the extensions of the registered writers.
+    pub fn extensions(&self) -> Vec<&str> {
+        self.writers.iter().map(|writer| writer.extension()).collect()
+    }
+
+    /// Find the writer for a file extension, ignoring case.
+    pub fn find(&self, extension: &str) -> Result<&dyn PlaylistWriter> {
+        self.writers
+            .iter()
+            .find(|writer| writer.extension().eq_ignore_ascii_case(extension.trim()))
+            .map(AsRef::as_ref)
+            .ok_or_else(|| {
+                PlaylistError::UnsupportedFormat(format!(
+                    "Unsupported output file format: '{extension}'. Supported formats are: {}",
+                    self.extensions().join(", ")
+                ))
+            })
+    }
+
+    /// Find the writer for a built-in output format.
+    pub fn for_format(&self, output_format: &OutputFormat) -> Result<&dyn PlaylistWriter> {
+        self.writers
+            .iter()
+            .find(|writer| writer.output_format().as_ref() == Some(output_format))
+            .map(AsRef::as_ref)
+            .ok_or_else(|| PlaylistError::UnsupportedFormat(format!("No writer registered for {output_format}")))
+    }
+}
+
+impl Default for WriterRegistry {
+    fn default() -> Self {
+        Self {
+            writers: vec![
+                Box::new(CsvWriter),
+                Box::new(TxtWriter),
+                #[cfg(feature = "xlsx")]
+                Box::new(XlsxWriter),
+                Box::new(M3uWriter),
+                Box::new(JsonWriter),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MarkdownWriter;
+
+    impl PlaylistWriter for MarkdownWriter {
+        fn extension(&self) -> &str {
+            "md"
+        }
+
+        fn write(&self, playlist: &Playlist, _options: &FormatOptions) -> Result<Vec<u8>> {
+            Ok(format!("# {}\n", playlist.name).into_bytes())
+        }
+    }
+
+    #[test]
+    fn find_builtin_writers() {
+        let registry = WriterRegistry::default();
+        assert_eq!(registry.find("CSV").unwrap().output_format(), Some(OutputFormat::Csv));
+        #[cfg(feature = "xlsx")]
+        assert!(registry.find("xlsx").unwrap().is_binary());
+        assert_eq!(registry.for_format(&OutputFormat::M3u).unwrap().extension(), "m3u");
+        assert_eq!(registry.for_format(&OutputFormat::Json).unwrap().extension(), "json");
+        assert!(matches!(registry.find("md"), Err(PlaylistError::UnsupportedFormat(_))));
+    }
+
+    #[test]
+    fn register_custom_writer() {
+        let mut registry = WriterRegistry::default();
+        registry.register(MarkdownWriter);
+        let playlist = crate::PlaylistBuilder::new("Friday").build();
+        assert_eq!(
+            registry
+                .find("md")
+                .unwrap()
+                .write(&playlist, &FormatOptions::default())
+                .unwrap(),
+            b"# Friday\n"
+        );
+        assert!(WriterRegistry::empty().for_format(&OutputFormat::Csv).is_err());
+    }
+}
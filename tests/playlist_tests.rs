@@ -1,10 +1,26 @@
+#![cfg(feature = "fs")]
+
 use std::path::PathBuf;
-use std::sync::LazyLock;
+use std::sync::{Arc, LazyLock, Mutex};
 
-use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
+use chrono::{NaiveDate, NaiveDateTime, NaiveTime, TimeDelta};
 
+use playlist_formatter::convert::{self, ConvertJob};
+use playlist_formatter::json::{self, JsonPlaylist};
+use playlist_formatter::options::{
+    ConvertOptions, FormatOptions, ParseOptions, ProgressCallback, ProgressEvent, SaveOptions,
+};
 use playlist_formatter::playlist::Playlist;
-use playlist_formatter::types::{FileFormat, PlaylistType};
+use playlist_formatter::reader::{PlaylistInput, PlaylistReader, ReaderRegistry};
+use playlist_formatter::track::Track;
+use playlist_formatter::types::{
+    DedupePolicy, DjAssignment, DurationFormat, FileFormat, FooterField, InputFormat, MergeStrategy, OutputFormat,
+    PlaylistType, SortKey, SplitPoint, TimeFormat, TimePosition, TrackRename,
+};
+use playlist_formatter::utils;
+use playlist_formatter::validate::IssueKind;
+use playlist_formatter::writer::{PlaylistWriter, WriterRegistry};
+use playlist_formatter::{PlaylistBuilder, PlaylistError};
 
 /// Path to the `tests/files` directory.
 static TEST_FILES_DIR: LazyLock<PathBuf> =
@@ -61,6 +77,58 @@ fn test_serato_txt() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[test]
+fn test_serato_txt_non_ascii_columns() -> anyhow::Result<()> {
+    let line = |name: &str, artist: &str, start: &str| format!("{name:<24}{artist:<16}{start}\n");
+    let divider = format!("{}\n", "-".repeat(56));
+    let contents = [
+        line("name", "artist", "start time"),
+        divider.clone(),
+        line("Ääniä 8.3.2024", "", "8.3.2024, 20.00.00 EET"),
+        divider,
+        line("Äänia Ääniä Ääniä", "Beyoncé", "20.00.00 EET"),
+        line("Crazy in Love", "Beyoncé", ""),
+        "Ünfinished line".to_string(),
+    ]
+    .concat();
+    let path = std::env::temp_dir().join(format!("playfmt-serato-non-ascii-{}.txt", std::process::id()));
+    std::fs::write(&path, contents)?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.name, "Ääniä 8.3.2024");
+    assert_eq!(playlist.tracks.len(), 3);
+    assert_eq!(playlist.tracks[0].artist, "Beyoncé");
+    assert_eq!(playlist.tracks[0].title, "Äänia Ääniä Ääniä");
+    assert_eq!(playlist.tracks[1].title, "Crazy in Love");
+    assert_eq!(playlist.tracks[2].title, "Ünfinished line");
+    Ok(())
+}
+
+#[test]
+fn test_malformed_files_do_not_panic() -> anyhow::Result<()> {
+    let files: [(&str, &[u8]); 8] = [
+        ("empty.csv", b""),
+        ("empty.txt", b""),
+        ("header.csv", b"name,artist,start time\n"),
+        ("divider.txt", b"-----\n"),
+        ("divider-header.txt", b"name     artist\n-----\n"),
+        ("short.txt", b"#\tArtist\tTrack Title\n1\n"),
+        ("binary.txt", &[0xff, 0xfe, 0x00, 0xd8, 0x0a, 0x00]),
+        ("quotes.csv", b"Artist,Title\n\"unterminated,x\n"),
+    ];
+    let dir = std::env::temp_dir().join(format!("playfmt-malformed-{}", std::process::id()));
+    std::fs::create_dir_all(&dir)?;
+    for (name, contents) in files {
+        let path = dir.join(name);
+        std::fs::write(&path, contents)?;
+        // Either result is fine as long as reading does not panic
+        let _ = Playlist::new(&path);
+    }
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
 #[test]
 fn test_rekordbox_txt() -> anyhow::Result<()> {
     let test_file_path = TEST_FILES_DIR.join("rekordbox.txt");
@@ -75,6 +143,9 @@ fn test_rekordbox_txt() -> anyhow::Result<()> {
     assert_eq!(playlist.playlist_type, PlaylistType::Rekordbox);
     assert_eq!(playlist.tracks.len(), 28);
     assert_eq!(playlist.date, None);
+    assert_eq!(playlist.tracks[0].play_time, TimeDelta::try_seconds(7 * 60 + 7));
+    assert_eq!(playlist.total_duration, utils::get_total_playtime(&playlist.tracks));
+    assert!(playlist.total_duration.is_some());
     Ok(())
 }
 
@@ -116,3 +187,1420 @@ fn test_formatted_csv() -> anyhow::Result<()> {
     assert_eq!(playlist.tracks.len(), 24);
     Ok(())
 }
+
+#[test]
+fn test_merge_playlists() -> anyhow::Result<()> {
+    let serato = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let rekordbox = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    let total_duration = serato.total_duration.zip(rekordbox.total_duration).map(|(a, b)| a + b);
+    let playlist = Playlist::merge_playlists(vec![serato, rekordbox], MergeStrategy::Concatenate)?;
+    assert_eq!(playlist.name, "Serato 30.3.2023".to_string());
+    assert_eq!(playlist.tracks.len(), 32);
+    assert_eq!(playlist.total_duration, total_duration);
+    assert_eq!(
+        playlist.date,
+        Some(NaiveDateTime::new(
+            NaiveDate::from_ymd_opt(2023, 3, 30).unwrap(),
+            NaiveTime::from_hms_opt(16, 4, 53).unwrap()
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_merge_playlists_by_timestamp() -> anyhow::Result<()> {
+    let path = TEST_FILES_DIR.join("serato 8.3.2024.csv");
+    // Second machine logged the first three tracks with a slightly different clock
+    let second = || -> anyhow::Result<Playlist> {
+        let mut playlist = Playlist::new(&path)?;
+        playlist.tracks.truncate(3);
+        for track in &mut playlist.tracks {
+            track.start_time = track.start_time.map(|t| t + TimeDelta::seconds(20));
+        }
+        Ok(playlist)
+    };
+    let track_count = Playlist::new(&path)?.tracks.len();
+    let interleaved = Playlist::merge_playlists(vec![Playlist::new(&path)?, second()?], MergeStrategy::Interleave)?;
+    assert_eq!(interleaved.tracks.len(), track_count + 3);
+    let merged = Playlist::merge_playlists(vec![Playlist::new(&path)?, second()?], MergeStrategy::Timestamp)?;
+    assert_eq!(merged.tracks.len(), track_count);
+    assert!(merged.tracks.windows(2).all(|w| w[0].start_time <= w[1].start_time));
+    Ok(())
+}
+
+#[test]
+fn test_rekordbox_original_numbers() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    playlist.remove_tracks(&[2])?;
+    assert_eq!(playlist.track_number(1, &FormatOptions::default()), 2);
+    let options = FormatOptions {
+        output_format: OutputFormat::Csv,
+        original_numbers: true,
+        ..FormatOptions::default()
+    };
+    assert_eq!(playlist.track_number(0, &options), 1);
+    assert_eq!(playlist.track_number(1, &options), 3);
+
+    let contents = playlist.render_string_with_options(&options)?;
+    let mut lines = contents.lines().skip(1);
+    assert!(lines.next().unwrap().starts_with("1,"));
+    assert!(lines.next().unwrap().starts_with("3,"));
+    Ok(())
+}
+
+#[test]
+fn test_render_to_writer() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let text = playlist.render_string(&OutputFormat::Txt)?;
+    assert_eq!(text.lines().count(), playlist.tracks.len());
+    assert!(text.starts_with(&playlist.tracks[0].to_string()));
+
+    let mut buffer: Vec<u8> = Vec::new();
+    playlist.write_to(&mut buffer, &OutputFormat::Csv)?;
+    assert_eq!(String::from_utf8(buffer)?, playlist.render_string(&OutputFormat::Csv)?);
+
+    #[cfg(feature = "xlsx")]
+    {
+        let mut buffer: Vec<u8> = Vec::new();
+        playlist.write_to(&mut buffer, &OutputFormat::Xlsx)?;
+        assert!(buffer.starts_with(b"PK"));
+        assert!(playlist.render_string(&OutputFormat::Xlsx).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_json_export() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let text = playlist.render_string(&OutputFormat::Json)?;
+    let export = JsonPlaylist::from_json(&text)?;
+    assert_eq!(export.schema_version, json::SCHEMA_VERSION);
+    assert_eq!(export.name, playlist.name);
+    assert_eq!(export.tracks.len(), playlist.tracks.len());
+    assert_eq!(export.tracks[0].artist, playlist.tracks[0].artist);
+    assert_eq!(
+        export.tracks[0].playtime_seconds,
+        playlist.tracks[0].play_time.map(|d| d.num_seconds())
+    );
+
+    // Every exported field is described in the schema
+    let schema = serde_json::to_value(json::schema())?;
+    let value: serde_json::Value = serde_json::from_str(&text)?;
+    for key in value.as_object().unwrap().keys() {
+        assert!(schema["properties"].get(key).is_some(), "{key} missing from schema");
+    }
+    for key in value["tracks"][0].as_object().unwrap().keys() {
+        assert!(
+            schema["$defs"]["JsonTrack"]["properties"].get(key).is_some(),
+            "{key} missing from schema"
+        );
+    }
+    Ok(())
+}
+
+#[test]
+fn test_progress_callback() -> anyhow::Result<()> {
+    let events: Arc<Mutex<Vec<ProgressEvent>>> = Arc::default();
+    let recorded = Arc::clone(&events);
+    let options = ParseOptions {
+        progress: Some(ProgressCallback::new(move |event| {
+            recorded.lock().unwrap().push(event.clone());
+        })),
+        ..ParseOptions::default()
+    };
+    let contents: String = std::iter::once("Artist,Title\n".to_string())
+        .chain((0..2500).map(|index| format!("Artist {index},Title {index}\n")))
+        .collect();
+    let playlist = Playlist::from_str(&contents, "big.csv", &options)?;
+    assert_eq!(playlist.tracks.len(), 2500);
+    let rows: Vec<usize> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .filter_map(|event| match event {
+            ProgressEvent::RowsParsed { rows, .. } => Some(*rows),
+            ProgressEvent::FileCompleted { .. } => None,
+        })
+        .collect();
+    assert_eq!(rows, vec![1000, 2000, 2500]);
+    Ok(())
+}
+
+#[test]
+fn test_playlist_statistics() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let statistics = playlist.statistics();
+    assert_eq!(statistics.track_count, playlist.tracks.len());
+    assert_eq!(
+        statistics.artists.iter().map(|a| a.count).sum::<usize>(),
+        playlist.tracks.len()
+    );
+    assert_eq!(
+        statistics.total_playtime_seconds,
+        playlist.total_duration.map(|d| d.num_seconds())
+    );
+    assert!(statistics.shortest_track.is_some() && statistics.longest_track.is_some());
+    Ok(())
+}
+
+#[test]
+fn test_playlist_diff() -> anyhow::Result<()> {
+    let old = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    assert!(old.diff(&old).is_empty());
+
+    let mut new = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let removed = new.tracks.remove(0);
+    new.tracks.swap(0, 1);
+    new.tracks[0].genre = Some("Garage".to_string());
+    new.tracks.push(Track::from_name("New Artist - New Track"));
+    let diff = old.diff(&new);
+    assert_eq!(diff.removed.len(), 1);
+    assert_eq!(diff.removed[0].track, removed.to_string());
+    assert_eq!(diff.added.len(), 1);
+    assert_eq!(diff.added[0].track_number, new.tracks.len());
+    assert_eq!(diff.moved.len(), 1);
+    assert_eq!(diff.changed.len(), 1);
+    assert_eq!(diff.changed[0].changes[0].field, "Genre");
+    Ok(())
+}
+
+#[test]
+fn test_serato_csv_raw() -> anyhow::Result<()> {
+    let test_file_path = TEST_FILES_DIR.join("serato.csv");
+    let playlist = Playlist::new_with_options(&test_file_path, &ParseOptions::raw(Some(InputFormat::SeratoCsv)))?;
+    assert_eq!(playlist.playlist_type, PlaylistType::Serato);
+    // Consecutive duplicates are kept as is
+    assert_eq!(playlist.tracks.len(), 5);
+    Ok(())
+}
+
+#[test]
+fn test_organized_output_file_path() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let path =
+        playlist.get_organized_output_file_path(Some("/playlists/set.csv".to_string()), false, &OutputFormat::Csv);
+    assert_eq!(path, PathBuf::from("/playlists/2023/03/set.csv"));
+    Ok(())
+}
+
+#[test]
+fn test_split_playlist() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let parts = playlist.split(&[SplitPoint::Track(11)])?;
+    assert_eq!(parts.len(), 2);
+    assert_eq!(parts[0].name, "SUBRHYTHM 8.3.2024 part 1");
+    assert_eq!(parts[0].tracks.len(), 10);
+    assert_eq!(parts[1].tracks.len(), 20);
+    assert_eq!(parts[1].date, parts[1].tracks[0].start_time);
+    assert_eq!(
+        parts[0].total_duration.unwrap() + parts[1].total_duration.unwrap(),
+        playlist.total_duration.unwrap()
+    );
+
+    let parts = playlist.split(&[SplitPoint::Elapsed(TimeDelta::hours(1)), SplitPoint::Track(20)])?;
+    assert_eq!(parts.len(), 3);
+    assert_eq!(parts.iter().map(|p| p.tracks.len()).sum::<usize>(), 30);
+    assert!(parts[1].tracks[0].start_time.unwrap() - playlist.tracks[0].start_time.unwrap() >= TimeDelta::hours(1));
+
+    assert!(playlist.split(&[SplitPoint::Track(31)]).is_err());
+    assert!(matches!(
+        playlist.split(&[SplitPoint::Track(0)]),
+        Err(PlaylistError::InvalidArgument(_))
+    ));
+    assert!(playlist.split(&[SplitPoint::Elapsed(TimeDelta::hours(12))]).is_err());
+    Ok(())
+}
+
+#[test]
+fn test_trim_playlist() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    playlist.trim(
+        Some(TimePosition::Clock(NaiveTime::from_hms_opt(19, 0, 0).unwrap())),
+        Some(TimePosition::Elapsed(TimeDelta::hours(1))),
+    )?;
+    assert!(!playlist.tracks.is_empty());
+    let first_start = playlist.tracks[0].start_time.unwrap();
+    assert!(first_start.time() >= NaiveTime::from_hms_opt(19, 0, 0).unwrap());
+    assert_eq!(playlist.date, Some(first_start));
+    assert!(playlist
+        .tracks
+        .iter()
+        .all(|t| t.start_time.unwrap().time() < NaiveTime::from_hms_opt(19, 38, 41).unwrap()));
+
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    assert!(playlist
+        .trim(Some(TimePosition::Elapsed(TimeDelta::hours(5))), None)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_skip_tracks() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let third_track = playlist.tracks[2].clone();
+    playlist.skip_tracks(2, 3)?;
+    assert_eq!(playlist.tracks.len(), 25);
+    assert_eq!(playlist.tracks[0].title, third_track.title);
+    assert_eq!(playlist.date, third_track.start_time);
+    assert!(playlist.skip_tracks(20, 5).is_err());
+    assert!(playlist.skip_tracks(usize::MAX, 1).is_err());
+    assert_eq!(playlist.tracks.len(), 25);
+    Ok(())
+}
+
+#[test]
+fn test_serato_csv_min_playtime() -> anyhow::Result<()> {
+    let test_file_path = TEST_FILES_DIR.join("serato 8.3.2024.csv");
+    let playlist = Playlist::new(&test_file_path)?;
+    let options = ParseOptions {
+        min_playtime: Some(TimeDelta::minutes(7)),
+        ..ParseOptions::default()
+    };
+    let filtered = Playlist::new_with_options(&test_file_path, &options)?;
+    assert!(filtered.tracks.len() < playlist.tracks.len());
+    assert!(filtered
+        .tracks
+        .iter()
+        .all(|t| t.play_time.is_none_or(|p| p >= TimeDelta::minutes(7))));
+    assert_eq!(filtered.total_duration, utils::get_total_playtime(&filtered.tracks));
+    Ok(())
+}
+
+#[test]
+fn test_rekordbox_min_playtime() -> anyhow::Result<()> {
+    let test_file_path = TEST_FILES_DIR.join("rekordbox.txt");
+    let playlist = Playlist::new(&test_file_path)?;
+    let options = ParseOptions {
+        min_playtime: Some(TimeDelta::minutes(6)),
+        ..ParseOptions::default()
+    };
+    let filtered = Playlist::new_with_options(&test_file_path, &options)?;
+    assert!(!filtered.tracks.is_empty());
+    assert!(filtered.tracks.len() < playlist.tracks.len());
+    assert!(filtered
+        .tracks
+        .iter()
+        .all(|t| t.play_time.is_none_or(|p| p >= TimeDelta::minutes(6))));
+    assert_eq!(filtered.total_duration, utils::get_total_playtime(&filtered.tracks));
+    Ok(())
+}
+
+#[test]
+fn test_rekordbox_dedupe_keeps_track_length() -> anyhow::Result<()> {
+    let playlist = Playlist::from_str(
+        "#\tTrack Title\tArtist\tTime\n\
+         1\tTitle 1\tArtist 1\t05:00\n\
+         2\tTitle 1\tArtist 1\t05:00\n\
+         3\tTitle 2\tArtist 2\t04:00\n",
+        "rekordbox.txt",
+        &ParseOptions {
+            dedupe: DedupePolicy::Consecutive,
+            ..ParseOptions::default()
+        },
+    )?;
+    assert_eq!(playlist.playlist_type, PlaylistType::Rekordbox);
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.tracks[0].play_time, Some(TimeDelta::minutes(5)));
+    assert_eq!(playlist.total_duration, Some(TimeDelta::minutes(9)));
+    Ok(())
+}
+
+#[test]
+fn test_serato_csv_extra_columns() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-serato-deck-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime,deck,bpm,key,notes\n\
+         Serato 30.3.2023,,\"30.3.2023, 16.04.53 EEST\",,,,,,\n\
+         Title 1,Artist 1,16.04.53 EEST,16.10.10 EEST,00:05:17,1,124,8A,\n\
+         Title 2,Artist 2,16.09.50 EEST,16.15.00 EEST,00:05:10,2,125.5,9A,mic break\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.tracks[0].deck, Some(1));
+    assert_eq!(playlist.tracks[1].deck, Some(2));
+    assert_eq!(playlist.tracks[1].bpm, Some(125.5));
+    assert_eq!(playlist.tracks[1].key.as_deref(), Some("9A"));
+    assert_eq!(playlist.tracks[0].notes, None);
+    assert_eq!(playlist.tracks[1].notes.as_deref(), Some("mic break"));
+    Ok(())
+}
+
+#[test]
+fn test_serato_set_over_midnight() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-midnight-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime\n\
+         New Year 31.12.2023,,\"31.12.2023, 23.50.00 EET\",,\n\
+         Title 1,Artist 1,23.50.00 EET,23.56.00 EET,\n\
+         Title 2,Artist 2,23.56.00 EET,00.03.00 EET,\n\
+         Title 3,Artist 3,00.03.00 EET,00.09.00 EET,\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    let new_year = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+    assert_eq!(playlist.tracks[1].play_time, Some(TimeDelta::minutes(7)));
+    assert_eq!(playlist.tracks[1].end_time.map(|t| t.date()), Some(new_year));
+    assert_eq!(playlist.tracks[2].start_time.map(|t| t.date()), Some(new_year));
+    assert_eq!(playlist.tracks[2].play_time, Some(TimeDelta::minutes(6)));
+    assert_eq!(playlist.total_duration, Some(TimeDelta::minutes(19)));
+    assert_eq!(
+        playlist.time_range(),
+        Some((
+            NaiveDate::from_ymd_opt(2023, 12, 31)
+                .unwrap()
+                .and_hms_opt(23, 50, 0)
+                .unwrap(),
+            new_year.and_hms_opt(0, 9, 0).unwrap()
+        ))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_serato_multi_day_session() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-multi-day-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime\n\
+         Festival 12.7.2024,,\"12.7.2024, 23.00.00 EEST\",,\n\
+         Title 1,Artist 1,23.00.00 EEST,23.30.00 EEST,\n\
+         Title 2,Artist 2,23.30.00 EEST,00.00.00 EEST,\n\
+         Title 3,Artist 3,00.00.00 EEST,00.30.00 EEST,\n\
+         Title 4,Artist 4,12.00.00 EEST,12.30.00 EEST,\n\
+         Title 5,Artist 5,23.45.00 EEST,00.15.00 EEST,\n\
+         Title 6,Artist 6,00.15.00 EEST,00.45.00 EEST,\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    let dates: Vec<_> = playlist
+        .tracks
+        .iter()
+        .filter_map(|t| t.start_time.map(|s| s.date()))
+        .collect();
+    assert_eq!(
+        dates,
+        [12, 12, 13, 13, 13, 14].map(|day| NaiveDate::from_ymd_opt(2024, 7, day).unwrap())
+    );
+    assert!(playlist
+        .tracks
+        .iter()
+        .all(|t| t.play_time == Some(TimeDelta::minutes(30))));
+    Ok(())
+}
+
+#[test]
+fn test_serato_out_of_order_row() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-out-of-order-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime\n\
+         Set 1.2.2024,,\"1.2.2024, 20.00.00 EET\",,\n\
+         Title 1,Artist 1,20.00.00 EET,20.05.00 EET,\n\
+         Title 2,Artist 2,20.10.00 EET,20.15.00 EET,\n\
+         Title 3,Artist 3,20.08.00 EET,20.12.00 EET,\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    let date = NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+    assert!(playlist
+        .tracks
+        .iter()
+        .all(|t| t.start_time.map(|s| s.date()) == Some(date)));
+    let issues = playlist.validate();
+    assert!(issues
+        .iter()
+        .any(|issue| issue.kind == IssueKind::StartTimeOrder && issue.track_index == 2));
+    Ok(())
+}
+
+#[test]
+fn test_fill_missing_times() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-missing-times-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime\n\
+         Set 1.2.2024,,\"1.2.2024, 20.00.00 EET\",,\n\
+         Title 1,Artist 1,20.00.00 EET,,00:05:00\n\
+         Title 2,Artist 2,20.05.00 EET,20.09.00 EET,\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    let raw = Playlist::new_with_options(&path, &ParseOptions::raw(None));
+    std::fs::remove_file(&path)?;
+    let (playlist, raw) = (playlist?, raw?);
+    let start = playlist.tracks[0].start_time.unwrap();
+    assert_eq!(playlist.tracks[0].end_time, Some(start + TimeDelta::minutes(5)));
+    assert_eq!(playlist.tracks[1].play_time, Some(TimeDelta::minutes(4)));
+    assert_eq!(raw.tracks[0].end_time, None);
+    Ok(())
+}
+
+#[test]
+fn test_set_start_times() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("rekordbox-2020.12.20.txt"))?;
+    assert!(playlist.tracks.iter().all(|t| t.start_time.is_none()));
+    assert!(playlist
+        .set_start_times(NaiveTime::from_hms_opt(22, 0, 0).unwrap())
+        .is_err());
+    playlist.tracks.retain(|t| t.play_time.is_some());
+    playlist.update_totals();
+    playlist.set_start_times(NaiveTime::from_hms_opt(22, 0, 0).unwrap())?;
+    let set_start = NaiveDate::from_ymd_opt(2020, 12, 20).unwrap().and_hms_opt(22, 0, 0);
+    assert_eq!(playlist.date, set_start);
+    assert_eq!(playlist.tracks[0].start_time, set_start);
+    assert_eq!(playlist.tracks[1].start_time, playlist.tracks[0].end_time);
+    let last = playlist.tracks.last().unwrap();
+    assert_eq!(
+        last.end_time,
+        set_start.zip(playlist.total_duration).map(|(s, d)| s + d)
+    );
+
+    playlist.tracks[2].play_time = None;
+    assert!(playlist
+        .set_start_times(NaiveTime::from_hms_opt(22, 0, 0).unwrap())
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_elapsed_column() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    assert_eq!(playlist.elapsed_column(&FormatOptions::default()), None);
+    let mut options = FormatOptions {
+        output_format: OutputFormat::Csv,
+        show_elapsed: true,
+        ..FormatOptions::default()
+    };
+    let elapsed = playlist.elapsed_column(&options).unwrap();
+    assert_eq!(elapsed[0], "00:00");
+    assert_eq!(elapsed[1], "01:34");
+
+    let csv = playlist.render_string_with_options(&options)?;
+    assert!(csv.starts_with("Elapsed,Artist,"));
+    assert!(csv.lines().nth(2).unwrap().starts_with("01:34,"));
+
+    options.output_format = OutputFormat::Txt;
+    let txt = playlist.render_string_with_options(&options)?;
+    assert!(txt.starts_with(&format!("00:00 {}\n", playlist.tracks[0])));
+
+    options.cue_offset = TimeDelta::seconds(30);
+    assert_eq!(playlist.elapsed_column(&options).unwrap()[..2], ["00:30", "02:04"]);
+    options.cue_offset = TimeDelta::seconds(-60);
+    assert_eq!(playlist.elapsed_column(&options).unwrap()[..2], ["00:00", "00:34"]);
+    Ok(())
+}
+
+#[test]
+fn test_round_playtime() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let total_duration = playlist.total_duration;
+    let contents = playlist.render_string_with_options(&FormatOptions {
+        output_format: OutputFormat::Csv,
+        playtime_rounding: Some(TimeDelta::minutes(1)),
+        ..FormatOptions::default()
+    })?;
+    // First track playtime is 5:11
+    assert!(contents.lines().nth(1).unwrap().contains(",5:00,"));
+    let total = utils::formatted_duration(total_duration.unwrap());
+    assert!(contents.lines().last().unwrap().contains(&total));
+    Ok(())
+}
+
+#[test]
+fn test_duration_format() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let contents = playlist.render_string_with_options(&FormatOptions {
+        output_format: OutputFormat::Csv,
+        duration_format: DurationFormat::Padded,
+        ..FormatOptions::default()
+    })?;
+    // First track playtime is 5:11
+    assert!(contents.lines().nth(1).unwrap().contains(",00:05:11,"));
+    let total = utils::format_duration(playlist.total_duration.unwrap(), DurationFormat::Padded);
+    assert!(contents.lines().last().unwrap().contains(&total));
+    Ok(())
+}
+
+#[test]
+fn test_save_with_options() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let options = SaveOptions {
+        format: FormatOptions {
+            output_format: OutputFormat::Txt,
+            start_number: Some(10),
+            duration_format: DurationFormat::Padded,
+            ..FormatOptions::default()
+        },
+        overwrite: false,
+    };
+    let output_path = std::env::temp_dir().join(format!("playfmt-save-options-{}.csv", std::process::id()));
+    playlist.save_with_options(&output_path, &options)?;
+    let contents = std::fs::read_to_string(&output_path)?;
+    assert!(matches!(
+        playlist.save_with_options(&output_path, &options),
+        Err(PlaylistError::InvalidArgument(_))
+    ));
+    std::fs::remove_file(&output_path)?;
+    // Saved as text regardless of the file extension
+    assert_eq!(contents, playlist.render_string_with_options(&options.format)?);
+    assert!(contents.starts_with(&format!("{}\n", playlist.tracks[0])));
+    Ok(())
+}
+
+#[test]
+fn test_format_options_dedupe() -> anyhow::Result<()> {
+    let playlist = PlaylistBuilder::new("Repeats")
+        .tracks(["A - One", "A - One", "B - Two"].map(Track::from_name))
+        .build();
+    let options = FormatOptions {
+        output_format: OutputFormat::Txt,
+        dedupe: DedupePolicy::Consecutive,
+        ..FormatOptions::default()
+    };
+    assert_eq!(playlist.render_string_with_options(&options)?, "A - One\nB - Two\n");
+    // Writing does not change the playlist
+    assert_eq!(playlist.len(), 3);
+    Ok(())
+}
+
+#[test]
+fn test_csv_footer() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let mut options = FormatOptions::for_format(&OutputFormat::Csv);
+    let total = options.formatted_duration(playlist.total_duration.unwrap_or_default());
+    let default_csv = playlist.render_string_with_options(&options)?;
+    assert!(default_csv.lines().last().is_some_and(|line| line.contains(&total)));
+
+    options.footer = Some(Vec::new());
+    let bare_csv = playlist.render_string_with_options(&options)?;
+    assert_eq!(bare_csv.lines().count(), playlist.len() + 1);
+
+    options.footer = Some(vec![FooterField::Tracks, FooterField::Duration, FooterField::Average]);
+    let full_csv = playlist.render_string_with_options(&options)?;
+    let footer = full_csv.lines().last().unwrap_or_default();
+    assert!(footer.contains(&format!("{} tracks, avg. ", playlist.len())));
+    assert!(footer.contains(&total));
+    let parsed = Playlist::from_reader(std::io::Cursor::new(full_csv), "footer.csv", &ParseOptions::default())?;
+    assert_eq!(parsed.len(), playlist.len());
+    Ok(())
+}
+
+#[test]
+fn test_serato_timezone() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    assert_eq!(playlist.timezone, Some(chrono_tz::Tz::Europe__Helsinki));
+    let start_time = playlist.tracks[0].start_time;
+    let date = playlist.date;
+    playlist.convert_timezone(chrono_tz::Tz::Europe__London);
+    assert_eq!(playlist.timezone, Some(chrono_tz::Tz::Europe__London));
+    assert_eq!(
+        playlist.tracks[0].start_time,
+        start_time.map(|t| t - TimeDelta::hours(2))
+    );
+    assert_eq!(playlist.date, date.map(|t| t - TimeDelta::hours(2)));
+
+    let mut rekordbox = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    assert_eq!(rekordbox.timezone, None);
+    rekordbox.convert_timezone(chrono_tz::Tz::UTC);
+    assert_eq!(rekordbox.timezone, Some(chrono_tz::Tz::UTC));
+    Ok(())
+}
+
+#[test]
+fn test_sort_tracks() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let first_track = playlist.tracks[0].clone();
+    playlist.sort_tracks(SortKey::Artist, false);
+    assert!(playlist
+        .tracks
+        .windows(2)
+        .all(|w| w[0].artist.to_lowercase() <= w[1].artist.to_lowercase()));
+
+    playlist.sort_tracks(SortKey::Playtime, true);
+    assert!(playlist.tracks.windows(2).all(|w| w[0].play_time >= w[1].play_time));
+
+    playlist.sort_tracks(SortKey::StartTime, false);
+    assert_eq!(playlist.tracks[0], first_track);
+    Ok(())
+}
+
+#[test]
+fn test_reverse_sort_is_stable() {
+    let mut playlist = PlaylistBuilder::new("Set")
+        .track(Track::from_name("B - One") + TimeDelta::minutes(3))
+        .track(Track::from_name("A - Two") + TimeDelta::minutes(5))
+        .track(Track::from_name("C - Three") + TimeDelta::minutes(3))
+        .build();
+    playlist.sort_tracks(SortKey::Playtime, true);
+    let titles: Vec<&str> = playlist.tracks.iter().map(|t| t.title.as_str()).collect();
+    assert_eq!(titles, ["Two", "One", "Three"]);
+}
+
+#[test]
+fn test_start_number() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    assert_eq!(playlist.track_number(0, &FormatOptions::default()), 1);
+    let options = FormatOptions {
+        output_format: OutputFormat::Csv,
+        start_number: Some(16),
+        ..FormatOptions::default()
+    };
+    assert_eq!(playlist.track_number(0, &options), 16);
+    assert_eq!(playlist.track_number(3, &options), 19);
+
+    let contents = playlist.render_string_with_options(&options)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().starts_with("#,Artist,,Title"));
+    assert!(lines.next().unwrap().starts_with("16,"));
+    Ok(())
+}
+
+#[test]
+fn test_rename_and_remove_tracks() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    let second_track = playlist.tracks[1].clone();
+    playlist.rename_tracks(&["2:New Artist - New Title".parse::<TrackRename>()?])?;
+    assert_eq!(playlist.tracks[1].artist, "New Artist");
+    assert_eq!(playlist.tracks[1].title, "New Title");
+    assert_eq!(playlist.tracks[1].start_time, second_track.start_time);
+
+    let fourth_track = playlist.tracks[3].clone();
+    playlist.remove_tracks(&[1, 3, 3])?;
+    assert_eq!(playlist.tracks.len(), 28);
+    assert_eq!(playlist.tracks[0].artist, "New Artist");
+    assert_eq!(playlist.tracks[1], fourth_track);
+
+    assert!(playlist.remove_tracks(&[29]).is_err());
+    assert!(playlist
+        .rename_tracks(&["29:Artist - Title".parse::<TrackRename>()?])
+        .is_err());
+    let rename = TrackRename {
+        number: 0,
+        artist: "Artist".to_string(),
+        title: "Title".to_string(),
+    };
+    assert!(matches!(
+        playlist.rename_tracks(&[rename]),
+        Err(PlaylistError::InvalidArgument(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_notes_in_output() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    assert!(!playlist.has_notes());
+    let dir = std::env::temp_dir();
+    let notes_path = dir.join(format!("playfmt-notes-{}.notes.toml", std::process::id()));
+    std::fs::write(&notes_path, "2 = \"request\"\n")?;
+    let count = playlist.apply_notes_file(&notes_path);
+    std::fs::remove_file(&notes_path)?;
+    assert_eq!(count?, 1);
+    assert_eq!(playlist.tracks[1].notes.as_deref(), Some("request"));
+
+    let output_path = dir.join(format!("playfmt-notes-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let contents = std::fs::read_to_string(&output_path)?;
+    std::fs::remove_file(&output_path)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().ends_with(",Notes"));
+    assert!(lines.next().unwrap().ends_with(','));
+    assert!(lines.next().unwrap().ends_with(",request"));
+    Ok(())
+}
+
+#[test]
+fn test_rekordbox_metadata_in_output() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    let track = &playlist.tracks[0];
+    assert_eq!(track.bpm, Some(122.32));
+    assert_eq!(track.key.as_deref(), Some("6A"));
+    assert_eq!(track.genre.as_deref(), Some("Dance"));
+    assert_eq!(track.length, TimeDelta::try_seconds(7 * 60 + 7));
+    assert_eq!(track.date_added, chrono::NaiveDate::from_ymd_opt(2023, 3, 13));
+    assert_eq!(playlist.metadata_columns(), vec![0, 1, 2, 7, 8]);
+
+    let output_path = std::env::temp_dir().join(format!("playfmt-metadata-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let formatted = Playlist::new(&output_path);
+    let contents = std::fs::read_to_string(&output_path)?;
+    std::fs::remove_file(&output_path)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().ends_with(",BPM,Key,Genre,Length,Date Added"));
+    assert!(lines.next().unwrap().ends_with(",122.32,6A,Dance,7:07,2023-03-13"));
+
+    let formatted = formatted?;
+    assert_eq!(formatted.tracks[0].bpm, Some(122.32));
+    assert_eq!(formatted.tracks[0].genre.as_deref(), Some("Dance"));
+    assert_eq!(formatted.tracks[0].length, track.length);
+    Ok(())
+}
+
+#[test]
+fn test_unknown_columns_round_trip() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir();
+    let input_path = dir.join(format!("playfmt-extras-{}.csv", std::process::id()));
+    std::fs::write(
+        &input_path,
+        "Artist,,Title,Playtime,Comment,Notes\n\
+         Artist 1,-,Title 1,5:17,crowd favourite,\n\
+         Artist 2,-,Title 2,4:10,,request\n",
+    )?;
+    let playlist = Playlist::new(&input_path);
+    std::fs::remove_file(&input_path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.extra_columns(), vec!["Comment".to_string()]);
+    assert_eq!(playlist.tracks[1].notes.as_deref(), Some("request"));
+
+    let output_path = dir.join(format!("playfmt-extras-out-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let contents = std::fs::read_to_string(&output_path)?;
+    std::fs::remove_file(&output_path)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().ends_with(",Comment,Notes"));
+    assert!(lines.next().unwrap().ends_with(",crowd favourite,"));
+    assert!(lines.next().unwrap().ends_with(",,request"));
+
+    let mut options = FormatOptions {
+        output_format: OutputFormat::Csv,
+        include_extras: false,
+        ..FormatOptions::default()
+    };
+    let csv = playlist.render_string_with_options(&options)?;
+    assert!(csv.lines().next().unwrap().ends_with(",Notes"));
+    assert!(!csv.contains("crowd favourite"));
+    options.output_format = OutputFormat::Json;
+    assert!(!playlist
+        .render_string_with_options(&options)?
+        .contains("crowd favourite"));
+    Ok(())
+}
+
+#[test]
+fn test_formatted_csv_times_round_trip() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    for (index, time_format) in [TimeFormat::Default, TimeFormat::Iso].into_iter().enumerate() {
+        let options = FormatOptions {
+            output_format: OutputFormat::Csv,
+            time_format,
+            ..FormatOptions::default()
+        };
+        let output_path = std::env::temp_dir().join(format!("playfmt-times-{}-{index}.csv", std::process::id()));
+        std::fs::write(&output_path, playlist.to_bytes_with_options(&options)?)?;
+        let formatted = Playlist::new(&output_path);
+        std::fs::remove_file(&output_path)?;
+        let formatted = formatted?;
+        assert_eq!(formatted.playlist_type, PlaylistType::Formatted);
+        assert_eq!(formatted.tracks.len(), playlist.tracks.len());
+        for (original, parsed) in playlist.tracks.iter().zip(&formatted.tracks) {
+            assert_eq!(parsed.start_time, original.start_time);
+            assert_eq!(parsed.end_time, original.end_time);
+            assert_eq!(parsed.play_time, original.play_time);
+        }
+        assert_eq!(formatted.total_duration, playlist.total_duration);
+        assert_eq!(formatted.date, playlist.tracks[0].start_time);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_hand_edited_formatted_csv() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-hand-edited-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "artist,,song,playtime,bpm,comment\n\
+         Artist 1,-,Title 1,5:17,124,opener\n\
+         Artist 2,-,Title 2,4:10,,\n",
+    )?;
+    let playlist = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.playlist_type, PlaylistType::Formatted);
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.tracks[0].title, "Title 1");
+    assert_eq!(playlist.tracks[0].bpm, Some(124.0));
+    assert_eq!(playlist.tracks[1].play_time, TimeDelta::try_seconds(4 * 60 + 10));
+    assert_eq!(playlist.extra_columns(), vec!["comment".to_string()]);
+    Ok(())
+}
+
+#[test]
+fn test_strict_parsing() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir();
+    let strict = ParseOptions {
+        strict: true,
+        ..ParseOptions::default()
+    };
+    let serato_path = dir.join(format!("playfmt-strict-{}.csv", std::process::id()));
+    std::fs::write(
+        &serato_path,
+        "name,artist,start time,end time,playtime\n\
+         Serato 30.3.2023,,\"30.3.2023, 16.04.53 EEST\",,\n\
+         Title 1,Artist 1,16.04.53 EEST,16.10.10 EEST,00:05:17\n\
+         Title 2,Artist 2,16 09 50,16.15.00 EEST,00:05:10\n\
+         Title 3,Artist 3\n",
+    )?;
+    let lenient = Playlist::new(&serato_path);
+    let strict_result = Playlist::new_with_options(&serato_path, &strict);
+    std::fs::remove_file(&serato_path)?;
+    assert_eq!(lenient?.tracks.len(), 2);
+    let error = strict_result.unwrap_err().to_string();
+    assert!(error.contains("Line 5"), "{error}");
+
+    let formatted_path = dir.join(format!("playfmt-strict-formatted-{}.csv", std::process::id()));
+    std::fs::write(
+        &formatted_path,
+        "Artist,,Title,Playtime,Start time\n\
+         Artist 1,-,Title 1,5:17,2024.03.08 20:00:00\n\
+         Artist 2,-,Title 2,4:10,8.3.2024 20:05\n",
+    )?;
+    let lenient = Playlist::new(&formatted_path);
+    let strict_result = Playlist::new_with_options(&formatted_path, &strict);
+    std::fs::remove_file(&formatted_path)?;
+    assert_eq!(lenient?.tracks[1].start_time, None);
+    let error = strict_result.unwrap_err().to_string();
+    assert!(
+        error.ends_with("Line 3: invalid start time '8.3.2024 20:05'"),
+        "{error}"
+    );
+
+    let unknown_path = dir.join(format!("playfmt-strict-unknown-{}.csv", std::process::id()));
+    std::fs::write(
+        &unknown_path,
+        "Artist,,Title,Comment\n\
+         Artist 1,-,Title 1,opener\n",
+    )?;
+    let unknown = Playlist::new_with_options(&unknown_path, &strict);
+    std::fs::remove_file(&unknown_path)?;
+    assert!(unknown.unwrap_err().to_string().ends_with("'Comment'"));
+    assert!(Playlist::new_with_options(&TEST_FILES_DIR.join("serato.csv"), &strict).is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_quality_warnings() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato 8.3.2024.csv"))?;
+    assert!(playlist.quality_warnings().is_empty());
+
+    let path = std::env::temp_dir().join(format!("playfmt-quality-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "name,artist,start time,end time,playtime\n\
+         Serato 8.3.2024,,\"8.3.2024, 20.00.00 EET\",,\n\
+         Title 1,Artist 1,20.00.00 EET,20.05.00 EET,00:05:00\n\
+         Title 1,Artist 1,20.05.00 EET,20.05.00 EET,00:00:00\n\
+         Title 3,,20.02.00 EET,20.01.00 EET,00:04:00\n",
+    )?;
+    let playlist = Playlist::new_with_options(&path, &ParseOptions::raw(None));
+    std::fs::remove_file(&path)?;
+    let warnings = playlist?.quality_warnings();
+    assert_eq!(
+        warnings,
+        vec![
+            "Track 2: Zero second playtime: Artist 1 - Title 1",
+            "Track 2: Duplicate of the previous track: Artist 1 - Title 1",
+            "Track 3: Missing artist for 'Title 3'",
+            "Track 3: Start time 20:02:00 is before previous track start time 20:05:00",
+        ]
+    );
+
+    let path = std::env::temp_dir().join(format!("playfmt-quality-order-{}.csv", std::process::id()));
+    std::fs::write(
+        &path,
+        "Artist,,Title,Start time,End time\n\
+         Artist 1,-,Title 1,2024.03.08 20:00:00,2024.03.08 20:05:00\n\
+         Artist 2,-,Title 2,2024.03.08 19:58:00,2024.03.08 19:57:00\n",
+    )?;
+    let playlist = Playlist::new_with_options(&path, &ParseOptions::raw(None));
+    std::fs::remove_file(&path)?;
+    assert_eq!(
+        playlist?.quality_warnings(),
+        vec![
+            "Track 2: End time 19:57:00 is before start time 19:58:00",
+            "Track 2: Start time 19:58:00 is before previous track start time 20:00:00",
+        ]
+    );
+    Ok(())
+}
+
+#[test]
+fn test_dedupe_all_play_counts() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir();
+    let input_path = dir.join(format!("playfmt-plays-{}.csv", std::process::id()));
+    std::fs::write(
+        &input_path,
+        "name,artist,start time,end time,playtime\n\
+         Serato 8.3.2024,,\"8.3.2024, 20.00.00 EET\",,\n\
+         Title 1,Artist 1,20.00.00 EET,20.03.00 EET,00:03:00\n\
+         Title 2,Artist 2,20.03.00 EET,20.07.00 EET,00:04:00\n\
+         Title 1,Artist 1,20.07.00 EET,20.09.00 EET,00:02:00\n",
+    )?;
+    let options = ParseOptions {
+        dedupe: DedupePolicy::All,
+        ..ParseOptions::default()
+    };
+    let playlist = Playlist::new_with_options(&input_path, &options);
+    std::fs::remove_file(&input_path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.tracks[0].plays, 2);
+    assert_eq!(playlist.tracks[0].play_time, TimeDelta::try_minutes(5));
+    assert_eq!(playlist.tracks[0].display_title(), "Title 1 ×2");
+
+    let output_path = dir.join(format!("playfmt-plays-out-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let formatted = Playlist::new(&output_path);
+    let contents = std::fs::read_to_string(&output_path)?;
+    std::fs::remove_file(&output_path)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().ends_with(",Plays"));
+    assert!(lines.next().unwrap().ends_with(",2"));
+    assert_eq!(formatted?.tracks[0].plays, 2);
+    Ok(())
+}
+
+#[test]
+fn test_assign_djs() -> anyhow::Result<()> {
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let split = playlist.tracks[3]
+        .start_time
+        .unwrap()
+        .time()
+        .format("%H:%M:%S")
+        .to_string();
+    let assignments = [
+        format!("A=00:00-{split}").parse::<DjAssignment>()?,
+        format!("B={split}-00:00").parse::<DjAssignment>()?,
+    ];
+    assert_eq!(playlist.assign_djs(&assignments), playlist.tracks.len());
+    assert_eq!(playlist.tracks[2].dj.as_deref(), Some("A"));
+    assert_eq!(playlist.tracks[3].dj.as_deref(), Some("B"));
+
+    let output_path = std::env::temp_dir().join(format!("playfmt-djs-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let formatted = Playlist::new(&output_path);
+    std::fs::remove_file(&output_path)?;
+    let formatted = formatted?;
+    assert_eq!(formatted.tracks[3].dj.as_deref(), Some("B"));
+    assert!(formatted.extra_columns().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_mashups_round_trip() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir();
+    let input_path = dir.join(format!("playfmt-mashups-{}.csv", std::process::id()));
+    std::fs::write(
+        &input_path,
+        "name,artist,start time,end time,playtime\n\
+         Serato 30.3.2023,,\"30.3.2023, 16.04.53 EEST\",,\n\
+         Title 1 w/ Singer - Acapella,Artist 1,16.04.53 EEST,16.10.10 EEST,00:05:17\n\
+         Loop,Artist 2,16.06.00 EEST,16.08.00 EEST,00:02:00\n\
+         Title 3,Artist 3,16.09.50 EEST,16.15.00 EEST,00:05:10\n",
+    )?;
+    let options = ParseOptions {
+        mashups: true,
+        ..ParseOptions::default()
+    };
+    let playlist = Playlist::new_with_options(&input_path, &options);
+    std::fs::remove_file(&input_path)?;
+    let playlist = playlist?;
+    assert_eq!(playlist.tracks.len(), 2);
+    assert_eq!(playlist.tracks[0].title, "Title 1");
+    assert_eq!(playlist.tracks[0].mashups_text(), "Singer - Acapella; Artist 2 - Loop");
+
+    let output_path = dir.join(format!("playfmt-mashups-out-{}.csv", std::process::id()));
+    playlist.write_file(&output_path, &OutputFormat::Csv)?;
+    let formatted = Playlist::new(&output_path);
+    let contents = std::fs::read_to_string(&output_path)?;
+    std::fs::remove_file(&output_path)?;
+    let mut lines = contents.lines();
+    assert!(lines.next().unwrap().ends_with(",Mashup"));
+    assert!(lines.next().unwrap().ends_with(",Singer - Acapella; Artist 2 - Loop"));
+
+    let formatted = formatted?;
+    assert_eq!(formatted.tracks[0].mashups.len(), 2);
+    assert_eq!(formatted.tracks[0].mashups[1].artist, "Artist 2");
+    assert!(formatted.extra_columns().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_append_to_archive() -> anyhow::Result<()> {
+    let serato = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let rekordbox = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    let archive_path = std::env::temp_dir().join(format!("playfmt-archive-{}.csv", std::process::id()));
+    let options = FormatOptions::default();
+    serato.append_to_path(&archive_path, &options)?;
+    rekordbox.append_to_path(&archive_path, &options)?;
+    let contents = std::fs::read_to_string(&archive_path)?;
+    std::fs::remove_file(&archive_path)?;
+    let lines: Vec<&str> = contents.lines().collect();
+    assert_eq!(lines.len(), 1 + serato.tracks.len() + rekordbox.tracks.len());
+    assert_eq!(lines[0], "Date,Set,Artist,,Title,Playtime");
+    assert!(lines[1].starts_with("2023.03.30,Serato 30.3.2023,"));
+
+    assert!(serato
+        .append_to_path(&TEST_FILES_DIR.join("serato.m3u"), &options)
+        .is_err());
+    Ok(())
+}
+
+#[test]
+fn test_append_to_archive_with_duration_format() -> anyhow::Result<()> {
+    let serato = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let archive_path = std::env::temp_dir().join(format!("playfmt-archive-seconds-{}.csv", std::process::id()));
+    let options = FormatOptions {
+        duration_format: DurationFormat::Seconds,
+        ..FormatOptions::default()
+    };
+    serato.append_to_path(&archive_path, &options)?;
+    let contents = std::fs::read_to_string(&archive_path)?;
+    std::fs::remove_file(&archive_path)?;
+    assert!(serato.tracks.iter().all(|track| track.play_time.is_some()));
+    for (line, track) in contents.lines().skip(1).zip(&serato.tracks) {
+        let play_time = track.play_time.map_or(String::new(), |d| options.formatted_playtime(d));
+        assert!(line.ends_with(&format!(",{play_time}")), "{line}");
+        assert!(!play_time.contains(':'));
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "xlsx")]
+fn test_append_to_excel_archive() -> anyhow::Result<()> {
+    use calamine::{Data, Reader, Xlsx};
+
+    let serato = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let rekordbox = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    let archive_path = std::env::temp_dir().join(format!("playfmt-archive-{}.xlsx", std::process::id()));
+    let options = FormatOptions::default();
+    serato.append_to_path(&archive_path, &options)?;
+    rekordbox.append_to_path(&archive_path, &options)?;
+    serato.append_to_path(&archive_path, &options)?;
+    let mut archive: Xlsx<_> = calamine::open_workbook(&archive_path)?;
+    std::fs::remove_file(&archive_path)?;
+    assert_eq!(
+        archive.sheet_names(),
+        [
+            serato.name.clone(),
+            rekordbox.name.clone(),
+            format!("{} (2)", serato.name)
+        ]
+    );
+    let first = archive.worksheet_range(&serato.name)?;
+    assert_eq!(first.get_value((0, 0)), Some(&Data::String("Artist".to_string())));
+    assert_eq!(
+        first.get_value((1, 0)),
+        Some(&Data::String(serato.tracks[0].artist.clone()))
+    );
+    assert_eq!(first.height(), 1 + serato.tracks.len() + 1);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "xlsx")]
+fn test_excel_stats_sheet() -> anyhow::Result<()> {
+    use calamine::{Reader, Xlsx};
+    use playlist_formatter::writer::DEFAULT_WRITERS;
+
+    let mut playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let writer = DEFAULT_WRITERS.for_format(&OutputFormat::Xlsx)?;
+    let sheet_names = |playlist: &Playlist, options: &FormatOptions| -> anyhow::Result<Vec<String>> {
+        let bytes = writer.write(playlist, options)?;
+        let workbook = Xlsx::new(std::io::Cursor::new(bytes))?;
+        Ok(workbook.sheet_names())
+    };
+
+    let options = FormatOptions::for_format(&OutputFormat::Xlsx);
+    assert_eq!(sheet_names(&playlist, &options)?, [playlist.name.clone()]);
+
+    let options = FormatOptions {
+        stats_sheet: true,
+        ..options
+    };
+    assert_eq!(
+        sheet_names(&playlist, &options)?,
+        [playlist.name.clone(), "Stats".to_string()]
+    );
+
+    playlist.name = "Stats".to_string();
+    assert_eq!(sheet_names(&playlist, &options)?, ["Stats", "Stats (2)"]);
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "xlsx")]
+fn test_is_up_to_date() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    for format in [OutputFormat::Csv, OutputFormat::Xlsx] {
+        let output_path = std::env::temp_dir().join(format!(
+            "playfmt-up-to-date-{}.{}",
+            std::process::id(),
+            format.to_extension()
+        ));
+        let options = FormatOptions::default();
+        assert!(!playlist.is_up_to_date(&output_path, &options));
+        playlist.write_file(&output_path, &format)?;
+        assert!(playlist.is_up_to_date(&output_path, &options));
+        std::fs::write(&output_path, "changed")?;
+        let up_to_date = playlist.is_up_to_date(&output_path, &options);
+        std::fs::remove_file(&output_path)?;
+        assert!(!up_to_date);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_error_kinds() -> anyhow::Result<()> {
+    let missing = Playlist::new(&TEST_FILES_DIR.join("does-not-exist.csv"));
+    assert!(matches!(missing, Err(PlaylistError::Io { .. })));
+    let no_extension = Playlist::new(&TEST_FILES_DIR.join("serato"));
+    assert!(matches!(no_extension, Err(PlaylistError::UnsupportedFormat(_))));
+
+    let path = std::env::temp_dir().join(format!("playfmt-error-kinds-{}.csv", std::process::id()));
+    std::fs::write(&path, "name,performer,start time\nSerato 8.3.2024,,\n")?;
+    let result = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    match result {
+        Err(PlaylistError::MissingField { field, suggestion, .. }) => {
+            assert_eq!(field, "artist");
+            assert_eq!(suggestion.as_deref(), Some("performer"));
+        }
+        other => panic!("Expected a missing field error, got: {other:?}"),
+    }
+    Ok(())
+}
+
+/// Reader for a CSV with only `Song` and `Performer` columns.
+struct SongListReader;
+
+impl PlaylistReader for SongListReader {
+    fn name(&self) -> &str {
+        "Song list CSV"
+    }
+
+    fn detect(&self, input: &PlaylistInput) -> bool {
+        input.has_header("Performer")
+    }
+
+    fn read(&self, input: PlaylistInput, _options: &ParseOptions) -> playlist_formatter::error::Result<Playlist> {
+        input.require_fields(self.name(), &["Song", "Performer"])?;
+        let tracks = input
+            .rows
+            .map(|row| {
+                row.map(|row| {
+                    Track::new(
+                        row.get("Performer").unwrap_or_default().to_string(),
+                        row.get("Song").unwrap_or_default().to_string(),
+                    )
+                })
+            })
+            .collect::<playlist_formatter::error::Result<Vec<Track>>>()?;
+        Ok(PlaylistBuilder::new(input.name).file(input.path).tracks(tracks).build())
+    }
+}
+
+#[test]
+fn test_custom_reader() -> anyhow::Result<()> {
+    let path = std::env::temp_dir().join(format!("playfmt-custom-reader-{}.csv", std::process::id()));
+    std::fs::write(&path, "Song,Performer\nTitle 1,Artist 1\nTitle 2,Artist 2\n")?;
+    let mut readers = ReaderRegistry::default();
+    readers.register(SongListReader);
+    let custom = Playlist::new_with_readers(&path, &ParseOptions::default(), &readers);
+    let default = Playlist::new(&path);
+    std::fs::remove_file(&path)?;
+    let custom = custom?;
+    assert_eq!(custom.tracks.len(), 2);
+    assert_eq!(custom.tracks[1].artist, "Artist 2");
+    assert!(matches!(default, Err(PlaylistError::MissingField { .. })));
+    Ok(())
+}
+
+/// Writer for a plain list of artists.
+struct ArtistListWriter;
+
+impl PlaylistWriter for ArtistListWriter {
+    fn extension(&self) -> &str {
+        "artists"
+    }
+
+    fn write(&self, playlist: &Playlist, _options: &FormatOptions) -> playlist_formatter::error::Result<Vec<u8>> {
+        Ok(playlist
+            .tracks
+            .iter()
+            .map(|track| format!("{}\n", track.artist))
+            .collect::<String>()
+            .into_bytes())
+    }
+}
+
+#[test]
+fn test_custom_writer() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    let path = std::env::temp_dir().join(format!("playfmt-custom-writer-{}.artists", std::process::id()));
+    assert!(matches!(
+        playlist.save_to_path(&path, true),
+        Err(PlaylistError::UnsupportedFormat(_))
+    ));
+    let mut writers = WriterRegistry::default();
+    writers.register(ArtistListWriter);
+    playlist.save_with_writers(&path, true, &writers)?;
+    let contents = std::fs::read_to_string(&path)?;
+    std::fs::remove_file(&path)?;
+    assert_eq!(contents.lines().count(), playlist.tracks.len());
+    assert_eq!(contents.lines().next(), Some(playlist.tracks[0].artist.as_str()));
+    Ok(())
+}
+
+#[test]
+fn test_playlist_as_collection() -> anyhow::Result<()> {
+    let playlist = Playlist::new(&TEST_FILES_DIR.join("serato.csv"))?;
+    assert_eq!(playlist.len(), playlist.tracks.len());
+    assert!(!playlist.is_empty());
+    assert_eq!(playlist[1], playlist.tracks[1]);
+    assert_eq!(playlist.iter().count(), playlist.len());
+    let artists: Vec<&str> = (&playlist).into_iter().map(|t| t.artist.as_str()).collect();
+    assert_eq!(artists[0], playlist.tracks[0].artist);
+    let mut count = 0;
+    for track in &playlist {
+        assert!(!track.title.is_empty());
+        count += 1;
+    }
+    assert_eq!(count, playlist.len());
+    let tracks: Vec<Track> = playlist.into_iter().collect();
+    assert_eq!(tracks.len(), count);
+    assert!(PlaylistBuilder::new("Empty").build().is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_mutation_keeps_totals() -> anyhow::Result<()> {
+    let mut playlist = PlaylistBuilder::new("Friday")
+        .track(Track::new("Artist".to_string(), "Title".to_string()) + TimeDelta::minutes(5))
+        .build();
+    playlist.push_track(Track::new("A Much Longer Artist".to_string(), "Song".to_string()) + TimeDelta::minutes(4));
+    assert_eq!(playlist.max_artist_length, "A Much Longer Artist".len());
+    assert_eq!(playlist.total_duration, TimeDelta::try_minutes(9));
+
+    playlist.push_track(Track::new("A Much Longer Artist".to_string(), "Song".to_string()) + TimeDelta::minutes(2));
+    assert_eq!(playlist.dedup_consecutive(), 1);
+    assert_eq!(playlist.len(), 2);
+    assert_eq!(playlist.total_duration, TimeDelta::try_minutes(11));
+
+    playlist.sort_by(|a, b| a.title.cmp(&b.title));
+    assert_eq!(playlist[0].title, "Song");
+
+    let removed = playlist.remove_track(0);
+    assert_eq!(removed.map(|t| t.artist), Some("A Much Longer Artist".to_string()));
+    assert!(playlist.remove_track(5).is_none());
+    assert_eq!(playlist.max_artist_length, "Artist".len());
+    assert_eq!(playlist.total_duration, TimeDelta::try_minutes(5));
+
+    let other = PlaylistBuilder::new("Saturday")
+        .track(Track::new("Other Artist".to_string(), "Other Title".to_string()) + TimeDelta::minutes(3))
+        .build();
+    playlist.merge(other);
+    assert_eq!(playlist.len(), 2);
+    assert_eq!(playlist.max_title_length, "Other Title".len());
+
+    playlist.retain(|t| t.artist == "Artist");
+    assert_eq!(playlist.len(), 1);
+    assert_eq!(playlist.max_title_length, "Title".len());
+    assert_eq!(playlist.total_duration, TimeDelta::try_minutes(5));
+    Ok(())
+}
+
+#[test]
+fn test_convert_many() -> anyhow::Result<()> {
+    let dir = std::env::temp_dir().join(format!("playfmt-convert-many-{}", std::process::id()));
+    let jobs = [
+        ConvertJob::new(TEST_FILES_DIR.join("serato.csv"), dir.join("serato.csv")),
+        ConvertJob::new(TEST_FILES_DIR.join("missing.csv"), dir.join("missing.csv")),
+        ConvertJob::new(TEST_FILES_DIR.join("rekordbox.txt"), dir.join("rekordbox.json")),
+    ];
+    let completed: Arc<Mutex<Vec<(usize, usize)>>> = Arc::default();
+    let recorded = Arc::clone(&completed);
+    let options = ConvertOptions {
+        parse: ParseOptions {
+            progress: Some(ProgressCallback::new(move |event| {
+                if let ProgressEvent::FileCompleted { completed, total, .. } = event {
+                    recorded.lock().unwrap().push((*completed, *total));
+                }
+            })),
+            ..ParseOptions::default()
+        },
+        ..ConvertOptions::default()
+    };
+    let reports = convert::convert_many(&jobs, &options);
+    assert_eq!(reports.len(), 3);
+    let mut completed = completed.lock().unwrap().clone();
+    completed.sort_unstable();
+    assert_eq!(completed, vec![(1, 3), (2, 3), (3, 3)]);
+    let serato = reports[0].as_ref().expect("serato.csv should convert");
+    assert_eq!(serato.playlist_type, PlaylistType::Serato);
+    assert!(serato.tracks > 0);
+    assert!(matches!(reports[1], Err(PlaylistError::Io { .. })));
+    let rekordbox = reports[2].as_ref().expect("rekordbox.txt should convert");
+    assert_eq!(rekordbox.playlist_type, PlaylistType::Rekordbox);
+    assert!(std::fs::read_to_string(&rekordbox.output)?.starts_with('{'));
+
+    // Existing outputs are kept unless overwriting is enabled
+    assert!(convert::convert(&jobs[0], &ConvertOptions::default()).is_err());
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+#[test]
+fn test_m3u_uses_file_locations() -> anyhow::Result<()> {
+    let options = FormatOptions::for_format(&OutputFormat::M3u);
+    let playlist = Playlist::from_str(
+        "Artist,Title,Playtime,Location\n\
+         A,One,3:00,/music/a - one.mp3\n\
+         B,Two,4:00,\n",
+        "friday.csv",
+        &ParseOptions::default(),
+    )?;
+    assert_eq!(
+        playlist.render_string_with_options(&options)?,
+        "#EXTM3U\n#PLAYLIST:friday\n#EXTINF:180,A - One\n/music/a - one.mp3\n"
+    );
+
+    let without_locations = Playlist::from_str(
+        "Artist,Title,Playtime\nA,One,3:00\n",
+        "friday.csv",
+        &ParseOptions::default(),
+    )?;
+    assert!(matches!(
+        without_locations.render_string_with_options(&options),
+        Err(PlaylistError::UnsupportedFormat(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_parse_from_memory() -> anyhow::Result<()> {
+    let playlist = Playlist::from_str(
+        "Artist,Title,Playtime\nA,One,3:00\nB,Two,4:00\n",
+        "friday.csv",
+        &ParseOptions::default(),
+    )?;
+    assert_eq!(playlist.name, "friday");
+    assert_eq!(playlist.playlist_type, PlaylistType::Formatted);
+    assert_eq!(playlist.total_duration, Some(TimeDelta::minutes(7)));
+    assert!(Playlist::from_str("Artist,Title\n", "friday", &ParseOptions::default()).is_err());
+
+    // Rekordbox exports UTF-16 text
+    let bytes = std::fs::read(TEST_FILES_DIR.join("rekordbox.txt"))?;
+    let from_bytes = Playlist::from_reader(std::io::Cursor::new(bytes), "rekordbox.txt", &ParseOptions::default())?;
+    let from_file = Playlist::new(&TEST_FILES_DIR.join("rekordbox.txt"))?;
+    assert_eq!(from_bytes.playlist_type, PlaylistType::Rekordbox);
+    assert_eq!(from_bytes.tracks, from_file.tracks);
+    Ok(())
+}